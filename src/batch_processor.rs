@@ -28,6 +28,8 @@ pub struct BatchConfig {
     pub verbose: bool,
     /// 是否安静模式
     pub quiet: bool,
+    /// 暴力破解设置：搜索空间、搜索策略、评估预算、二值化窗口以及配方的加载和保存路径
+    pub processing: ProcessingConfig,
 }
 
 impl Default for BatchConfig {
@@ -50,6 +52,7 @@ impl Default for BatchConfig {
             colored_output: true,
             verbose: false,
             quiet: false,
+            processing: ProcessingConfig::default(),
         }
     }
 }
@@ -153,7 +156,7 @@ pub struct BatchProcessor {
 impl BatchProcessor {
     /// 创建新的批量处理器
     pub fn new(config: BatchConfig) -> Result<Self, QRDecodeError> {
-        let decoder = BruteForceDecoder::new()?;
+        let decoder = BruteForceDecoder::from_processing_config(&config.processing)?;
         Ok(Self { config, decoder })
     }
 
@@ -271,6 +274,9 @@ impl BatchProcessor {
                             if let Some(points) = &qr_result.points {
                                 println!("      📍 位置: {:?}", points);
                             }
+                            if let Some(recipe) = &qr_result.recipe {
+                                println!("      📋 配方: {}", recipe.description());
+                            }
                         }
                     }
                 } else {
@@ -297,6 +303,14 @@ impl BatchProcessor {
             results.push(result);
         }
 
+        // 前面图像成功的配方已在处理后续图像时重放，这里连同成功次数一起保存
+        if let Some(path) = &self.config.processing.save_recipe {
+            self.decoder.recipes().save(path)?;
+            if !self.config.quiet {
+                println!("📋 {} 条变换配方已保存到: {}", self.decoder.recipes().len(), path.display());
+            }
+        }
+
         Ok(results)
     }
 
@@ -336,6 +350,9 @@ impl BatchProcessor {
                     if let Some(points) = &qr_result.points {
                         report.push_str(&format!("  坐标: {:?}\n", points));
                     }
+                    if let Some(recipe) = &qr_result.recipe {
+                        report.push_str(&format!("  配方: {}\n", recipe.description()));
+                    }
                 }
             } else if let Some(error) = &result.error {
                 report.push_str(&format!("错误: {}\n", error));
@@ -0,0 +1,280 @@
+//! 局部二值化模块
+//!
+//! 全局 Otsu 和固定参数的自适应高斯阈值在低对比度或光照不均的二维码上容易失败。
+//! 本模块提供 Niblack、Sauvola、Wolf-Jolion 三种基于局部均值和标准差的阈值方法，
+//! 以及多级 Otsu（把直方图分成多类后取其中一个分界），窗口大小均可调。
+
+use opencv::{
+    core::{self, Mat, Point, Scalar, Size, BORDER_REPLICATE, CV_32F, CV_8UC1},
+    imgproc::{self, cvt_color, COLOR_BGR2GRAY, THRESH_BINARY, THRESH_OTSU},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+
+/// 默认局部窗口大小（像素，奇数）
+pub const DEFAULT_BINARIZATION_WINDOW: i32 = 31;
+
+/// Sauvola 方法中标准差的动态范围
+const SAUVOLA_DYNAMIC_RANGE: f32 = 128.0;
+
+/// 二值化方法
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BinarizationMethod {
+    /// 全局 Otsu
+    Otsu,
+    /// Niblack：T = m + k·s
+    Niblack { window: i32, k: f64 },
+    /// Sauvola：T = m·(1 + k·(s/R − 1))
+    Sauvola { window: i32, k: f64 },
+    /// Wolf-Jolion：T = m − k·(1 − s/S)·(m − M)，S 为最大局部标准差，M 为最小灰度
+    Wolf { window: i32, k: f64 },
+    /// 多级 Otsu：分成 `classes` 类，以第 `level` 个分界二值化
+    MultiOtsu { classes: u8, level: u8 },
+}
+
+impl BinarizationMethod {
+    /// 获取二值化方法的描述
+    pub fn description(&self) -> String {
+        match self {
+            BinarizationMethod::Otsu => "Otsu".to_string(),
+            BinarizationMethod::Niblack { window, k } => format!("Niblack: 窗口 {} / k {:.2}", window, k),
+            BinarizationMethod::Sauvola { window, k } => format!("Sauvola: 窗口 {} / k {:.2}", window, k),
+            BinarizationMethod::Wolf { window, k } => format!("Wolf: 窗口 {} / k {:.2}", window, k),
+            BinarizationMethod::MultiOtsu { classes, level } => format!("多级 Otsu: {} 类 / 第 {} 分界", classes, level + 1),
+        }
+    }
+
+    /// 改用给定的局部窗口，Otsu 和多级 Otsu 不受影响
+    pub fn with_window(self, window: i32) -> Self {
+        match self {
+            BinarizationMethod::Niblack { k, .. } => BinarizationMethod::Niblack { window, k },
+            BinarizationMethod::Sauvola { k, .. } => BinarizationMethod::Sauvola { window, k },
+            BinarizationMethod::Wolf { k, .. } => BinarizationMethod::Wolf { window, k },
+            other => other,
+        }
+    }
+
+    /// 按给定窗口生成一组常用的局部二值化方法
+    pub fn local_family(window: i32) -> Vec<BinarizationMethod> {
+        vec![
+            BinarizationMethod::Sauvola { window, k: 0.34 },
+            BinarizationMethod::Wolf { window, k: 0.5 },
+            BinarizationMethod::Niblack { window, k: -0.2 },
+            BinarizationMethod::MultiOtsu { classes: 3, level: 0 },
+            BinarizationMethod::MultiOtsu { classes: 3, level: 1 },
+        ]
+    }
+}
+
+/// 对图像二值化，返回单通道 0/255 图像
+pub fn binarize(image: &Mat, method: BinarizationMethod) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+
+    match method {
+        BinarizationMethod::Otsu => {
+            let mut result = Mat::default();
+            imgproc::threshold(&gray, &mut result, 0.0, 255.0, THRESH_BINARY | THRESH_OTSU)
+                .map_err(|e| QRDecodeError::image_processing_error(format!("Otsu 二值化失败: {}", e)))?;
+            Ok(result)
+        }
+        BinarizationMethod::MultiOtsu { classes, level } => {
+            let histogram = histogram(gray.data_bytes()?);
+            let thresholds = multi_otsu_thresholds(&histogram, classes as usize);
+            let threshold = thresholds
+                .get(level as usize)
+                .or(thresholds.last())
+                .copied()
+                .unwrap_or(128);
+            let mut result = Mat::default();
+            imgproc::threshold(&gray, &mut result, threshold as f64, 255.0, THRESH_BINARY)
+                .map_err(|e| QRDecodeError::image_processing_error(format!("多级 Otsu 二值化失败: {}", e)))?;
+            Ok(result)
+        }
+        BinarizationMethod::Niblack { window, .. }
+        | BinarizationMethod::Sauvola { window, .. }
+        | BinarizationMethod::Wolf { window, .. } => {
+            let (means, stds) = local_statistics(&gray, window)?;
+            let pixels = gray.data_bytes()?;
+            let thresholds = local_thresholds(method, &means, &stds, pixels);
+
+            let mut result = Mat::new_rows_cols_with_default(gray.rows(), gray.cols(), CV_8UC1, Scalar::all(0.0))?;
+            let output = result.data_bytes_mut()?;
+            for ((out, &pixel), threshold) in output.iter_mut().zip(pixels).zip(thresholds) {
+                *out = if pixel as f32 > threshold { 255 } else { 0 };
+            }
+            Ok(result)
+        }
+    }
+}
+
+/// 计算每个像素窗口内的均值和标准差
+fn local_statistics(gray: &Mat, window: i32) -> Result<(Vec<f32>, Vec<f32>)> {
+    let window = if window % 2 == 0 { window + 1 } else { window.max(3) };
+    let size = Size::new(window, window);
+
+    let mut float = Mat::default();
+    gray.convert_to(&mut float, CV_32F, 1.0, 0.0)?;
+    let mut squared = Mat::default();
+    core::multiply(&float, &float, &mut squared, 1.0, -1)?;
+
+    let mut mean = Mat::default();
+    imgproc::box_filter(&float, &mut mean, -1, size, Point::new(-1, -1), true, BORDER_REPLICATE)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("局部均值计算失败: {}", e)))?;
+    let mut mean_sq = Mat::default();
+    imgproc::box_filter(&squared, &mut mean_sq, -1, size, Point::new(-1, -1), true, BORDER_REPLICATE)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("局部方差计算失败: {}", e)))?;
+
+    let means = mean.data_typed::<f32>()?.to_vec();
+    let stds = means
+        .iter()
+        .zip(mean_sq.data_typed::<f32>()?)
+        .map(|(&m, &sq)| (sq - m * m).max(0.0).sqrt())
+        .collect();
+    Ok((means, stds))
+}
+
+/// 根据局部均值和标准差计算每个像素的阈值
+pub fn local_thresholds(method: BinarizationMethod, means: &[f32], stds: &[f32], pixels: &[u8]) -> Vec<f32> {
+    match method {
+        BinarizationMethod::Niblack { k, .. } => {
+            let k = k as f32;
+            means.iter().zip(stds).map(|(&m, &s)| m + k * s).collect()
+        }
+        BinarizationMethod::Sauvola { k, .. } => {
+            let k = k as f32;
+            means
+                .iter()
+                .zip(stds)
+                .map(|(&m, &s)| m * (1.0 + k * (s / SAUVOLA_DYNAMIC_RANGE - 1.0)))
+                .collect()
+        }
+        BinarizationMethod::Wolf { k, .. } => {
+            let k = k as f32;
+            let min_gray = pixels.iter().copied().min().unwrap_or(0) as f32;
+            let max_std = stds.iter().copied().fold(0.0f32, f32::max).max(f32::EPSILON);
+            means
+                .iter()
+                .zip(stds)
+                .map(|(&m, &s)| m - k * (1.0 - s / max_std) * (m - min_gray))
+                .collect()
+        }
+        BinarizationMethod::Otsu | BinarizationMethod::MultiOtsu { .. } => {
+            vec![128.0; means.len()]
+        }
+    }
+}
+
+/// 灰度直方图
+fn histogram(pixels: &[u8]) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for &pixel in pixels {
+        histogram[pixel as usize] += 1;
+    }
+    histogram
+}
+
+/// 多级 Otsu 阈值
+///
+/// 用动态规划求使类间方差最大的 `classes - 1` 个分界，返回升序阈值：
+/// 灰度大于阈值的像素属于更亮的一类。
+pub fn multi_otsu_thresholds(histogram: &[u64; 256], classes: usize) -> Vec<u8> {
+    let classes = classes.clamp(2, 8);
+
+    // 前缀和：权重与一阶矩
+    let mut weight = [0f64; 257];
+    let mut moment = [0f64; 257];
+    for i in 0..256 {
+        weight[i + 1] = weight[i] + histogram[i] as f64;
+        moment[i + 1] = moment[i] + i as f64 * histogram[i] as f64;
+    }
+    // 区间 [a, b) 的 w·μ²，类间方差最大等价于该项之和最大
+    let score = |a: usize, b: usize| -> f64 {
+        let w = weight[b] - weight[a];
+        if w <= 0.0 {
+            0.0
+        } else {
+            let m = moment[b] - moment[a];
+            m * m / w
+        }
+    };
+
+    // best[c][b]：前 b 个灰度级分成 c 类的最优得分
+    let mut best = vec![vec![f64::MIN; 257]; classes + 1];
+    let mut split = vec![vec![0usize; 257]; classes + 1];
+    for (b, slot) in best[1].iter_mut().enumerate().skip(1) {
+        *slot = score(0, b);
+    }
+    for c in 2..=classes {
+        for b in c..=256 {
+            for a in (c - 1)..b {
+                let candidate = best[c - 1][a] + score(a, b);
+                if candidate > best[c][b] {
+                    best[c][b] = candidate;
+                    split[c][b] = a;
+                }
+            }
+        }
+    }
+
+    let mut thresholds = Vec::with_capacity(classes - 1);
+    let mut end = 256;
+    for c in (2..=classes).rev() {
+        end = split[c][end];
+        thresholds.push((end - 1) as u8);
+    }
+    thresholds.reverse();
+    thresholds
+}
+
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    let gray = if image.channels() == 1 {
+        image.try_clone()?
+    } else {
+        let mut gray = Mat::default();
+        cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+        gray
+    };
+    Ok(gray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_otsu_trimodal() {
+        let mut histogram = [0u64; 256];
+        for (centre, count) in [(30usize, 500u64), (120, 300), (220, 400)] {
+            for offset in 0..10 {
+                histogram[centre - 5 + offset] = count / 10;
+            }
+        }
+        let thresholds = multi_otsu_thresholds(&histogram, 3);
+        assert_eq!(thresholds.len(), 2);
+        assert!((34..115).contains(&thresholds[0]), "{:?}", thresholds);
+        assert!((124..215).contains(&thresholds[1]), "{:?}", thresholds);
+
+        let two = multi_otsu_thresholds(&histogram, 2);
+        assert_eq!(two.len(), 1);
+    }
+
+    #[test]
+    fn test_local_threshold_formulas() {
+        let means = [100.0, 100.0];
+        let stds = [0.0, 64.0];
+        let pixels = [20u8, 200];
+
+        let niblack = local_thresholds(BinarizationMethod::Niblack { window: 15, k: -0.2 }, &means, &stds, &pixels);
+        assert_eq!(niblack, vec![100.0, 87.2]);
+
+        let sauvola = local_thresholds(BinarizationMethod::Sauvola { window: 15, k: 0.5 }, &means, &stds, &pixels);
+        assert_eq!(sauvola, vec![50.0, 75.0]);
+
+        // 局部对比度最大的像素阈值等于均值，平坦区域向最暗灰度靠拢
+        let wolf = local_thresholds(BinarizationMethod::Wolf { window: 15, k: 0.5 }, &means, &stds, &pixels);
+        assert_eq!(wolf, vec![60.0, 100.0]);
+    }
+}
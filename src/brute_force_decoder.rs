@@ -6,12 +6,18 @@ use opencv::{
     imgproc::{self, THRESH_BINARY, THRESH_OTSU, INTER_LINEAR},
     prelude::*,
 };
-use rand::seq::SliceRandom;
 use rand::thread_rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 use crate::{
+    binarization::{self, BinarizationMethod},
     error::QRDecodeError,
+    geometry::{GeometricTransform, Mirror},
+    illumination::{self, BackgroundEstimate},
+    recipe::RecipeBook,
+    search_space::{self, Channel, SearchAxis},
+    search_strategy::{self, Evaluation, SearchStrategy},
     types::{QRCodeResult, QRPosition},
     wechat_qr_decoder::WeChatQRDecoder,
 };
@@ -19,63 +25,222 @@ use crate::{
 /// 暴力破解配置
 #[derive(Debug, Clone)]
 pub struct BruteForceConfig {
-    /// 对比度选项 [2, 1, 3]
-    pub contrast_options: Vec<f64>,
-    /// 亮度选项 [-75, 75, -50, -25, -10, 0, 25, 50]
-    pub brightness_options: Vec<i32>,
-    /// 模糊选项 [-7, -3, 7, 3, -1, 5, 9, 11, 13, 15, 17, 19, 21, 23, 25]
-    pub blur_options: Vec<i32>,
-    /// 缩放选项 [0.2, 0.5, 0.7, 0.9, 1.3, 2.0]
-    pub scale_options: Vec<f64>,
+    /// 搜索空间：按顺序排列的参数轴，排在前面的轴变化最慢
+    pub axes: Vec<SearchAxis>,
     /// 重复检测距离阈值
     pub duplicate_threshold: f64,
-    /// 是否随机化参数组合
+    /// 是否随机化参数组合（穷举时打乱顺序）
     pub randomize: bool,
+    /// 搜索策略
+    pub strategy: SearchStrategy,
+    /// 最多评估的参数组合数，`None` 时使用策略的默认预算
+    pub budget: Option<usize>,
 }
 
 impl Default for BruteForceConfig {
     fn default() -> Self {
         Self {
-            contrast_options: vec![2.0, 1.0, 3.0],
-            brightness_options: vec![-75, 75, -50, -25, -10, 0, 25, 50],
-            blur_options: vec![-7, -3, 7, 3, -1, 5, 9, 11, 13, 15, 17, 19, 21, 23, 25],
-            scale_options: vec![0.2, 0.5, 0.7, 0.9, 1.3, 2.0],
+            axes: search_space::default_axes(),
             duplicate_threshold: 10.0,
             randomize: false,
+            strategy: SearchStrategy::Exhaustive,
+            budget: None,
         }
     }
 }
 
+impl BruteForceConfig {
+    /// 按对比度、亮度、模糊和缩放四组选项构造配置
+    ///
+    /// 各组选项成为同名参数轴，按缩放、对比度、亮度、模糊排列，二值化轴为 Otsu 和不二值化。
+    pub fn from_options(
+        contrast_options: Vec<f64>,
+        brightness_options: Vec<i32>,
+        blur_options: Vec<i32>,
+        scale_options: Vec<f64>,
+    ) -> Self {
+        Self {
+            axes: vec![
+                SearchAxis::Scale(scale_options),
+                SearchAxis::Contrast(contrast_options),
+                SearchAxis::Brightness(brightness_options),
+                SearchAxis::Blur(blur_options),
+                SearchAxis::Binarization(vec![Some(BinarizationMethod::Otsu), None]),
+            ],
+            ..Self::default()
+        }
+    }
+
+    /// 对比度轴的取值，搜索空间中没有该轴时为空
+    pub fn contrast_options(&self) -> &[f64] {
+        self.axes.iter().find_map(|axis| match axis {
+            SearchAxis::Contrast(values) => Some(values.as_slice()),
+            _ => None,
+        }).unwrap_or(&[])
+    }
+
+    /// 亮度轴的取值，搜索空间中没有该轴时为空
+    pub fn brightness_options(&self) -> &[i32] {
+        self.axes.iter().find_map(|axis| match axis {
+            SearchAxis::Brightness(values) => Some(values.as_slice()),
+            _ => None,
+        }).unwrap_or(&[])
+    }
+
+    /// 模糊轴的取值，搜索空间中没有该轴时为空
+    pub fn blur_options(&self) -> &[i32] {
+        self.axes.iter().find_map(|axis| match axis {
+            SearchAxis::Blur(values) => Some(values.as_slice()),
+            _ => None,
+        }).unwrap_or(&[])
+    }
+
+    /// 缩放轴的取值，搜索空间中没有该轴时为空
+    pub fn scale_options(&self) -> &[f64] {
+        self.axes.iter().find_map(|axis| match axis {
+            SearchAxis::Scale(values) => Some(values.as_slice()),
+            _ => None,
+        }).unwrap_or(&[])
+    }
+}
+
 /// 变换参数
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransformParams {
     pub contrast: f64,
     pub brightness: i32,
     pub blur: i32,
     pub scale: f64,
-    pub grayscale: bool,
+    pub channel: Channel,
     pub binary: bool,
+    pub binarization: BinarizationMethod,
+    pub illumination: Option<BackgroundEstimate>,
+    pub mirror: Mirror,
+    pub rotation: i32,
+}
+
+impl Default for TransformParams {
+    /// 不做处理的中性参数，搜索空间中未列出的轴取这些值
+    fn default() -> Self {
+        Self {
+            contrast: 1.0,
+            brightness: 0,
+            blur: 0,
+            scale: 1.0,
+            channel: Channel::Gray,
+            binary: false,
+            binarization: BinarizationMethod::Otsu,
+            illumination: None,
+            mirror: Mirror::None,
+            rotation: 0,
+        }
+    }
+}
+
+impl TransformParams {
+    /// 获取参数组合的描述
+    pub fn description(&self) -> String {
+        format!(
+            "scale:{}, channel:{}, contrast:{}, brightness:{}, blur:{}, binary:{} ({}), illumination:{}, mirror:{}, rotation:{}°",
+            self.scale, self.channel.description(), self.contrast, self.brightness, self.blur, self.binary,
+            self.binarization.description(),
+            self.illumination.map(|e| e.description()).unwrap_or_else(|| "无".to_string()),
+            self.mirror.description(), self.rotation
+        )
+    }
 }
 
 /// 暴力破解解码器
 pub struct BruteForceDecoder {
     config: BruteForceConfig,
     decoder: WeChatQRDecoder,
+    /// 上一次搜索评估的参数组合数
+    evaluations: usize,
+    /// 成功过的变换配方，搜索前先按成功次数重放
+    recipes: RecipeBook,
 }
 
 impl BruteForceDecoder {
     /// 创建新的暴力破解解码器
     pub fn new() -> Result<Self, QRDecodeError> {
+        Self::with_config(BruteForceConfig::default())
+    }
+
+    /// 使用给定配置创建暴力破解解码器
+    pub fn with_config(config: BruteForceConfig) -> Result<Self, QRDecodeError> {
         // 创建默认的处理配置
         let processing_config = crate::types::ProcessingConfig::default();
         let decoder = WeChatQRDecoder::new(&processing_config)
             .map_err(|e| QRDecodeError::decode_error(format!("创建解码器失败: {:?}", e)))?;
         Ok(Self {
-            config: BruteForceConfig::default(),
+            config,
             decoder,
+            evaluations: 0,
+            recipes: RecipeBook::default(),
         })
     }
 
+    /// 按处理配置的搜索空间、搜索策略、评估预算和配方文件创建暴力破解解码器
+    pub fn from_processing_config(config: &crate::types::ProcessingConfig) -> Result<Self, QRDecodeError> {
+        let axes = search_space::resolve_search_space(
+            config.search_space.as_deref(),
+            &config.search_axes,
+            config.binarization_window,
+        )?;
+        let recipes = match &config.recipe {
+            Some(path) => RecipeBook::load(path)?,
+            None => RecipeBook::default(),
+        };
+        let decoder = Self::with_config(BruteForceConfig {
+            axes,
+            strategy: config.search,
+            budget: config.search_budget,
+            ..BruteForceConfig::default()
+        })?;
+        Ok(decoder.with_recipes(recipes))
+    }
+
+    /// 设置局部二值化窗口大小，二值化轴中的局部方法改用该窗口
+    pub fn with_binarization_window(mut self, window: i32) -> Self {
+        for axis in &mut self.config.axes {
+            if let SearchAxis::Binarization(values) = axis {
+                for method in values.iter_mut().flatten() {
+                    *method = method.with_window(window);
+                }
+            }
+        }
+        self
+    }
+
+    /// 设置搜索空间
+    pub fn with_axes(mut self, axes: Vec<SearchAxis>) -> Self {
+        self.config.axes = axes;
+        self
+    }
+
+    /// 设置搜索策略和评估预算
+    pub fn with_search(mut self, strategy: SearchStrategy, budget: Option<usize>) -> Self {
+        self.config.strategy = strategy;
+        self.config.budget = budget;
+        self
+    }
+
+    /// 设置预先加载的变换配方
+    pub fn with_recipes(mut self, recipes: RecipeBook) -> Self {
+        self.recipes = recipes;
+        self
+    }
+
+    /// 上一次搜索评估的参数组合数
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// 已加载和本次运行中成功的变换配方
+    pub fn recipes(&self) -> &RecipeBook {
+        &self.recipes
+    }
+
     /// 从文件路径解码二维码（批量处理接口）
     pub fn decode_with_brute_force(
         &mut self,
@@ -96,9 +261,12 @@ impl BruteForceDecoder {
             return Err(QRDecodeError::invalid_input("图像为空".to_string()));
         }
         
-        // 执行暴力破解解码
-        let qr_results = self.detect_and_decode(&image)
-            .map_err(|e| QRDecodeError::decode_error(format!("解码失败: {:?}", e)))?;
+        // 先重放已有配方，都失败时再执行暴力破解解码
+        let mut qr_results = self.decode_with_recipes(&image)?;
+        if qr_results.is_empty() {
+            qr_results = self.detect_and_decode(&image)
+                .map_err(|e| QRDecodeError::decode_error(format!("解码失败: {:?}", e)))?;
+        }
         
         // 转换结果格式
         let mut results = Vec::new();
@@ -111,6 +279,7 @@ impl BruteForceDecoder {
                     (qr_result.position.x as f32 + qr_result.position.width as f32, qr_result.position.y as f32 + qr_result.position.height as f32),
                     (qr_result.position.x as f32, qr_result.position.y as f32 + qr_result.position.height as f32),
                 ]),
+                recipe: qr_result.recipe,
             };
             results.push(result);
         }
@@ -135,48 +304,51 @@ impl BruteForceDecoder {
         false
     }
 
-    /// 生成所有参数组合
-    fn generate_param_combinations(&self) -> Vec<TransformParams> {
-        let mut combinations = Vec::new();
-        
-        // 完全匹配Python版本的参数范围
-        let contrast_options = vec![2.0, 1.0, 3.0];
-        let brightness_options = vec![-75, 75, -50, -25, -10, 0, 25, 50];
-        let blur_options = vec![-7, -3, 7, 3, -1, 5, 9, 11, 13, 15, 17, 19, 21, 23, 25];
-        let scale_options = vec![0.2, 0.5, 0.7, 0.9, 1.3, 2.0];
-        
-        for &scale in &scale_options {
-            for &grayscale in &[true] { // Python版本固定使用灰度
-                for &contrast in &contrast_options {
-                    for &brightness in &brightness_options {
-                        for &blur in &blur_options {
-                            for &binary in &[true, false] {
-                                combinations.push(TransformParams {
-                                    contrast,
-                                    brightness,
-                                    blur,
-                                    scale,
-                                    grayscale,
-                                    binary,
-                                });
-                            }
-                        }
-                    }
+    /// 各参数轴的取值个数
+    fn axis_sizes(&self) -> Vec<usize> {
+        self.config.axes.iter().map(SearchAxis::len).collect()
+    }
+
+    /// 按各轴下标取出参数组合，搜索空间中未列出的轴取中性值
+    fn params_at(&self, index: &[usize]) -> TransformParams {
+        let mut params = TransformParams::default();
+        for (axis, &i) in self.config.axes.iter().zip(index) {
+            match axis {
+                SearchAxis::Mirror(values) => params.mirror = values[i],
+                SearchAxis::Rotation(values) => params.rotation = values[i],
+                SearchAxis::Illumination(values) => params.illumination = values[i],
+                SearchAxis::Scale(values) => params.scale = values[i],
+                SearchAxis::Channel(values) => params.channel = values[i],
+                SearchAxis::Contrast(values) => params.contrast = values[i],
+                SearchAxis::Brightness(values) => params.brightness = values[i],
+                SearchAxis::Blur(values) => params.blur = values[i],
+                SearchAxis::Binarization(values) => {
+                    params.binary = values[i].is_some();
+                    params.binarization = values[i].unwrap_or(BinarizationMethod::Otsu);
                 }
             }
         }
-        
-        combinations
+        params
     }
 
     /// 应用图像变换
+    ///
+    /// 同时返回原图到变换后图像的几何映射（镜像、旋转和缩放），用于把检测到的坐标映射回原图。
     fn apply_transform(
         &self,
         image: &Mat,
         params: &TransformParams,
         invert: bool,
-    ) -> Result<Mat, QRDecodeError> {
-        let mut result = image.clone();
+    ) -> Result<(Mat, GeometricTransform), QRDecodeError> {
+        // 镜像和旋转
+        let geometry = GeometricTransform::mirror_rotation(
+            params.mirror,
+            params.rotation as f64,
+            image.cols(),
+            image.rows(),
+        );
+        let mut result = geometry.apply(image)?;
+        let geometry = geometry.scaled(params.scale);
         
         // 缩放处理
         if params.scale != 1.0 {
@@ -208,16 +380,27 @@ impl BruteForceDecoder {
             }
         }
         
-        // 灰度转换
-        if params.grayscale {
+        // 灰度转换或取单个颜色通道
+        if result.channels() > 1 {
             let mut temp = Mat::default();
-            imgproc::cvt_color(&result, &mut temp, imgproc::COLOR_BGR2GRAY, 0, opencv::core::AlgorithmHint::ALGO_HINT_DEFAULT)
-                 .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+            match params.channel.bgr_index() {
+                None => imgproc::cvt_color(&result, &mut temp, imgproc::COLOR_BGR2GRAY, 0, opencv::core::AlgorithmHint::ALGO_HINT_DEFAULT)
+                    .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?,
+                Some(index) => opencv::core::extract_channel(&result, &mut temp, index)
+                    .map_err(|e| QRDecodeError::image_processing_error(format!("提取 {} 通道失败: {}", params.channel.description(), e)))?,
+            }
             result = temp;
         }
         
-        // 二值化处理 (使用THRESH_BINARY | THRESH_OTSU匹配Python版本)
-        if params.binary {
+        // 光照校正（在灰度图上估计背景并除去）
+        if let Some(estimate) = params.illumination {
+            result = illumination::normalize_illumination(&result, estimate)?;
+        }
+        
+        // 二值化处理 (Otsu 使用THRESH_BINARY | THRESH_OTSU匹配Python版本，其余为局部二值化)
+        if params.binary && params.binarization != BinarizationMethod::Otsu {
+            result = binarization::binarize(&result, params.binarization)?;
+        } else if params.binary {
             let mut temp = opencv::core::Mat::default();
             opencv::imgproc::threshold(&result, &mut temp, 0.0, 255.0, 
                 opencv::imgproc::THRESH_BINARY | opencv::imgproc::THRESH_OTSU)
@@ -233,62 +416,96 @@ impl BruteForceDecoder {
             result = temp;
         }
         
-        Ok(result)
+        Ok((result, geometry))
     }
 
 
 
+    /// 按成功次数从高到低重放已有配方，第一条解码成功的配方即返回
+    ///
+    /// 没有配方或全部失败时返回空列表。
+    pub fn decode_with_recipes(&mut self, image: &Mat) -> Result<Vec<QRCodeResult>, QRDecodeError> {
+        for params in self.recipes.ordered() {
+            let Ok((processed_image, geometry)) = self.apply_transform(image, &params, false) else {
+                continue; // 忽略变换错误
+            };
+            let Ok(results) = self.decoder.decode_qr_codes(&processed_image) else {
+                continue;
+            };
+            if results.is_empty() {
+                continue;
+            }
+            
+            println!("📋 配方重放检测到 {} 个二维码 ({})", results.len(), params.description());
+            let mut all_results = Vec::new();
+            for result in results.into_iter().map(|r| geometry.map_result(r).with_recipe(params.clone())) {
+                if !self.is_duplicate(&result, &all_results) {
+                    all_results.push(result);
+                }
+            }
+            self.recipes.record(&params);
+            return Ok(all_results);
+        }
+        Ok(Vec::new())
+    }
+
     /// 检测和解码二维码
     pub fn detect_and_decode(&mut self, image: &Mat) -> Result<Vec<QRCodeResult>, QRDecodeError> {
         let mut all_results = Vec::new();
-        let mut combinations = self.generate_param_combinations();
-        
-        // 随机化处理（如果启用）
-        if self.config.randomize {
-            use rand::seq::SliceRandom;
-            let mut rng = rand::thread_rng();
-            combinations.shuffle(&mut rng);
-        }
+        let sizes = self.axis_sizes();
+        let total: usize = sizes.iter().product();
+        let strategy = self.config.strategy;
+        let budget = self.config.budget.or(strategy.default_budget());
+        let randomize = self.config.randomize;
+        let planned = budget.map_or(total, |budget| budget.min(total));
         
-        println!("开始暴力破解，共{}种参数组合", combinations.len());
+        println!("开始暴力破解（{}），共{}种参数组合，计划评估{}种", strategy.description(), total, planned);
         
-        for (i, params) in combinations.iter().enumerate() {
-            if i % 100 == 0 {
-                println!("进度: {}/{}", i, combinations.len());
+        let mut evaluated = 0;
+        let mut winner = None;
+        let outcome = search_strategy::search(strategy, &sizes, budget, randomize, &mut thread_rng(), |index| {
+            if evaluated % 100 == 0 {
+                println!("进度: {}/{}", evaluated, planned);
             }
+            evaluated += 1;
             
-            match self.apply_transform(image, params, false) {
-                Ok(processed_image) => {
-                    match self.decoder.decode_qr_codes(&processed_image) {
-                        Ok(results) => {
-                            if !results.is_empty() {
-                                println!("✅ 参数组合 {} 检测到 {} 个二维码 (scale:{}, contrast:{}, brightness:{}, blur:{}, binary:{})", 
-                                    i, results.len(), params.scale, params.contrast, params.brightness, params.blur, params.binary);
-                                
-                                // 添加去重逻辑
-                                for result in results {
-                                    if !self.is_duplicate(&result, &all_results) {
-                                        all_results.push(result);
-                                    }
-                                }
-                                
-                                // 找到二维码后立即返回结果，不再继续尝试其他参数组合
-                                if !all_results.is_empty() {
-                                    println!("🎯 成功找到 {} 个二维码，停止暴力破解", all_results.len());
-                                    return Ok(all_results);
-                                }
-                            }
+            let params = self.params_at(index);
+            let Ok((processed_image, geometry)) = self.apply_transform(image, &params, false) else {
+                return Evaluation::MISS; // 忽略变换错误
+            };
+            if let Ok(results) = self.decoder.decode_qr_codes(&processed_image) {
+                if !results.is_empty() {
+                    println!("✅ 第 {} 次评估检测到 {} 个二维码 ({})", evaluated, results.len(), params.description());
+                    
+                    // 坐标映射回原图后再去重，并附上成功的配方
+                    for result in results.into_iter().map(|r| geometry.map_result(r).with_recipe(params.clone())) {
+                        if !self.is_duplicate(&result, &all_results) {
+                            all_results.push(result);
                         }
-                        Err(_) => {} // 忽略解码错误
                     }
+                    // 找到二维码后立即停止，不再继续尝试其他参数组合
+                    winner = Some(params);
+                    return Evaluation::DECODED;
                 }
-                Err(_) => {} // 忽略变换错误
             }
+            
+            // 未解码时按定位图形的命中数打分，供粗到细和老虎机策略参考
+            let score = if strategy.uses_scores() {
+                search_strategy::finder_score(&processed_image).unwrap_or(0.0)
+            } else {
+                0.0
+            };
+            Evaluation { decoded: false, score }
+        });
+        self.evaluations = outcome.evaluations;
+        if let Some(params) = &winner {
+            self.recipes.record(params);
         }
         
-        // 如果所有参数组合都尝试完了还没找到二维码
         if all_results.is_empty() {
-            println!("❌ 暴力破解完成，未找到任何二维码");
+            println!("❌ 暴力破解完成，评估 {} 种参数组合，未找到任何二维码", outcome.evaluations);
+        } else {
+            println!("🎯 成功找到 {} 个二维码，共评估 {} 种参数组合，停止暴力破解", all_results.len(), outcome.evaluations);
         }
         
         Ok(all_results)
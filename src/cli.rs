@@ -2,8 +2,15 @@ use clap::{Arg, ArgMatches, Command};
 use std::path::PathBuf;
 use std::env;
 
+use crate::binarization::DEFAULT_BINARIZATION_WINDOW;
+use crate::enhanced_processor::TransformType;
 use crate::error::{QRDecodeError, Result};
-use crate::types::OutputFormat;
+use crate::local_contrast::{DEFAULT_CLAHE_CLIP_LIMIT, DEFAULT_CLAHE_TILE_SIZE};
+use crate::pyramid::{DEFAULT_MAX_SCALE, DEFAULT_TILE_SIZE};
+use crate::tiled::{DEFAULT_MAX_MEMORY_MB, DEFAULT_SCAN_OVERLAP, DEFAULT_SCAN_TILE};
+use crate::search_space::SearchAxis;
+use crate::search_strategy::SearchStrategy;
+use crate::types::{MatrixExportFormat, OutputFormat};
 
 /// 命令行参数结构
 #[derive(Debug, Clone)]
@@ -16,6 +23,8 @@ pub struct Args {
     pub output_format: OutputFormat,
     /// 是否启用图像预处理
     pub preprocess: bool,
+    /// 是否根据图像质量评估自动选择处理策略
+    pub auto: bool,
     /// 是否显示详细信息
     pub verbose: bool,
     /// 是否静默模式
@@ -52,6 +61,78 @@ pub struct Args {
     pub show_progress: bool,
     /// 是否启用彩色输出
     pub colored_output: bool,
+    /// 用户提供的四个角点 (左上、右上、右下、左下)
+    pub corners: Option<Vec<(f32, f32)>>,
+    /// 每边模块数 (由 --modules 或 --qr-version 指定)
+    pub module_count: Option<usize>,
+    /// 模块矩阵导出格式
+    pub matrix_export: Option<MatrixExportFormat>,
+    /// 模块矩阵导出路径
+    pub matrix_output_path: Option<PathBuf>,
+    /// 输入是否为文本模块网格
+    pub grid_input: bool,
+    /// 歧义模块擦除阈值 (0 表示关闭)
+    pub ambiguity_margin: f32,
+    /// 遮挡掩码图像路径
+    pub occlusion_mask: Option<PathBuf>,
+    /// 是否把检测到的反光区域视为擦除
+    pub glare_erasures: bool,
+    /// 是否把中心 logo 区域视为擦除
+    pub logo_erasures: bool,
+    /// 是否检查隐藏数据和非标准填充
+    pub inspect: bool,
+    /// 是否按颜色通道分层解码
+    pub color_layers: bool,
+    /// 是否多尺度金字塔分块检测
+    pub pyramid: bool,
+    /// 金字塔最大放大倍数
+    pub pyramid_max_scale: f64,
+    /// 金字塔图块边长
+    pub pyramid_tile: i32,
+    /// 是否按行带分块扫描大图
+    pub tiled: bool,
+    /// 分块扫描的图块边长
+    pub tile_size: i32,
+    /// 相邻图块的重叠宽度
+    pub tile_overlap: i32,
+    /// 分块扫描的内存上限（MB）
+    pub max_memory_mb: usize,
+    /// 预处理时是否使用 CLAHE
+    pub clahe: bool,
+    /// CLAHE 限幅
+    pub clahe_clip_limit: f64,
+    /// CLAHE 网格大小
+    pub clahe_tile_size: i32,
+    /// 局部色调映射强度 (可选)
+    pub tone_mapping: Option<f64>,
+    /// 局部二值化窗口大小
+    pub binarization_window: i32,
+    /// 增强解码的流水线文件
+    pub pipeline: Option<PathBuf>,
+    /// 流水线配置名
+    pub profile: Option<String>,
+    /// 命令行指定的变换链
+    pub transforms: Option<Vec<TransformType>>,
+    /// 是否只尝试命令行指定的变换链
+    pub transforms_only: bool,
+    /// 最多尝试的组合变换链数（未指定时只在 --auto 或 --profile 下启用）
+    pub max_compositions: Option<usize>,
+    /// 增强解码的时间预算（秒）
+    pub time_budget: Option<f64>,
+    /// 跨运行的变换统计文件
+    pub transform_stats: Option<PathBuf>,
+    /// 暴力破解的搜索策略
+    pub search: SearchStrategy,
+    /// 暴力破解最多评估的参数组合数
+    pub search_budget: Option<usize>,
+    /// 暴力破解搜索空间文件
+    pub search_space: Option<PathBuf>,
+    /// 命令行替换或追加的搜索轴
+    pub search_axes: Vec<SearchAxis>,
+    /// 预先重放的变换配方文件
+    pub recipe: Option<PathBuf>,
+    /// 保存成功配方的文件
+    pub save_recipe: Option<PathBuf>,
 }
 
 impl Args {
@@ -86,6 +167,7 @@ impl Args {
             output_path: None,
             output_format: OutputFormat::Text,
             preprocess: false,
+            auto: false,
             verbose: false,
             quiet: false,
             show_position: false,
@@ -104,6 +186,42 @@ impl Args {
             report_output: None,
             show_progress: true,
             colored_output: true,
+            corners: None,
+            module_count: None,
+            matrix_export: None,
+            matrix_output_path: None,
+            grid_input: false,
+            ambiguity_margin: 0.0,
+            occlusion_mask: None,
+            glare_erasures: false,
+            logo_erasures: false,
+            inspect: false,
+            color_layers: false,
+            pyramid: false,
+            pyramid_max_scale: DEFAULT_MAX_SCALE,
+            pyramid_tile: DEFAULT_TILE_SIZE,
+            tiled: false,
+            tile_size: DEFAULT_SCAN_TILE,
+            tile_overlap: DEFAULT_SCAN_OVERLAP,
+            max_memory_mb: DEFAULT_MAX_MEMORY_MB,
+            clahe: false,
+            clahe_clip_limit: DEFAULT_CLAHE_CLIP_LIMIT,
+            clahe_tile_size: DEFAULT_CLAHE_TILE_SIZE,
+            tone_mapping: None,
+            binarization_window: DEFAULT_BINARIZATION_WINDOW,
+            pipeline: None,
+            profile: None,
+            transforms: None,
+            transforms_only: false,
+            max_compositions: None,
+            time_budget: None,
+            transform_stats: None,
+            search: SearchStrategy::Exhaustive,
+            search_budget: None,
+            search_space: None,
+            search_axes: Vec::new(),
+            recipe: None,
+            save_recipe: None,
         }
     }
     
@@ -114,6 +232,7 @@ impl Args {
             output_path: None,
             output_format: OutputFormat::Text,
             preprocess: false,
+            auto: false,
             verbose: false,
             quiet: false,
             show_position: false,
@@ -132,6 +251,42 @@ impl Args {
             report_output: None,
             show_progress: true,
             colored_output: true,
+            corners: None,
+            module_count: None,
+            matrix_export: None,
+            matrix_output_path: None,
+            grid_input: false,
+            ambiguity_margin: 0.0,
+            occlusion_mask: None,
+            glare_erasures: false,
+            logo_erasures: false,
+            inspect: false,
+            color_layers: false,
+            pyramid: false,
+            pyramid_max_scale: DEFAULT_MAX_SCALE,
+            pyramid_tile: DEFAULT_TILE_SIZE,
+            tiled: false,
+            tile_size: DEFAULT_SCAN_TILE,
+            tile_overlap: DEFAULT_SCAN_OVERLAP,
+            max_memory_mb: DEFAULT_MAX_MEMORY_MB,
+            clahe: false,
+            clahe_clip_limit: DEFAULT_CLAHE_CLIP_LIMIT,
+            clahe_tile_size: DEFAULT_CLAHE_TILE_SIZE,
+            tone_mapping: None,
+            binarization_window: DEFAULT_BINARIZATION_WINDOW,
+            pipeline: None,
+            profile: None,
+            transforms: None,
+            transforms_only: false,
+            max_compositions: None,
+            time_budget: None,
+            transform_stats: None,
+            search: SearchStrategy::Exhaustive,
+            search_budget: None,
+            search_space: None,
+            search_axes: Vec::new(),
+            recipe: None,
+            save_recipe: None,
         }
     }
     
@@ -172,6 +327,12 @@ impl Args {
                     .help("启用图像预处理")
                     .action(clap::ArgAction::SetTrue)
             )
+            .arg(
+                Arg::new("auto")
+                    .long("auto")
+                    .help("评估图像质量（模糊、曝光、噪声、对比度、色调、模块尺度），自动选择预处理、变换配置和暴力破解")
+                    .action(clap::ArgAction::SetTrue)
+            )
             .arg(
                 Arg::new("verbose")
                     .short('v')
@@ -274,6 +435,242 @@ impl Args {
                     .help("禁用彩色输出")
                     .action(clap::ArgAction::SetTrue)
             )
+            .arg(
+                Arg::new("corners")
+                    .long("corners")
+                    .help("四个角点坐标，格式 x1,y1;x2,y2;x3,y3;x4,y4 (左上、右上、右下、左下)")
+                    .conflicts_with("batch")
+            )
+            .arg(
+                Arg::new("modules")
+                    .long("modules")
+                    .help("每边模块数 (21-177)")
+                    .value_parser(clap::value_parser!(usize))
+                    .requires("corners")
+            )
+            .arg(
+                Arg::new("qr-version")
+                    .long("qr-version")
+                    .help("二维码版本号 (1-40)")
+                    .value_parser(clap::value_parser!(u8))
+                    .requires("corners")
+                    .conflicts_with("modules")
+            )
+            .arg(
+                Arg::new("matrix-format")
+                    .long("matrix-format")
+                    .help("导出采样到的模块矩阵 [ascii|binary|png]")
+                    .value_parser(["ascii", "binary", "png"])
+                    .conflicts_with("batch")
+            )
+            .arg(
+                Arg::new("matrix-output")
+                    .long("matrix-output")
+                    .help("模块矩阵导出路径")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .requires("matrix-format")
+            )
+            .arg(
+                Arg::new("grid")
+                    .long("grid")
+                    .help("输入为文本模块网格 (ASCII 字符画或 0/1 矩阵，? 表示未知模块)")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with_all(["batch", "corners"])
+            )
+            .arg(
+                Arg::new("ambiguity")
+                    .long("ambiguity")
+                    .help("将灰度接近深浅分界的模块视为擦除，取值为相对对比度的比例 (0.0-0.5)")
+                    .value_parser(clap::value_parser!(f32))
+                    .requires("corners")
+            )
+            .arg(
+                Arg::new("occlusion-mask")
+                    .long("occlusion-mask")
+                    .help("遮挡掩码图像，非零像素覆盖的模块视为擦除")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .requires("corners")
+            )
+            .arg(
+                Arg::new("glare-erasures")
+                    .long("glare-erasures")
+                    .help("检测亮于纸面的饱和反光区域，被覆盖的模块视为擦除")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("corners")
+            )
+            .arg(
+                Arg::new("logo-erasures")
+                    .long("logo-erasures")
+                    .help("检测符号中心的 logo（远大于单个模块的纯色区域），覆盖的模块视为擦除")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("corners")
+            )
+            .arg(
+                Arg::new("inspect")
+                    .long("inspect")
+                    .help("检查隐藏数据：列出数据码字、非标准填充、终止符之后的字节及与干净重编码不一致的模块")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with("batch")
+            )
+            .arg(
+                Arg::new("color-layers")
+                    .long("color-layers")
+                    .help("按颜色通道分层解码 RGB 复用的二维码，结果标注所属图层")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with_all(["batch", "corners", "grid"])
+            )
+            .arg(
+                Arg::new("pyramid")
+                    .long("pyramid")
+                    .help("多尺度金字塔分块检测，适合大幅图像中的小二维码")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with_all(["batch", "corners", "grid", "color-layers"])
+            )
+            .arg(
+                Arg::new("pyramid-max-scale")
+                    .long("pyramid-max-scale")
+                    .help("金字塔最大放大倍数 (默认 2.0)")
+                    .value_parser(clap::value_parser!(f64))
+            )
+            .arg(
+                Arg::new("pyramid-tile")
+                    .long("pyramid-tile")
+                    .help("金字塔图块边长，单位像素 (256-4096，默认 1024)")
+                    .value_parser(clap::value_parser!(i32))
+            )
+            .arg(
+                Arg::new("tiled")
+                    .long("tiled")
+                    .help("按行带分块扫描超大图像，TIFF 流式读取，峰值内存不超过 --max-memory")
+                    .action(clap::ArgAction::SetTrue)
+                    .conflicts_with_all(["batch", "corners", "grid", "color-layers", "pyramid"])
+            )
+            .arg(
+                Arg::new("tile-size")
+                    .long("tile-size")
+                    .help("分块扫描的图块边长，单位像素 (256-8192，默认 2048)")
+                    .value_parser(clap::value_parser!(i32))
+            )
+            .arg(
+                Arg::new("tile-overlap")
+                    .long("tile-overlap")
+                    .help("相邻图块的重叠宽度，单位像素 (默认 256，不超过图块边长的一半)")
+                    .value_parser(clap::value_parser!(i32))
+            )
+            .arg(
+                Arg::new("max-memory")
+                    .long("max-memory")
+                    .value_name("MB")
+                    .help("分块扫描的内存上限，超出时自动缩小图块 (默认 1024)")
+                    .value_parser(clap::value_parser!(usize))
+            )
+            .arg(
+                Arg::new("clahe")
+                    .long("clahe")
+                    .help("预处理时使用 CLAHE 局部直方图均衡化代替全局均衡化")
+                    .action(clap::ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("clahe-clip")
+                    .long("clahe-clip")
+                    .help("CLAHE 限幅，越大对比度增强越强 (默认 2.0)")
+                    .value_parser(clap::value_parser!(f64))
+            )
+            .arg(
+                Arg::new("clahe-tiles")
+                    .long("clahe-tiles")
+                    .help("CLAHE 每边分块数 (1-64，默认 8)")
+                    .value_parser(clap::value_parser!(i32))
+            )
+            .arg(
+                Arg::new("tone-mapping")
+                    .long("tone-mapping")
+                    .help("预处理时应用局部色调映射，压缩聚光灯或阴影造成的光照差异 (强度 0.0-1.0)")
+                    .value_parser(clap::value_parser!(f64))
+            )
+            .arg(
+                Arg::new("binarize-window")
+                    .long("binarize-window")
+                    .help("Sauvola/Niblack/Wolf 局部二值化的窗口大小，单位像素 (默认 31)")
+                    .value_parser(clap::value_parser!(i32))
+            )
+            .arg(
+                Arg::new("pipeline")
+                    .long("pipeline")
+                    .help("从 JSON 文件加载增强解码的变换流水线")
+                    .value_parser(clap::value_parser!(PathBuf))
+            )
+            .arg(
+                Arg::new("profile")
+                    .long("profile")
+                    .help("使用的流水线配置名 (文件中的配置或内置的 screen-photo、art-qr、print-scan)")
+            )
+            .arg(
+                Arg::new("transforms")
+                    .long("transforms")
+                    .help("按顺序叠加的变换链，如 \"gray,clahe:2.0,gamma:0.8,otsu\"，在增强解码序列之前尝试")
+            )
+            .arg(
+                Arg::new("transforms-only")
+                    .long("transforms-only")
+                    .help("只尝试 --transforms 指定的变换链，不再尝试增强解码序列")
+                    .action(clap::ArgAction::SetTrue)
+                    .requires("transforms")
+            )
+            .arg(
+                Arg::new("max-compositions")
+                    .long("max-compositions")
+                    .help("单步变换都失败后最多尝试的 2-3 步组合变换链数，0 表示关闭 (默认只在 --auto 或 --profile 下启用 48 个)")
+                    .value_parser(clap::value_parser!(usize))
+            )
+            .arg(
+                Arg::new("time-budget")
+                    .long("time-budget")
+                    .help("增强解码的时间预算，单位秒，超出后停止尝试新的变换")
+                    .value_parser(clap::value_parser!(f64))
+            )
+            .arg(
+                Arg::new("transform-stats")
+                    .long("transform-stats")
+                    .help("跨运行记录各变换的成功次数和耗时，并据此调整增强解码的尝试顺序")
+                    .value_parser(clap::value_parser!(PathBuf))
+            )
+            .arg(
+                Arg::new("search")
+                    .long("search")
+                    .help("暴力破解的搜索策略 [exhaustive|coarse-to-fine|random|bandit]")
+                    .value_parser(["exhaustive", "coarse-to-fine", "random", "bandit"])
+            )
+            .arg(
+                Arg::new("search-budget")
+                    .long("search-budget")
+                    .help("暴力破解最多评估的参数组合数 (random/bandit 默认 2000)")
+                    .value_parser(clap::value_parser!(usize))
+            )
+            .arg(
+                Arg::new("search-space")
+                    .long("search-space")
+                    .help("从 JSON 文件加载暴力破解的搜索空间 (参数轴及取值)")
+                    .value_parser(clap::value_parser!(PathBuf))
+            )
+            .arg(
+                Arg::new("search-axis")
+                    .long("search-axis")
+                    .help("替换或追加一个搜索轴，如 \"rotation=0,45\"、\"channel=r,g,b\"，可重复")
+                    .action(clap::ArgAction::Append)
+            )
+            .arg(
+                Arg::new("recipe")
+                    .long("recipe")
+                    .help("先重放 JSON 文件中成功过的变换配方，都失败时再搜索")
+                    .value_parser(clap::value_parser!(PathBuf))
+            )
+            .arg(
+                Arg::new("save-recipe")
+                    .long("save-recipe")
+                    .help("把暴力破解成功的变换配方保存到 JSON 文件")
+                    .value_parser(clap::value_parser!(PathBuf))
+            )
     }
     
     /// 从 ArgMatches 创建 Args
@@ -303,6 +700,7 @@ impl Args {
         };
         
         let preprocess = matches.get_flag("preprocess");
+        let auto = matches.get_flag("auto");
         let verbose = matches.get_flag("verbose");
         let quiet = matches.get_flag("quiet");
         let show_position = matches.get_flag("show-position");
@@ -327,11 +725,85 @@ impl Args {
         let show_progress = !matches.get_flag("no-progress");
         let colored_output = !matches.get_flag("no-color");
         
+        // 角点采样参数
+        let corners = match matches.get_one::<String>("corners") {
+            Some(value) => Some(Self::parse_corners(value)?),
+            None => None,
+        };
+        let module_count = match matches.get_one::<u8>("qr-version") {
+            Some(&version) => Some(crate::module_matrix::size_from_version(version).ok_or_else(|| {
+                QRDecodeError::invalid_input(format!("无效的二维码版本号: {} (应为 1-40)", version))
+            })?),
+            None => matches.get_one::<usize>("modules").copied(),
+        };
+        
+        // 模块矩阵导出参数
+        let matrix_export = match matches.get_one::<String>("matrix-format") {
+            Some(value) => Some(value.parse::<MatrixExportFormat>()?),
+            None => None,
+        };
+        let matrix_output_path = matches.get_one::<PathBuf>("matrix-output").cloned();
+        
+        // 文本模块网格输入：显式指定或按扩展名识别
+        let grid_input = !batch_mode
+            && (matches.get_flag("grid") || crate::grid_input::is_grid_file(&input_path));
+        
+        // 擦除来源参数
+        let ambiguity_margin = matches.get_one::<f32>("ambiguity").copied().unwrap_or(0.0);
+        let occlusion_mask = matches.get_one::<PathBuf>("occlusion-mask").cloned();
+        let glare_erasures = matches.get_flag("glare-erasures");
+        let logo_erasures = matches.get_flag("logo-erasures");
+        let inspect = matches.get_flag("inspect");
+        let color_layers = matches.get_flag("color-layers");
+        let pyramid = matches.get_flag("pyramid");
+        let pyramid_max_scale = matches.get_one::<f64>("pyramid-max-scale").copied().unwrap_or(DEFAULT_MAX_SCALE);
+        let pyramid_tile = matches.get_one::<i32>("pyramid-tile").copied().unwrap_or(DEFAULT_TILE_SIZE);
+        let tiled = matches.get_flag("tiled");
+        let tile_size = matches.get_one::<i32>("tile-size").copied().unwrap_or(DEFAULT_SCAN_TILE);
+        let tile_overlap = matches.get_one::<i32>("tile-overlap").copied().unwrap_or(DEFAULT_SCAN_OVERLAP);
+        let max_memory_mb = matches.get_one::<usize>("max-memory").copied().unwrap_or(DEFAULT_MAX_MEMORY_MB);
+        
+        // 局部对比度增强参数（CLAHE 参数同时用于增强解码的变换序列）
+        let clahe = matches.get_flag("clahe");
+        let clahe_clip_limit = matches.get_one::<f64>("clahe-clip").copied().unwrap_or(DEFAULT_CLAHE_CLIP_LIMIT);
+        let clahe_tile_size = matches.get_one::<i32>("clahe-tiles").copied().unwrap_or(DEFAULT_CLAHE_TILE_SIZE);
+        let tone_mapping = matches.get_one::<f64>("tone-mapping").copied();
+        let binarization_window = matches.get_one::<i32>("binarize-window").copied().unwrap_or(DEFAULT_BINARIZATION_WINDOW);
+        
+        // 增强解码流水线参数
+        let pipeline = matches.get_one::<PathBuf>("pipeline").cloned();
+        let profile = matches.get_one::<String>("profile").cloned();
+        let transforms = match matches.get_one::<String>("transforms") {
+            Some(value) => Some(crate::transform_dsl::parse_transforms(value, binarization_window)?),
+            None => None,
+        };
+        let transforms_only = matches.get_flag("transforms-only");
+        let max_compositions = matches.get_one::<usize>("max-compositions").copied();
+        let time_budget = matches.get_one::<f64>("time-budget").copied();
+        let transform_stats = matches.get_one::<PathBuf>("transform-stats").cloned();
+        
+        // 暴力破解搜索参数
+        let search = match matches.get_one::<String>("search") {
+            Some(value) => value.parse::<SearchStrategy>()?,
+            None => SearchStrategy::Exhaustive,
+        };
+        let search_budget = matches.get_one::<usize>("search-budget").copied();
+        let search_space = matches.get_one::<PathBuf>("search-space").cloned();
+        let search_axes = matches
+            .get_many::<String>("search-axis")
+            .into_iter()
+            .flatten()
+            .map(|value| crate::search_space::parse_axis_arg(value, binarization_window))
+            .collect::<Result<Vec<_>>>()?;
+        let recipe = matches.get_one::<PathBuf>("recipe").cloned();
+        let save_recipe = matches.get_one::<PathBuf>("save-recipe").cloned();
+        
         Ok(Args {
             input_path,
             output_path,
             output_format,
             preprocess,
+            auto,
             verbose,
             quiet,
             show_position,
@@ -350,9 +822,75 @@ impl Args {
             report_output,
             show_progress,
             colored_output,
+            corners,
+            module_count,
+            matrix_export,
+            matrix_output_path,
+            grid_input,
+            ambiguity_margin,
+            occlusion_mask,
+            glare_erasures,
+            logo_erasures,
+            inspect,
+            color_layers,
+            pyramid,
+            pyramid_max_scale,
+            pyramid_tile,
+            tiled,
+            tile_size,
+            tile_overlap,
+            max_memory_mb,
+            clahe,
+            clahe_clip_limit,
+            clahe_tile_size,
+            tone_mapping,
+            binarization_window,
+            pipeline,
+            profile,
+            transforms,
+            transforms_only,
+            max_compositions,
+            time_budget,
+            transform_stats,
+            search,
+            search_budget,
+            search_space,
+            search_axes,
+            recipe,
+            save_recipe,
         })
     }
     
+    /// 解析角点参数，格式为 x1,y1;x2,y2;x3,y3;x4,y4
+    pub fn parse_corners(value: &str) -> Result<Vec<(f32, f32)>> {
+        let corners = value
+            .split(';')
+            .map(|pair| {
+                let coords: Vec<&str> = pair.split(',').map(str::trim).collect();
+                if coords.len() != 2 {
+                    return Err(QRDecodeError::invalid_input(format!(
+                        "无效的角点坐标: \"{}\" (应为 x,y)", pair.trim()
+                    )));
+                }
+                let x = coords[0].parse::<f32>().map_err(|_| {
+                    QRDecodeError::invalid_input(format!("无效的 x 坐标: \"{}\"", coords[0]))
+                })?;
+                let y = coords[1].parse::<f32>().map_err(|_| {
+                    QRDecodeError::invalid_input(format!("无效的 y 坐标: \"{}\"", coords[1]))
+                })?;
+                Ok((x, y))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        
+        if corners.len() != 4 {
+            return Err(QRDecodeError::invalid_input(format!(
+                "需要 4 个角点，实际提供了 {} 个", corners.len()
+            )));
+        }
+        
+        Ok(corners)
+    }
+    
     /// 验证参数
     pub fn validate(&self) -> Result<()> {
         // 如果是帮助或版本请求，跳过验证
@@ -385,8 +923,8 @@ impl Args {
                 )));
             }
             
-            // 验证输入文件格式
-            if !Self::is_supported_format(&self.input_path) {
+            // 验证输入文件格式（文本模块网格不经过图像加载）
+            if !self.grid_input && !Self::is_supported_format(&self.input_path) {
                 return Err(QRDecodeError::UnsupportedFormat(format!(
                     "不支持的文件格式: {}\n支持的格式: jpg, jpeg, png, bmp, tiff, tif, webp",
                     self.input_path.display()
@@ -394,6 +932,128 @@ impl Args {
             }
         }
         
+        // 验证模块数
+        if let Some(size) = self.module_count {
+            if crate::module_matrix::version_from_size(size).is_none() {
+                return Err(QRDecodeError::InvalidInput(format!(
+                    "无效的模块数: {} (应为 21-177 之间的 17+4n)", size
+                )));
+            }
+        }
+        
+        // 验证擦除参数
+        if !(0.0..=0.5).contains(&self.ambiguity_margin) {
+            return Err(QRDecodeError::InvalidInput(
+                "歧义擦除阈值必须在 0.0 到 0.5 之间".to_string()
+            ));
+        }
+        if let Some(mask_path) = &self.occlusion_mask {
+            if !mask_path.exists() {
+                return Err(QRDecodeError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("遮挡掩码文件不存在: {}", mask_path.display())
+                )));
+            }
+        }
+        
+        // 验证局部对比度参数
+        if self.clahe_clip_limit <= 0.0 {
+            return Err(QRDecodeError::InvalidInput(
+                "CLAHE 限幅必须大于 0".to_string()
+            ));
+        }
+        if !(1..=64).contains(&self.clahe_tile_size) {
+            return Err(QRDecodeError::InvalidInput(
+                "CLAHE 网格大小必须在 1 到 64 之间".to_string()
+            ));
+        }
+        
+        // 验证金字塔参数
+        if !(self.pyramid_max_scale > 0.0 && self.pyramid_max_scale <= 8.0) {
+            return Err(QRDecodeError::InvalidInput(
+                "金字塔最大放大倍数必须在 0 到 8 之间".to_string()
+            ));
+        }
+        if !(256..=4096).contains(&self.pyramid_tile) {
+            return Err(QRDecodeError::InvalidInput(
+                "金字塔图块边长必须在 256 到 4096 像素之间".to_string()
+            ));
+        }
+        
+        // 验证分块扫描参数
+        if !(256..=8192).contains(&self.tile_size) {
+            return Err(QRDecodeError::InvalidInput(
+                "分块扫描的图块边长必须在 256 到 8192 像素之间".to_string()
+            ));
+        }
+        if self.tile_overlap < 0 || self.tile_overlap * 2 > self.tile_size {
+            return Err(QRDecodeError::InvalidInput(
+                "图块重叠宽度必须在 0 到图块边长的一半之间".to_string()
+            ));
+        }
+        if self.max_memory_mb < 64 {
+            return Err(QRDecodeError::InvalidInput(
+                "分块扫描的内存上限不能小于 64 MB".to_string()
+            ));
+        }
+        if let Some(strength) = self.tone_mapping {
+            if !(0.0..=1.0).contains(&strength) {
+                return Err(QRDecodeError::InvalidInput(
+                    "局部色调映射强度必须在 0.0 到 1.0 之间".to_string()
+                ));
+            }
+        }
+        
+        // 验证局部二值化窗口
+        if !(3..=255).contains(&self.binarization_window) {
+            return Err(QRDecodeError::InvalidInput(
+                "局部二值化窗口必须在 3 到 255 像素之间".to_string()
+            ));
+        }
+        
+        // 验证时间预算
+        if let Some(budget) = self.time_budget {
+            if budget <= 0.0 || !budget.is_finite() {
+                return Err(QRDecodeError::InvalidInput(
+                    "时间预算必须大于 0 秒".to_string()
+                ));
+            }
+        }
+        
+        // 加载并校验变换统计文件（文件不存在时会在运行后创建）
+        if let Some(path) = &self.transform_stats {
+            crate::transform_stats::TransformStats::load(path)?;
+        }
+        
+        // 验证搜索预算
+        if self.search_budget == Some(0) {
+            return Err(QRDecodeError::InvalidInput(
+                "搜索预算必须大于 0".to_string()
+            ));
+        }
+        
+        // 加载并校验搜索空间文件
+        if let Some(path) = &self.search_space {
+            crate::search_space::load_search_space(path, self.binarization_window)?;
+        }
+        
+        // 只有暴力破解（批量处理总是暴力破解）才会产生新配方
+        if self.save_recipe.is_some() && !self.brute_force && !self.batch_mode && self.recipe.is_none() {
+            return Err(QRDecodeError::InvalidInput(
+                "--save-recipe 需要同时使用 --brute-force、--batch 或 --recipe".to_string()
+            ));
+        }
+        
+        // 加载并校验配方文件
+        if let Some(path) = &self.recipe {
+            crate::recipe::RecipeBook::load(path)?;
+        }
+        
+        // 加载并校验流水线文件和配置名，出错时在处理图像之前报告
+        if self.pipeline.is_some() || self.profile.is_some() {
+            crate::pipeline::resolve_pipeline(self.pipeline.as_deref(), self.profile.as_deref(), self.binarization_window)?;
+        }
+        
         // 验证置信度范围
         if !(0.0..=1.0).contains(&self.min_confidence) {
             return Err(QRDecodeError::InvalidInput(
@@ -413,6 +1073,18 @@ impl Args {
             }
         }
         
+        // 验证模块矩阵导出路径
+        if let Some(matrix_path) = &self.matrix_output_path {
+            if let Some(parent) = matrix_path.parent() {
+                if parent.exists() && !Self::is_directory_writable(parent) {
+                    return Err(QRDecodeError::IoError(std::io::Error::new(
+                        std::io::ErrorKind::PermissionDenied,
+                        format!("模块矩阵导出目录不可写: {}", parent.display())
+                    )));
+                }
+            }
+        }
+        
         // 验证预处理输出路径
         if let Some(processed_path) = &self.processed_output_path {
             if let Some(parent) = processed_path.parent() {
@@ -506,19 +1178,37 @@ impl Args {
         println!("  -o, --output <文件>        输出文件路径");
         println!("  -f, --format <格式>        输出格式 [text|json|csv|verbose]");
         println!("  -p, --preprocess           启用图像预处理");
+        println!("  --auto                     评估图像质量，自动选择预处理、变换配置和是否暴力破解");
         println!("  -v, --verbose              详细输出");
         println!("  -q, --quiet                静默模式");
         println!("  --show-position            显示二维码位置信息");
         println!("  --min-confidence <值>      最小置信度阈值 (0.0-1.0)");
         println!("  --save-processed <文件>    保存预处理后的图像");
+        println!("  --clahe                    预处理时使用 CLAHE 局部均衡化代替全局均衡化");
+        println!("  --clahe-clip <值>          CLAHE 限幅 (默认 2.0)");
+        println!("  --clahe-tiles <数>         CLAHE 每边分块数 (1-64，默认 8)");
+        println!("  --tone-mapping <强度>      局部色调映射，压缩聚光灯/阴影的光照差异 (0.0-1.0)");
         println!("  -h, --help                 显示此帮助信息");
         println!("  -V, --version              显示版本信息");
         println!();
         println!("暴力破解选项:");
         println!("  -b, --brute-force          启用暴力破解模式");
         println!("  -e, --expected-count <数>  预期的二维码数量");
-        println!("  -r, --randomize            随机化参数组合");
+        println!("  -r, --randomize            随机化参数组合 (穷举时打乱顺序)");
+        println!("  --search <策略>            搜索策略 [exhaustive|coarse-to-fine|random|bandit] (默认 exhaustive)");
+        println!("                             coarse-to-fine 在定位图形命中最多的参数附近逐级细化，");
+        println!("                             bandit 按各参数取值的定位图形得分用 UCB1 选择组合");
+        println!("  --search-budget <数>       最多评估的参数组合数 (random/bandit 默认 2000)");
+        println!("  --search-space <文件>      从 JSON 文件加载搜索空间，未列出的参数轴固定为中性值");
+        println!("  --search-axis <轴=值,...>  替换或追加一个搜索轴，可重复，可用轴:");
+        println!("                             {}", crate::search_space::AXIS_NAMES.join(", "));
+        println!("                             默认不搜索镜像、旋转和光照校正，如 mirror=none,h,v、");
+        println!("                             rotation=sweep (15° 步长)、illumination=none,close；");
+        println!("                             binarization=otsu,local,none 加入局部二值化方法");
+        println!("  --recipe <文件>            先按成功次数重放配方文件中的变换配方，都失败时再搜索");
+        println!("  --save-recipe <文件>       把成功的变换配方及成功次数保存到 JSON 文件");
         println!("  -i, --invert               启用反色处理");
+        println!("  --binarize-window <像素>   Sauvola/Niblack/Wolf 局部二值化窗口 (默认 31)");
         println!();
         println!("批量处理选项:");
         println!("  --batch                    启用批量处理模式");
@@ -528,6 +1218,60 @@ impl Args {
         println!("  --no-progress              禁用进度显示");
         println!("  --no-color                 禁用彩色输出");
         println!();
+        println!("角点采样选项:");
+        println!("  --corners <坐标>           四个角点 x1,y1;x2,y2;x3,y3;x4,y4，跳过自动检测");
+        println!("  --modules <数>             每边模块数 (21-177)");
+        println!("  --qr-version <版本>        二维码版本号 (1-40)");
+        println!("  --ambiguity <比例>         灰度接近深浅分界的模块视为擦除 (0.0-0.5)");
+        println!("  --occlusion-mask <文件>    遮挡掩码图像，被覆盖的模块视为擦除");
+        println!("  --glare-erasures           检测饱和反光区域，被覆盖的模块视为擦除");
+        println!("  --logo-erasures            检测中心 logo，被覆盖的模块视为擦除");
+        println!();
+        println!("模块矩阵导出选项:");
+        println!("  --matrix-format <格式>     导出采样到的模块矩阵 [ascii|binary|png]");
+        println!("  --matrix-output <路径>     模块矩阵导出路径");
+        println!();
+        println!("隐藏数据检查选项:");
+        println!("  --inspect                  列出数据码字，检查非标准填充、终止符之后的字节、");
+        println!("                             剩余位以及与干净重编码不一致的模块");
+        println!();
+        println!("彩色分层选项:");
+        println!("  --color-layers             拆分红/绿/蓝通道并按颜色聚类分层，逐层解码");
+        println!("                             RGB 复用的二维码，结果标注图层 (channel-R、cluster-G 等)");
+        println!();
+        println!("多尺度检测选项:");
+        println!("  --pyramid                  从粗到细逐层扫描，放大层切成重叠图块，合并各层结果");
+        println!("  --pyramid-max-scale <倍数> 最大放大倍数 (默认 2.0)");
+        println!("  --pyramid-tile <像素>      图块边长 (256-4096，默认 1024)");
+        println!();
+        println!("大图分块扫描选项:");
+        println!("  --tiled                    按行带读取图像，切成重叠图块逐块解码并合并跨块的重复结果，");
+        println!("                             无压缩或 PackBits 的 TIFF 流式读取，不受 50 MP 限制");
+        println!("  --tile-size <像素>         图块边长 (256-8192，默认 2048)");
+        println!("  --tile-overlap <像素>      相邻图块重叠宽度 (默认 256)，不超过该边长的二维码不会被切断");
+        println!("  --max-memory <MB>          内存上限 (默认 1024)，超出时自动缩小图块");
+        println!();
+        println!("变换流水线选项:");
+        println!("  --pipeline <文件>          从 JSON 文件加载增强解码的变换序列 (可含变换链和多个命名配置)");
+        println!("  --profile <名称>           选择流水线配置，内置: screen-photo, art-qr, print-scan");
+        println!("  --transforms <链>          按顺序叠加的变换链，在增强解码序列之前尝试，如 gray,clahe:2.0,otsu");
+        println!("                             配合 --save-processed 保存每一步的中间结果 (<文件名>_stepNN)");
+        println!("  --transforms-only          只尝试 --transforms 指定的变换链");
+        println!("  --max-compositions <数>    单步变换都失败后按预期收益尝试的组合变换链数 (0 关闭，");
+        println!("                             默认只在 --auto 或 --profile 下启用 48 个)");
+        println!("  --time-budget <秒>         增强解码的时间预算，超出后停止尝试");
+        println!("  --transform-stats <文件>   按配置名累计各变换的成功次数和耗时，之后的运行按");
+        println!("                             单位耗时的成功概率重新排列增强解码的尝试顺序");
+        println!();
+        println!("变换链写法 (名称[:参数[:参数]]，方括号内可省略):");
+        for (syntax, description) in crate::transform_dsl::DSL_TRANSFORMS {
+            println!("  {:<27}{}", syntax, description);
+        }
+        println!();
+        println!("模块网格输入选项:");
+        println!("  --grid                     输入为文本模块网格 (.txt/.csv/.tsv/.grid 自动识别)");
+        println!("                             深色: █ # X 1  浅色: 空格 . 0  未知: ?");
+        println!();
         println!("支持的图像格式:");
         println!("  {}", Self::supported_formats().join(", "));
         println!();
@@ -535,9 +1279,23 @@ impl Args {
         println!("  {} image.jpg", env!("CARGO_PKG_NAME"));
         println!("  {} -f json -o result.json image.png", env!("CARGO_PKG_NAME"));
         println!("  {} --preprocess --verbose image.jpg", env!("CARGO_PKG_NAME"));
+        println!("  {} --auto -f json photo.jpg", env!("CARGO_PKG_NAME"));
         println!("  {} --min-confidence 0.8 --show-position image.png", env!("CARGO_PKG_NAME"));
+        println!("  {} --brute-force --search coarse-to-fine hard.jpg", env!("CARGO_PKG_NAME"));
+        println!("  {} --brute-force --search-axis channel=r,g,b --search-axis \"binarization=otsu,sauvola:41\" rgb.png", env!("CARGO_PKG_NAME"));
+        println!("  {} --brute-force --save-recipe camera.json first.jpg", env!("CARGO_PKG_NAME"));
+        println!("  {} --batch -d ./shots --recipe camera.json --save-recipe camera.json", env!("CARGO_PKG_NAME"));
         println!("  {} --batch -d ./test --recursive", env!("CARGO_PKG_NAME"));
         println!("  {} --batch --directory ./images --report-output report.json", env!("CARGO_PKG_NAME"));
+        println!("  {} --corners \"12,10;310,14;306,312;8,308\" --qr-version 2 image.png", env!("CARGO_PKG_NAME"));
+        println!("  {} --grid transcribed.txt", env!("CARGO_PKG_NAME"));
+        println!("  {} --inspect -f json challenge.png", env!("CARGO_PKG_NAME"));
+        println!("  {} --color-layers -f verbose rgb_overlay.png", env!("CARGO_PKG_NAME"));
+        println!("  {} --pyramid --pyramid-max-scale 4 large_scan.tif", env!("CARGO_PKG_NAME"));
+        println!("  {} --tiled --max-memory 512 gigapixel_stitch.tif", env!("CARGO_PKG_NAME"));
+        println!("  {} --pipeline pipelines.json --profile screen-photo photo.jpg", env!("CARGO_PKG_NAME"));
+        println!("  {} --transforms \"gray,clahe:2.0,gamma:0.8,otsu\" --save-processed steps.png photo.jpg", env!("CARGO_PKG_NAME"));
+        println!("  {} --corners \"12,10;310,14;306,312;8,308\" --occlusion-mask logo_mask.png art.png", env!("CARGO_PKG_NAME"));
     }
     
     /// 显示版本信息
@@ -578,6 +1336,17 @@ mod tests {
         assert_eq!(formats.len(), 7);
     }
     
+    #[test]
+    fn test_parse_corners() {
+        let corners = Args::parse_corners("10,20; 110.5,20;110,120 ;10,120").unwrap();
+        assert_eq!(corners.len(), 4);
+        assert_eq!(corners[1], (110.5, 20.0));
+        
+        assert!(Args::parse_corners("10,20;110,20;110,120").is_err());
+        assert!(Args::parse_corners("10,20;110,20;110,abc;10,120").is_err());
+        assert!(Args::parse_corners("10;110,20;110,120;10,120").is_err());
+    }
+    
     #[test]
     fn test_help_args() {
         let args = Args::help_args();
@@ -0,0 +1,282 @@
+//! 彩色分层（RGB 复用）二维码解码模块
+//!
+//! 部分挑战题和营销图片把三个二维码分别叠加在红、绿、蓝通道中，直接用
+//! `COLOR_BGR2GRAY` 转灰度会把三层混在一起而全部无法识别。本模块先按通道拆分，
+//! 再对像素颜色做 k-means 聚类，按聚类中心在各通道上的深浅把像素重新划分为
+//! 干净的黑白层（可抵消油墨串色和整体偏色），每层单独解码并标注所属层。
+
+use opencv::{
+    core::{self, Mat, Scalar, CV_8UC1, CV_8UC3},
+    imgproc::{cvt_color, COLOR_GRAY2BGR},
+    prelude::*,
+};
+
+use crate::enhanced_processor::EnhancedImageProcessor;
+use crate::error::{QRDecodeError, Result};
+use crate::types::QRCodeResult;
+
+/// 通道名称（按 OpenCV 的 BGR 顺序）
+const CHANNEL_NAMES: [&str; 3] = ["B", "G", "R"];
+
+/// 输出时的通道顺序：红、绿、蓝
+const CHANNEL_ORDER: [usize; 3] = [2, 1, 0];
+
+/// 参与聚类的最大采样像素数，超出时按步长抽样
+const MAX_CLUSTER_SAMPLES: usize = 60_000;
+
+/// k-means 迭代次数
+const CLUSTER_ITERATIONS: usize = 12;
+
+/// 聚类中心在某通道上的最大间隔低于该值时认为该通道没有独立的层
+const MIN_LAYER_CONTRAST: f32 = 48.0;
+
+/// 拆分得到的单个图层
+pub struct ColorLayer {
+    /// 图层标签，如 `channel-R`、`cluster-G`
+    pub name: String,
+    /// 所属通道（BGR 下标）
+    pub channel: usize,
+    /// 单通道灰度图像，深色模块为 0
+    pub image: Mat,
+}
+
+/// 将彩色图像拆分为通道层和聚类层
+///
+/// 每个通道依次给出原始通道灰度图 (`channel-X`) 和按聚类重新二值化的图层
+/// (`cluster-X`)，没有独立内容的通道不会生成聚类层。
+pub fn split_color_layers(image: &Mat) -> Result<Vec<ColorLayer>> {
+    if image.typ() != CV_8UC3 {
+        return Err(QRDecodeError::invalid_input(
+            "彩色分层解码需要 3 通道彩色图像".to_string(),
+        ));
+    }
+
+    let rows = image.rows();
+    let cols = image.cols();
+    let image = image.try_clone()?;
+    let pixels: Vec<[u8; 3]> = image
+        .data_bytes()?
+        .chunks_exact(3)
+        .map(|bgr| [bgr[0], bgr[1], bgr[2]])
+        .collect();
+
+    let clustered = cluster_layers(&pixels);
+
+    let mut layers = Vec::new();
+    for &channel in &CHANNEL_ORDER {
+        let raw: Vec<u8> = pixels.iter().map(|bgr| bgr[channel]).collect();
+        layers.push(ColorLayer {
+            name: format!("channel-{}", CHANNEL_NAMES[channel]),
+            channel,
+            image: gray_from_bytes(rows, cols, &raw)?,
+        });
+
+        if let Some((_, layer)) = clustered.iter().find(|(c, _)| *c == channel) {
+            layers.push(ColorLayer {
+                name: format!("cluster-{}", CHANNEL_NAMES[channel]),
+                channel,
+                image: gray_from_bytes(rows, cols, layer)?,
+            });
+        }
+    }
+
+    Ok(layers)
+}
+
+/// 逐层解码彩色复用的二维码
+///
+/// 每个通道按“原始通道 → 聚类层 → 两者反色”的顺序尝试，找到结果即停止；
+/// 多个通道解出相同内容（例如黑色二维码出现在所有通道）时只保留第一个。
+pub fn decode_color_layers(
+    processor: &mut EnhancedImageProcessor,
+    image: &Mat,
+    verbose: bool,
+) -> Result<Vec<QRCodeResult>> {
+    let layers = split_color_layers(image)?;
+    let mut results: Vec<QRCodeResult> = Vec::new();
+
+    for &channel in &CHANNEL_ORDER {
+        let candidates: Vec<&ColorLayer> = layers.iter().filter(|l| l.channel == channel).collect();
+        let mut found = Vec::new();
+
+        'attempts: for invert in [false, true] {
+            for layer in &candidates {
+                let name = if invert { format!("{}-inverted", layer.name) } else { layer.name.clone() };
+                if verbose {
+                    println!("🎨 尝试解码图层: {}", name);
+                }
+
+                let gray = if invert { invert_image(&layer.image)? } else { layer.image.clone() };
+                let mut bgr = Mat::default();
+                cvt_color(&gray, &mut bgr, COLOR_GRAY2BGR, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+                    .map_err(|e| QRDecodeError::image_processing_error(format!("图层转换失败: {}", e)))?;
+
+                let decoded = processor.decode_with_transforms(&bgr)?;
+                if !decoded.is_empty() {
+                    found = decoded.into_iter().map(|r| r.with_layer(name.clone())).collect();
+                    break 'attempts;
+                }
+            }
+        }
+
+        for result in found {
+            if !results.iter().any(|r| r.content == result.content) {
+                results.push(result);
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// 对像素颜色聚类并按通道重新二值化
+///
+/// 返回 `(通道下标, 灰度数据)` 列表，像素所属聚类中心在该通道偏暗时为 0，否则为 255。
+pub fn cluster_layers(pixels: &[[u8; 3]]) -> Vec<(usize, Vec<u8>)> {
+    if pixels.is_empty() {
+        return Vec::new();
+    }
+
+    let step = pixels.len().div_ceil(MAX_CLUSTER_SAMPLES);
+    let samples: Vec<[u8; 3]> = pixels.iter().step_by(step).copied().collect();
+    let centres = kmeans(&samples, &cube_corners(), CLUSTER_ITERATIONS);
+    let labels: Vec<usize> = pixels.iter().map(|p| nearest_centre(p, &centres)).collect();
+
+    let mut layers = Vec::new();
+    for channel in 0..3 {
+        let dark = match split_channel(&centres, channel) {
+            Some(dark) => dark,
+            None => continue,
+        };
+        let layer = labels.iter().map(|&label| if dark[label] { 0 } else { 255 }).collect();
+        layers.push((channel, layer));
+    }
+    layers
+}
+
+/// 以 RGB 立方体的 8 个顶点作为初始中心，对应三层深浅的全部组合
+fn cube_corners() -> Vec<[f32; 3]> {
+    (0..8)
+        .map(|bits: usize| {
+            [0, 1, 2].map(|c| if bits >> c & 1 == 1 { 255.0 } else { 0.0 })
+        })
+        .collect()
+}
+
+/// 简单的 k-means，丢弃没有分到像素的中心
+fn kmeans(samples: &[[u8; 3]], initial: &[[f32; 3]], iterations: usize) -> Vec<[f32; 3]> {
+    let mut centres = initial.to_vec();
+    for _ in 0..iterations {
+        let mut sums = vec![[0f64; 3]; centres.len()];
+        let mut counts = vec![0usize; centres.len()];
+        for sample in samples {
+            let label = nearest_centre(sample, &centres);
+            for c in 0..3 {
+                sums[label][c] += sample[c] as f64;
+            }
+            counts[label] += 1;
+        }
+
+        let updated: Vec<[f32; 3]> = sums
+            .iter()
+            .zip(&counts)
+            .filter(|(_, &count)| count > 0)
+            .map(|(sum, &count)| sum.map(|s| (s / count as f64) as f32))
+            .collect();
+        if updated == centres {
+            break;
+        }
+        centres = updated;
+    }
+    centres
+}
+
+/// 距离最近的聚类中心
+fn nearest_centre(pixel: &[u8; 3], centres: &[[f32; 3]]) -> usize {
+    let distance = |centre: &[f32; 3]| -> f32 {
+        (0..3).map(|c| (pixel[c] as f32 - centre[c]).powi(2)).sum()
+    };
+    (0..centres.len())
+        .min_by(|&a, &b| distance(&centres[a]).total_cmp(&distance(&centres[b])))
+        .unwrap_or(0)
+}
+
+/// 在某通道上按最大间隔把聚类中心分成深浅两组，间隔过小时返回 None
+fn split_channel(centres: &[[f32; 3]], channel: usize) -> Option<Vec<bool>> {
+    let mut values: Vec<f32> = centres.iter().map(|c| c[channel]).collect();
+    values.sort_by(f32::total_cmp);
+
+    let (gap, threshold) = values
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0], (pair[0] + pair[1]) / 2.0))
+        .max_by(|a, b| a.0.total_cmp(&b.0))?;
+    if gap < MIN_LAYER_CONTRAST {
+        return None;
+    }
+
+    Some(centres.iter().map(|c| c[channel] < threshold).collect())
+}
+
+fn gray_from_bytes(rows: i32, cols: i32, bytes: &[u8]) -> Result<Mat> {
+    let mut gray = Mat::new_rows_cols_with_default(rows, cols, CV_8UC1, Scalar::all(0.0))?;
+    gray.data_bytes_mut()?.copy_from_slice(bytes);
+    Ok(gray)
+}
+
+fn invert_image(image: &Mat) -> Result<Mat> {
+    let mut inverted = Mat::default();
+    core::bitwise_not(image, &mut inverted, &core::no_array())
+        .map_err(|e| QRDecodeError::image_processing_error(format!("反色处理失败: {}", e)))?;
+    Ok(inverted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 三层互相独立的伪随机图案，带串色和偏色
+    fn multiplexed_pixels() -> (Vec<[u8; 3]>, [Vec<bool>; 3]) {
+        let mut state = 0x2545F491u32;
+        let mut bit = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state & 1 == 1
+        };
+        let patterns: [Vec<bool>; 3] = [(); 3].map(|_| (0..4000).map(|_| bit()).collect());
+        let pixels = (0..4000)
+            .map(|i| {
+                let level = |c: usize| if patterns[c][i] { 40.0 } else { 230.0 };
+                // 红色油墨会让蓝通道也变暗一些
+                let b = level(0) - if patterns[2][i] { 35.0 } else { 0.0 };
+                [b as u8, level(1) as u8, (level(2) - 20.0) as u8]
+            })
+            .collect();
+        (pixels, patterns)
+    }
+
+    #[test]
+    fn test_cluster_layers_separate_channels() {
+        let (pixels, patterns) = multiplexed_pixels();
+        let layers = cluster_layers(&pixels);
+        assert_eq!(layers.len(), 3);
+        for (channel, layer) in layers {
+            for (i, &value) in layer.iter().enumerate() {
+                assert_eq!(value == 0, patterns[channel][i], "通道 {} 像素 {}", channel, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cluster_layers_skip_flat_channel() {
+        // 只有红色层的图像：蓝、绿通道恒定
+        let pixels: Vec<[u8; 3]> = (0..500)
+            .map(|i| if i % 3 == 0 { [200, 200, 30] } else { [200, 200, 220] })
+            .collect();
+        let layers = cluster_layers(&pixels);
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].0, 2);
+        assert_eq!(layers[0].1[0], 0);
+        assert_eq!(layers[0].1[1], 255);
+    }
+}
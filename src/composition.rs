@@ -0,0 +1,191 @@
+//! 变换组合搜索模块
+//!
+//! 单个变换都失败时，往往需要几种处理叠加才能解码，例如先 CLAHE 拉开对比度、再中值滤波
+//! 去噪、最后 Otsu 二值化。本模块从变换库中按处理阶段组合出 2-3 步的变换链，并按
+//! 预期收益与耗时之比排序。变换库中每个变换带有单独使用时的先验收益，本次运行中已经
+//! 成功过的变换会提高收益估计。
+
+use std::collections::HashMap;
+
+use crate::binarization::BinarizationMethod;
+use crate::deblur::DeconvolutionMethod;
+use crate::enhanced_processor::TransformType;
+use crate::glare::DEFAULT_GLARE_THRESHOLD;
+use crate::halftone::HalftoneRepair;
+use crate::illumination::BackgroundEstimate;
+use crate::moire::MoireFilter;
+
+/// 默认最多尝试的组合数
+pub const DEFAULT_MAX_COMPOSITIONS: usize = 48;
+
+/// 组合的最短和最长步数
+const MIN_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 3;
+
+/// 先验收益的等效样本数，成功次数越多越接近 1
+const PRIOR_WEIGHT: f64 = 4.0;
+
+/// 每次尝试解码的相对耗时，与变换耗时使用同一单位
+const DECODE_COST: f64 = 4.0;
+
+/// 处理阶段，组合中的变换按阶段先后排列，每个阶段至多一个
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// 恢复：光照、反光、摩尔纹、模糊
+    Restore,
+    /// 对比度增强
+    Contrast,
+    /// 去噪
+    Denoise,
+    /// 二值化
+    Binarize,
+    /// 二值图上的形态学修补
+    Morphology,
+}
+
+/// 变换库中的一项
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LibraryEntry {
+    /// 变换
+    pub transform: TransformType,
+    /// 所属阶段
+    pub stage: Stage,
+    /// 单独使用时帮助解码的先验概率
+    pub gain: f64,
+    /// 相对耗时
+    pub cost: f64,
+}
+
+/// 一条候选变换链
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composition {
+    /// 依次叠加的变换
+    pub chain: Vec<TransformType>,
+    /// 预期收益：至少一步起作用的概率
+    pub expected_gain: f64,
+    /// 相对耗时：各步耗时之和加一次解码
+    pub cost: f64,
+}
+
+impl Composition {
+    /// 排序分数：单位耗时的预期收益
+    pub fn score(&self) -> f64 {
+        self.expected_gain / self.cost.max(f64::EPSILON)
+    }
+}
+
+/// 默认变换库，`window` 为局部二值化窗口
+pub fn transform_library(window: i32) -> Vec<LibraryEntry> {
+    let entry = |transform, stage, gain, cost| LibraryEntry { transform, stage, gain, cost };
+    vec![
+        entry(TransformType::IlluminationCorrection(BackgroundEstimate::MorphClose(0)), Stage::Restore, 0.30, 3.0),
+        entry(TransformType::GlareRemoval(DEFAULT_GLARE_THRESHOLD), Stage::Restore, 0.15, 4.0),
+        entry(TransformType::MoireSuppression(MoireFilter::Notch { min_frequency: 0.15 }), Stage::Restore, 0.15, 6.0),
+        entry(TransformType::DeblurEstimated(DeconvolutionMethod::Wiener { snr: 100.0 }), Stage::Restore, 0.15, 8.0),
+        entry(TransformType::Clahe(2.0, 8), Stage::Contrast, 0.35, 1.0),
+        entry(TransformType::Gamma(0.6), Stage::Contrast, 0.20, 0.5),
+        entry(TransformType::LocalToneMapping(0.7), Stage::Contrast, 0.25, 2.0),
+        entry(TransformType::Sharpen, Stage::Contrast, 0.20, 1.0),
+        entry(TransformType::MedianBlur(3), Stage::Denoise, 0.25, 1.0),
+        entry(TransformType::GaussianBlur(3), Stage::Denoise, 0.20, 0.5),
+        entry(TransformType::BilateralFilter, Stage::Denoise, 0.20, 3.0),
+        entry(TransformType::Binarize(BinarizationMethod::Otsu), Stage::Binarize, 0.30, 0.5),
+        entry(TransformType::Binarize(BinarizationMethod::Sauvola { window, k: 0.34 }), Stage::Binarize, 0.35, 2.0),
+        entry(TransformType::Binarize(BinarizationMethod::Wolf { window, k: 0.5 }), Stage::Binarize, 0.25, 2.0),
+        entry(TransformType::MorphClose, Stage::Morphology, 0.15, 0.5),
+        entry(TransformType::Halftone(HalftoneRepair::MorphClose), Stage::Morphology, 0.15, 2.0),
+    ]
+}
+
+/// 生成 2-3 步的组合并按分数从高到低排序
+///
+/// `stats` 为增强解码的成功统计（键为步骤描述，变换链以 " → " 连接），其中出现过的
+/// 变换按成功次数提高收益估计。
+pub fn compose(library: &[LibraryEntry], stats: &HashMap<String, usize>) -> Vec<Composition> {
+    let gains: Vec<f64> = library
+        .iter()
+        .map(|entry| {
+            let successes = success_count(stats, &entry.transform) as f64;
+            (entry.gain * PRIOR_WEIGHT + successes) / (PRIOR_WEIGHT + successes)
+        })
+        .collect();
+
+    let mut compositions = Vec::new();
+    let mut chain = Vec::new();
+    extend_chains(library, &gains, 0, &mut chain, &mut compositions);
+    compositions.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    compositions
+}
+
+/// 深度优先枚举阶段严格递增的组合，`chain` 中为已选变换在库中的下标
+fn extend_chains(library: &[LibraryEntry], gains: &[f64], start: usize, chain: &mut Vec<usize>, out: &mut Vec<Composition>) {
+    if chain.len() >= MIN_DEPTH {
+        let miss: f64 = chain.iter().map(|&i| 1.0 - gains[i]).product();
+        out.push(Composition {
+            chain: chain.iter().map(|&i| library[i].transform).collect(),
+            expected_gain: 1.0 - miss,
+            cost: DECODE_COST + chain.iter().map(|&i| library[i].cost).sum::<f64>(),
+        });
+    }
+    if chain.len() == MAX_DEPTH {
+        return;
+    }
+
+    for next in start..library.len() {
+        let after_last = chain.last().is_none_or(|&last| library[next].stage > library[last].stage);
+        if after_last {
+            chain.push(next);
+            extend_chains(library, gains, next + 1, chain, out);
+            chain.pop();
+        }
+    }
+}
+
+/// 统计中包含该变换的成功次数
+fn success_count(stats: &HashMap<String, usize>, transform: &TransformType) -> usize {
+    let description = transform.description();
+    stats
+        .iter()
+        .filter(|(key, _)| key.split(" → ").any(|part| part == description))
+        .map(|(_, &count)| count)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compositions_follow_stage_order() {
+        let library = transform_library(31);
+        let compositions = compose(&library, &HashMap::new());
+
+        let stage = |t: &TransformType| library.iter().find(|e| e.transform == *t).unwrap().stage;
+        assert!(!compositions.is_empty());
+        for composition in &compositions {
+            assert!((MIN_DEPTH..=MAX_DEPTH).contains(&composition.chain.len()));
+            assert!(composition.chain.windows(2).all(|w| stage(&w[0]) < stage(&w[1])));
+        }
+        assert!(compositions.windows(2).all(|w| w[0].score() >= w[1].score()));
+
+        let wanted = vec![
+            TransformType::Clahe(2.0, 8),
+            TransformType::MedianBlur(3),
+            TransformType::Binarize(BinarizationMethod::Otsu),
+        ];
+        assert!(compositions.iter().any(|c| c.chain == wanted));
+    }
+
+    #[test]
+    fn test_statistics_raise_successful_transforms() {
+        let library = transform_library(31);
+        let baseline = compose(&library, &HashMap::new());
+        let rank = |compositions: &[Composition], t: TransformType| compositions.iter().position(|c| c.chain.contains(&t)).unwrap();
+
+        let bilateral = TransformType::BilateralFilter;
+        let mut stats = HashMap::new();
+        stats.insert(format!("{} → Otsu", bilateral.description()), 3);
+        let boosted = compose(&library, &stats);
+        assert!(rank(&boosted, bilateral) < rank(&baseline, bilateral));
+    }
+}
@@ -0,0 +1,443 @@
+//! 运动模糊与散焦去模糊模块
+//!
+//! 固定的 3x3 锐化核对真实的运动模糊帮助很小。本模块按模糊核（点扩散函数，PSF）
+//! 做反卷积：维纳滤波在频域中一步求解，Richardson-Lucy 在空域中迭代逼近。
+//! 模糊核可以是直线运动或圆盘散焦；`estimate_motion_blur` 根据梯度方向和方向导数的
+//! 自相关估计运动方向和长度，`candidate_kernels` 给出搜索时依次尝试的一组候选核。
+
+use opencv::{
+    core::{self, Mat, Point, Scalar, Vector, BORDER_REPLICATE, CV_32F, CV_8U, DFT_COMPLEX_OUTPUT, DFT_INVERSE, DFT_REAL_OUTPUT, DFT_SCALE},
+    imgproc::{self, cvt_color, COLOR_BGR2GRAY, COLOR_GRAY2BGR},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+
+/// 估计运动模糊时考虑的最大长度（像素）
+const MAX_BLUR_LENGTH: usize = 40;
+
+/// 方向导数自相关的最小值需要低于该值才认为存在运动模糊
+const MIN_AUTOCORRELATION_DIP: f64 = -0.2;
+
+/// 结构张量的最小各向异性 (0-1)：二维码模块本身各向同性，运动模糊会抹平一个方向的梯度
+const MIN_ANISOTROPY: f64 = 0.3;
+
+/// 模糊核（点扩散函数）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BlurKernel {
+    /// 直线运动：长度（像素）和方向（度，从 x 轴逆时针，0-180）
+    Motion { length: f64, angle: f64 },
+    /// 圆盘散焦：半径（像素）
+    Defocus { radius: f64 },
+}
+
+impl BlurKernel {
+    /// 获取模糊核的描述
+    pub fn description(&self) -> String {
+        match self {
+            BlurKernel::Motion { length, angle } => format!("运动 长度 {:.0} / 角度 {:.0}°", length, angle),
+            BlurKernel::Defocus { radius } => format!("散焦 半径 {:.1}", radius),
+        }
+    }
+}
+
+/// 反卷积方法
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DeconvolutionMethod {
+    /// 维纳滤波，`snr` 为信噪比，越大恢复越激进
+    Wiener { snr: f64 },
+    /// Richardson-Lucy 迭代
+    RichardsonLucy { iterations: u32 },
+}
+
+impl DeconvolutionMethod {
+    /// 获取反卷积方法的描述
+    pub fn description(&self) -> String {
+        match self {
+            DeconvolutionMethod::Wiener { snr } => format!("维纳 (SNR {:.0})", snr),
+            DeconvolutionMethod::RichardsonLucy { iterations } => format!("Richardson-Lucy ({} 次迭代)", iterations),
+        }
+    }
+}
+
+/// 搜索时依次尝试的候选模糊核
+///
+/// 有估计结果时先尝试估计值及其上下浮动的长度，再尝试常见的散焦半径和
+/// 水平、垂直、对角方向的运动模糊。
+pub fn candidate_kernels(estimate: Option<BlurKernel>) -> Vec<BlurKernel> {
+    let mut kernels = Vec::new();
+    if let Some(BlurKernel::Motion { length, angle }) = estimate {
+        for factor in [1.0, 0.75, 1.25] {
+            kernels.push(BlurKernel::Motion { length: (length * factor).round().max(2.0), angle });
+        }
+    }
+    kernels.extend([
+        BlurKernel::Defocus { radius: 2.0 },
+        BlurKernel::Defocus { radius: 4.0 },
+        BlurKernel::Motion { length: 9.0, angle: 0.0 },
+        BlurKernel::Motion { length: 9.0, angle: 90.0 },
+        BlurKernel::Motion { length: 9.0, angle: 45.0 },
+        BlurKernel::Motion { length: 9.0, angle: 135.0 },
+    ]);
+    kernels
+}
+
+/// 按模糊核反卷积，输入为彩色图像时返回转换回 BGR 的结果
+pub fn deblur(image: &Mat, kernel: BlurKernel, method: DeconvolutionMethod) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+    let mut float = Mat::default();
+    gray.convert_to(&mut float, CV_32F, 1.0, 0.0)?;
+
+    let (size, psf) = render_psf(kernel);
+    let restored = match method {
+        DeconvolutionMethod::Wiener { snr } => wiener(&float, size, &psf, snr)?,
+        DeconvolutionMethod::RichardsonLucy { iterations } => richardson_lucy(&float, size, &psf, iterations)?,
+    };
+
+    let mut result = Mat::default();
+    restored.convert_to(&mut result, CV_8U, 1.0, 0.0)?;
+
+    if image.channels() == 3 {
+        let mut color = Mat::default();
+        cvt_color(&result, &mut color, COLOR_GRAY2BGR, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转彩色失败: {}", e)))?;
+        Ok(color)
+    } else {
+        Ok(result)
+    }
+}
+
+/// 估计模糊核后反卷积；估计不出运动模糊时原样返回
+pub fn deblur_estimated(image: &Mat, method: DeconvolutionMethod) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+    let mut float = Mat::default();
+    gray.convert_to(&mut float, CV_32F, 1.0, 0.0)?;
+
+    match estimate_motion_blur(float.data_typed::<f32>()?, gray.rows() as usize, gray.cols() as usize) {
+        Some(kernel) => deblur(image, kernel, method),
+        None => Ok(image.clone()),
+    }
+}
+
+/// 维纳滤波：F = G·H* / (|H|² + 1/SNR)
+fn wiener(image: &Mat, size: usize, psf: &[f32], snr: f64) -> Result<Mat> {
+    // 四周复制一圈模糊核大小的边界，减轻循环卷积在图像边缘造成的振铃
+    let (rows, cols) = (image.rows(), image.cols());
+    let margin = size as i32;
+    let dft_rows = core::get_optimal_dft_size(rows + 2 * margin)?;
+    let dft_cols = core::get_optimal_dft_size(cols + 2 * margin)?;
+    let mut padded = Mat::default();
+    core::copy_make_border(
+        image,
+        &mut padded,
+        margin,
+        dft_rows - rows - margin,
+        margin,
+        dft_cols - cols - margin,
+        BORDER_REPLICATE,
+        Scalar::all(0.0),
+    )?;
+
+    let layout = psf_layout(size, psf, dft_rows as usize, dft_cols as usize);
+    let psf_mat = Mat::new_rows_cols_with_data(dft_rows, dft_cols, &layout)?.try_clone()?;
+
+    let mut spectrum = Mat::default();
+    core::dft(&padded, &mut spectrum, DFT_COMPLEX_OUTPUT, 0)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("傅里叶变换失败: {}", e)))?;
+    let mut otf = Mat::default();
+    core::dft(&psf_mat, &mut otf, DFT_COMPLEX_OUTPUT, 0)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("傅里叶变换失败: {}", e)))?;
+
+    let mut numerator = Mat::default();
+    core::mul_spectrums(&spectrum, &otf, &mut numerator, 0, true)?;
+
+    let mut otf_planes = Vector::<Mat>::new();
+    core::split(&otf, &mut otf_planes)?;
+    let mut magnitude = Mat::default();
+    core::magnitude(&otf_planes.get(0)?, &otf_planes.get(1)?, &mut magnitude)?;
+    let mut power = Mat::default();
+    core::multiply(&magnitude, &magnitude, &mut power, 1.0, -1)?;
+    let mut denominator = Mat::default();
+    power.convert_to(&mut denominator, CV_32F, 1.0, 1.0 / snr.max(f64::EPSILON))?;
+
+    let mut planes = Vector::<Mat>::new();
+    core::split(&numerator, &mut planes)?;
+    let mut divided = Vector::<Mat>::new();
+    for plane in planes.iter() {
+        let mut quotient = Mat::default();
+        core::divide2(&plane, &denominator, &mut quotient, 1.0, -1)?;
+        divided.push(quotient);
+    }
+    let mut filtered = Mat::default();
+    core::merge(&divided, &mut filtered)?;
+
+    let mut restored = Mat::default();
+    core::dft(&filtered, &mut restored, DFT_INVERSE | DFT_SCALE | DFT_REAL_OUTPUT, 0)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("傅里叶逆变换失败: {}", e)))?;
+    Ok(Mat::roi(&restored, core::Rect::new(margin, margin, cols, rows))?.try_clone()?)
+}
+
+/// Richardson-Lucy 迭代：u ← u · (K ⋆ (g / (K ⋆ u)))
+///
+/// 直线运动核和圆盘核都关于中心对称，相关与卷积相同，可以直接使用 filter_2d。
+fn richardson_lucy(image: &Mat, size: usize, psf: &[f32], iterations: u32) -> Result<Mat> {
+    let kernel = Mat::new_rows_cols_with_data(size as i32, size as i32, psf)?.try_clone()?;
+    let filter = |src: &Mat| -> Result<Mat> {
+        let mut dst = Mat::default();
+        imgproc::filter_2d(src, &mut dst, -1, &kernel, Point::new(-1, -1), 0.0, BORDER_REPLICATE)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("卷积失败: {}", e)))?;
+        Ok(dst)
+    };
+
+    let mut observed = Mat::default();
+    image.convert_to(&mut observed, CV_32F, 1.0, 1.0)?;
+    let mut estimate = observed.try_clone()?;
+    for _ in 0..iterations {
+        let mut blurred = Mat::default();
+        filter(&estimate)?.convert_to(&mut blurred, CV_32F, 1.0, 1e-3)?;
+        let mut ratio = Mat::default();
+        core::divide2(&observed, &blurred, &mut ratio, 1.0, -1)?;
+        let correction = filter(&ratio)?;
+        let mut next = Mat::default();
+        core::multiply(&estimate, &correction, &mut next, 1.0, -1)?;
+        estimate = next;
+    }
+
+    let mut result = Mat::default();
+    estimate.convert_to(&mut result, CV_32F, 1.0, -1.0)?;
+    Ok(result)
+}
+
+/// 绘制模糊核，返回边长（奇数）和归一化后的按行排列的权重
+pub fn render_psf(kernel: BlurKernel) -> (usize, Vec<f32>) {
+    match kernel {
+        BlurKernel::Motion { length, angle } => {
+            let length = length.max(1.0);
+            let size = (length.ceil() as usize / 2) * 2 + 3;
+            let centre = (size / 2) as f64;
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let mut weights = vec![0f32; size * size];
+
+            // 沿线段均匀取样，双线性分配到相邻像素
+            let samples = (length * 4.0).ceil() as usize + 1;
+            for i in 0..samples {
+                let t = -length / 2.0 + length * i as f64 / (samples - 1).max(1) as f64;
+                let (x, y) = (centre + t * cos, centre - t * sin);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                for (dy, wy) in [(0usize, 1.0 - fy), (1, fy)] {
+                    for (dx, wx) in [(0usize, 1.0 - fx), (1, fx)] {
+                        let (px, py) = (x0 as usize + dx, y0 as usize + dy);
+                        if px < size && py < size {
+                            weights[py * size + px] += (wx * wy) as f32;
+                        }
+                    }
+                }
+            }
+            normalize(&mut weights);
+            (size, weights)
+        }
+        BlurKernel::Defocus { radius } => {
+            let radius = radius.max(0.5);
+            let size = radius.ceil() as usize * 2 + 1;
+            let centre = (size / 2) as f64;
+            let mut weights = vec![0f32; size * size];
+
+            // 每个像素 4x4 超采样，按落在圆盘内的比例计权
+            for y in 0..size {
+                for x in 0..size {
+                    let mut inside = 0;
+                    for sy in 0..4 {
+                        for sx in 0..4 {
+                            let px = x as f64 - 0.375 + sx as f64 * 0.25 - centre;
+                            let py = y as f64 - 0.375 + sy as f64 * 0.25 - centre;
+                            inside += (px * px + py * py <= radius * radius) as u32;
+                        }
+                    }
+                    weights[y * size + x] = inside as f32 / 16.0;
+                }
+            }
+            normalize(&mut weights);
+            (size, weights)
+        }
+    }
+}
+
+/// 把模糊核放到 DFT 尺寸的图像中，中心移到原点（循环平移）
+fn psf_layout(size: usize, psf: &[f32], rows: usize, cols: usize) -> Vec<f32> {
+    let mut layout = vec![0f32; rows * cols];
+    let centre = (size / 2) as isize;
+    for y in 0..size {
+        for x in 0..size {
+            let ty = (y as isize - centre).rem_euclid(rows as isize) as usize;
+            let tx = (x as isize - centre).rem_euclid(cols as isize) as usize;
+            layout[ty * cols + tx] += psf[y * size + x];
+        }
+    }
+    layout
+}
+
+fn normalize(weights: &mut [f32]) {
+    let sum: f32 = weights.iter().sum();
+    if sum > 0.0 {
+        weights.iter_mut().for_each(|w| *w /= sum);
+    }
+}
+
+/// 估计直线运动模糊
+///
+/// 运动方向上的边缘被抹平，梯度能量集中在垂直于运动的方向，由结构张量得到方向
+/// （各向异性不足时认为没有运动模糊）；
+/// 沿运动方向的导数等于原图与一对相距 L 的正负脉冲的卷积，其自相关在滞后 L 处
+/// 出现明显的负峰，由此得到长度。没有明显负峰时返回 None。
+pub fn estimate_motion_blur(pixels: &[f32], rows: usize, cols: usize) -> Option<BlurKernel> {
+    if rows < 3 || cols < 3 {
+        return None;
+    }
+
+    let mut gx = vec![0f64; rows * cols];
+    let mut gy = vec![0f64; rows * cols];
+    let (mut jxx, mut jyy, mut jxy) = (0f64, 0f64, 0f64);
+    for y in 1..rows - 1 {
+        for x in 1..cols - 1 {
+            let i = y * cols + x;
+            let dx = (pixels[i + 1] - pixels[i - 1]) as f64 / 2.0;
+            let dy = (pixels[i + cols] - pixels[i - cols]) as f64 / 2.0;
+            gx[i] = dx;
+            gy[i] = dy;
+            jxx += dx * dx;
+            jyy += dy * dy;
+            jxy += dx * dy;
+        }
+    }
+
+    let anisotropy = ((jxx - jyy).powi(2) + 4.0 * jxy * jxy).sqrt() / (jxx + jyy).max(f64::EPSILON);
+    if anisotropy < MIN_ANISOTROPY {
+        return None;
+    }
+
+    // 梯度主方向（y 轴向下），运动方向与之垂直；换算为 y 轴向上的逆时针角度
+    let gradient = 0.5 * (2.0 * jxy).atan2(jxx - jyy);
+    let motion = gradient + std::f64::consts::FRAC_PI_2;
+    let angle = (-motion.to_degrees()).rem_euclid(180.0);
+    let (sin, cos) = angle.to_radians().sin_cos();
+
+    let derivative: Vec<f64> = gx.iter().zip(&gy).map(|(&dx, &dy)| dx * cos - dy * sin).collect();
+    let energy: f64 = derivative.iter().map(|d| d * d).sum();
+    if energy <= f64::EPSILON {
+        return None;
+    }
+
+    let mut best = (0usize, 0f64);
+    for lag in 2..=MAX_BLUR_LENGTH.min(rows.min(cols) / 2) {
+        let ox = (lag as f64 * cos).round() as isize;
+        let oy = (-(lag as f64) * sin).round() as isize;
+        let mut sum = 0f64;
+        for y in 0..rows as isize {
+            let ny = y + oy;
+            if ny < 0 || ny >= rows as isize {
+                continue;
+            }
+            for x in 0..cols as isize {
+                let nx = x + ox;
+                if nx < 0 || nx >= cols as isize {
+                    continue;
+                }
+                sum += derivative[y as usize * cols + x as usize] * derivative[ny as usize * cols + nx as usize];
+            }
+        }
+        let correlation = sum / energy;
+        if correlation < best.1 {
+            best = (lag, correlation);
+        }
+    }
+
+    if best.1 < MIN_AUTOCORRELATION_DIP {
+        Some(BlurKernel::Motion { length: best.0 as f64, angle: angle.round() })
+    } else {
+        None
+    }
+}
+
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    if image.channels() == 1 {
+        return Ok(image.clone());
+    }
+
+    let mut gray = Mat::default();
+    cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+    Ok(gray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 8 像素随机方块，水平方向做长度 9 的运动模糊
+    fn motion_blurred_blocks() -> (Vec<f32>, Vec<f32>, usize) {
+        let size = 96;
+        let mut seed = 12345u32;
+        let blocks: Vec<f32> = (0..144)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                if (seed >> 16) & 1 == 1 { 220.0 } else { 30.0 }
+            })
+            .collect();
+        let sharp: Vec<f32> = (0..size * size).map(|i| blocks[(i / size / 8) * 12 + (i % size) / 8]).collect();
+        let blurred = (0..size * size)
+            .map(|i| {
+                let (y, x) = (i / size, (i % size) as isize);
+                (-4..=4).map(|d| sharp[y * size + (x + d).clamp(0, size as isize - 1) as usize]).sum::<f32>() / 9.0
+            })
+            .collect();
+        (sharp, blurred, size)
+    }
+
+    #[test]
+    fn test_render_psf() {
+        for kernel in [BlurKernel::Motion { length: 9.0, angle: 30.0 }, BlurKernel::Defocus { radius: 3.0 }] {
+            let (size, psf) = render_psf(kernel);
+            assert_eq!(size % 2, 1);
+            assert!((psf.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+            // 关于中心对称
+            for i in 0..psf.len() {
+                assert!((psf[i] - psf[psf.len() - 1 - i]).abs() < 1e-4, "{:?}", kernel);
+            }
+        }
+
+        let (size, psf) = render_psf(BlurKernel::Motion { length: 5.0, angle: 0.0 });
+        let middle = &psf[(size / 2) * size..(size / 2 + 1) * size];
+        assert!((middle.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_estimate_motion_blur() {
+        let (sharp, blurred, size) = motion_blurred_blocks();
+        match estimate_motion_blur(&blurred, size, size) {
+            Some(BlurKernel::Motion { length, angle }) => {
+                assert!((8.0..=10.0).contains(&length), "length {}", length);
+                assert!(angle <= 10.0 || angle >= 170.0, "angle {}", angle);
+            }
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(candidate_kernels(None).len(), 6);
+        assert!(estimate_motion_blur(&sharp, size, size).is_none());
+    }
+
+    #[test]
+    fn test_wiener_restores_edges() {
+        let (sharp, blurred, size) = motion_blurred_blocks();
+        let image = Mat::new_rows_cols_with_data(size as i32, size as i32, &blurred).unwrap().try_clone().unwrap();
+        let (psf_size, psf) = render_psf(BlurKernel::Motion { length: 9.0, angle: 0.0 });
+        let restored = wiener(&image, psf_size, &psf, 100.0).unwrap();
+        let restored = restored.data_typed::<f32>().unwrap();
+
+        // 只比较远离边界的中间区域
+        let error = |data: &[f32]| -> f32 {
+            (24..72).flat_map(|y| (24..72).map(move |x| y * size + x)).map(|i| (data[i] - sharp[i]).abs()).sum()
+        };
+        assert!(error(restored) < error(&blurred) * 0.6, "{} vs {}", error(restored), error(&blurred));
+    }
+}
@@ -5,7 +5,8 @@
 //! 提高二维码解码的成功率。
 
 use opencv::{
-    core::{Mat, Scalar, Size, CV_8UC1, CV_8UC3},
+    core::{Mat, Scalar, Size, Vector, CV_8UC1, CV_8UC3},
+    imgcodecs,
     imgproc::{
         cvt_color, gaussian_blur, COLOR_BGR2GRAY, COLOR_GRAY2BGR,
         bilateral_filter, median_blur, morphology_ex, MORPH_CLOSE, MORPH_OPEN,
@@ -13,14 +14,30 @@ use opencv::{
     },
     prelude::*,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
 
+use crate::binarization::{self, BinarizationMethod};
+use crate::composition;
+use crate::deblur::{self, BlurKernel, DeconvolutionMethod};
 use crate::error::{QRDecodeError, Result};
+use crate::geometry::{self, GeometricTransform, Mirror};
+use crate::glare;
+use crate::halftone::{self, HalftoneRepair};
+use crate::illumination::{self, BackgroundEstimate};
+use crate::local_contrast;
+use crate::logo;
+use crate::moire::{self, MoireFilter};
+use crate::pipeline::{self, PipelineStep};
 use crate::qr_decoder::QRDecoder;
+use crate::transform_dsl;
+use crate::transform_stats::{self, TransformStats};
 use crate::types::{QRCodeResult, ProcessingConfig};
 
 /// 图像变换类型
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TransformType {
     /// 原始图像
     Original,
@@ -44,6 +61,38 @@ pub enum TransformType {
     Sharpen,
     /// 组合变换：亮度+对比度
     BrightnessContrast(i32, f64),
+    /// 水平镜像（透过玻璃拍摄或镜像打印）
+    MirrorHorizontal,
+    /// 垂直镜像
+    MirrorVertical,
+    /// 逆时针旋转（度）
+    Rotate(i32),
+    /// CLAHE：限幅、网格大小
+    Clahe(f64, i32),
+    /// 局部色调映射：光照压缩强度
+    LocalToneMapping(f64),
+    /// 局部二值化（Sauvola、Niblack、Wolf、多级 Otsu）
+    Binarize(BinarizationMethod),
+    /// 光照不均校正：估计背景后除去
+    IlluminationCorrection(BackgroundEstimate),
+    /// 去除镜面反光：检测亮于纸面的饱和区域并修复
+    GlareRemoval(u8),
+    /// 频域摩尔纹抑制（屏幕翻拍）
+    MoireSuppression(MoireFilter),
+    /// 按指定模糊核反卷积
+    Deblur(BlurKernel, DeconvolutionMethod),
+    /// 先估计运动模糊核再反卷积
+    DeblurEstimated(DeconvolutionMethod),
+    /// 用中性模块覆盖符号中心的 logo
+    LogoInpainting,
+    /// 按定位图形估计的模块间距把点状模块合并回整块模块
+    Halftone(HalftoneRepair),
+    /// 转换为灰度图
+    Grayscale,
+    /// 按比例缩放
+    Scale(f64),
+    /// 反色
+    Invert,
 }
 
 impl TransformType {
@@ -61,6 +110,35 @@ impl TransformType {
             TransformType::MorphClose => "形态学闭运算".to_string(),
             TransformType::Sharpen => "锐化".to_string(),
             TransformType::BrightnessContrast(b, c) => format!("亮度+对比度: {} / {:.2}", b, c),
+            TransformType::MirrorHorizontal => "水平镜像".to_string(),
+            TransformType::MirrorVertical => "垂直镜像".to_string(),
+            TransformType::Rotate(degrees) => format!("旋转: {}°", degrees),
+            TransformType::Clahe(clip, tiles) => format!("CLAHE: 限幅 {:.1} / 网格 {}x{}", clip, tiles, tiles),
+            TransformType::LocalToneMapping(strength) => format!("局部色调映射: {:.2}", strength),
+            TransformType::Binarize(method) => format!("二值化: {}", method.description()),
+            TransformType::IlluminationCorrection(estimate) => format!("光照校正: {}", estimate.description()),
+            TransformType::GlareRemoval(threshold) => format!("去除反光: 阈值 {}", threshold),
+            TransformType::MoireSuppression(filter) => format!("摩尔纹抑制: {}", filter.description()),
+            TransformType::Deblur(kernel, method) => format!("去模糊: {} / {}", kernel.description(), method.description()),
+            TransformType::DeblurEstimated(method) => format!("去模糊: 估计运动核 / {}", method.description()),
+            TransformType::LogoInpainting => "覆盖中心 logo".to_string(),
+            TransformType::Halftone(repair) => format!("点状模块修复: {}", repair.description()),
+            TransformType::Grayscale => "灰度转换".to_string(),
+            TransformType::Scale(factor) => format!("缩放: {:.2}x", factor),
+            TransformType::Invert => "反色".to_string(),
+        }
+    }
+    
+    /// 获取几何变换，非几何变换返回 None
+    ///
+    /// 几何变换会改变坐标系，解码结果需要通过它映射回原图。
+    pub fn geometry(&self, width: i32, height: i32) -> Option<GeometricTransform> {
+        match self {
+            TransformType::MirrorHorizontal => Some(GeometricTransform::mirror(Mirror::Horizontal, width, height)),
+            TransformType::MirrorVertical => Some(GeometricTransform::mirror(Mirror::Vertical, width, height)),
+            TransformType::Rotate(degrees) => Some(GeometricTransform::rotation(*degrees as f64, width, height)),
+            TransformType::Scale(factor) => Some(GeometricTransform::identity(width, height).scaled(*factor)),
+            _ => None,
         }
     }
 }
@@ -73,17 +151,32 @@ pub struct EnhancedImageProcessor {
     decoder_config: ProcessingConfig,
     /// 变换尝试统计
     transform_stats: HashMap<String, usize>,
+    /// 由 `--pipeline`/`--profile` 指定的步骤，None 时使用内置序列
+    pipeline: Option<Vec<PipelineStep>>,
+    /// 由 `--transform-stats` 加载的跨运行统计，用于重新排列步骤
+    learned: Option<TransformStats>,
+    /// 跨运行统计所属的配置名
+    stats_profile: String,
 }
 
 impl EnhancedImageProcessor {
     /// 创建新的增强图像处理器
     pub fn new(config: ProcessingConfig) -> Result<Self> {
         let decoder_config = config.clone();
+        let pipeline = pipeline::resolve_pipeline(config.pipeline.as_deref(), config.profile.as_deref(), config.binarization_window)?;
+        let learned = match &config.transform_stats {
+            Some(path) => Some(TransformStats::load(path)?),
+            None => None,
+        };
+        let stats_profile = config.profile.clone().unwrap_or_else(|| transform_stats::DEFAULT_PROFILE.to_string());
         
         Ok(Self {
             config,
             decoder_config,
             transform_stats: HashMap::new(),
+            pipeline,
+            learned,
+            stats_profile,
         })
     }
     
@@ -93,23 +186,77 @@ impl EnhancedImageProcessor {
             println!("🔄 开始增强图像预处理解码...");
         }
         
-        // 定义要尝试的变换序列
-        let transforms = self.get_transform_sequence();
+        // 定义要尝试的变换序列：命令行变换链在前，然后是流水线配置或内置序列
+        let mut steps = Vec::new();
+        if let Some(chain) = &self.config.transforms {
+            steps.push(PipelineStep::Chain { chain: chain.clone() });
+        }
+        if !self.config.transforms_only {
+            let sequence = self.pipeline.clone().unwrap_or_else(|| {
+                self.get_transform_sequence().into_iter().map(PipelineStep::Single).collect()
+            });
+            
+            // 有跨运行统计时按单位耗时的成功概率重新排列
+            steps.extend(match &self.learned {
+                Some(learned) => learned.reorder(&self.stats_profile, sequence, PipelineStep::description),
+                None => sequence,
+            });
+            
+            // 单步变换都失败后，按预期收益尝试组合变换链
+            let success_counts = match &self.learned {
+                Some(learned) => learned.success_counts(&self.stats_profile),
+                None => self.transform_stats.clone(),
+            };
+            let library = composition::transform_library(self.config.binarization_window);
+            let compositions: Vec<PipelineStep> = composition::compose(&library, &success_counts)
+                .into_iter()
+                .map(|composition| PipelineStep::Chain { chain: composition.chain })
+                .filter(|step| !steps.contains(step))
+                .take(self.config.composition_limit())
+                .collect();
+            steps.extend(compositions);
+        }
         
-        for (i, transform) in transforms.iter().enumerate() {
+        // 命令行变换链的每一步可以保存下来检查
+        let dump_path = match (&self.config.transforms, &self.config.processed_output_path) {
+            (Some(_), Some(path)) if self.config.save_processed => Some(path.clone()),
+            _ => None,
+        };
+        
+        let started = Instant::now();
+        for (i, step) in steps.iter().enumerate() {
+            if let Some(budget) = self.config.time_budget {
+                if started.elapsed().as_secs_f64() >= budget {
+                    if self.config.verbose {
+                        println!("   ⏱️ 已用完 {:.1} 秒时间预算，跳过剩余 {} 个变换", budget, steps.len() - i);
+                    }
+                    break;
+                }
+            }
+            
             if self.config.verbose {
-                println!("   [{}/{}] 尝试变换: {}", i + 1, transforms.len(), transform.description());
+                println!("   [{}/{}] 尝试变换: {}", i + 1, steps.len(), step.description());
             }
             
             // 应用变换
-            match self.apply_transform(image, *transform) {
-                Ok(transformed_image) => {
+            let step_started = Instant::now();
+            let dump = if i == 0 { dump_path.as_deref() } else { None };
+            match self.apply_step(image, step, dump) {
+                Ok((transformed_image, geometry)) => {
                     // 创建新的解码器实例并尝试解码变换后的图像
                     let mut decoder = QRDecoder::new(&self.decoder_config);
                     match decoder.decode_qr_codes(&transformed_image) {
                         Ok(results) if !results.is_empty() => {
+                            // 几何变换后的坐标映射回原图
+                            let results: Vec<QRCodeResult> = match geometry {
+                                Some(geometry) => results.into_iter().map(|r| geometry.map_result(r)).collect(),
+                                None => results,
+                            };
+                            
                             // 记录成功的变换
-                            *self.transform_stats.entry(transform.description()).or_insert(0) += 1;
+                            *self.transform_stats.entry(step.description()).or_insert(0) += 1;
+                            self.record_attempt(step, true, step_started.elapsed().as_secs_f64());
+                            self.save_learned()?;
                             
                             if self.config.verbose {
                                 println!("   ✅ 解码成功! 找到 {} 个二维码", results.len());
@@ -139,19 +286,36 @@ impl EnhancedImageProcessor {
                     }
                 }
             }
+            self.record_attempt(step, false, step_started.elapsed().as_secs_f64());
         }
         
         if self.config.verbose {
             println!("❌ 所有变换尝试均失败");
         }
         
+        self.save_learned()?;
         Ok(vec![])
     }
     
+    /// 把一次尝试记入跨运行统计
+    fn record_attempt(&mut self, step: &PipelineStep, success: bool, seconds: f64) {
+        if let Some(learned) = &mut self.learned {
+            learned.record(&self.stats_profile, &step.description(), success, seconds);
+        }
+    }
+    
+    /// 保存跨运行统计
+    fn save_learned(&self) -> Result<()> {
+        match (&self.learned, &self.config.transform_stats) {
+            (Some(learned), Some(path)) => learned.save(path),
+            _ => Ok(()),
+        }
+    }
+    
     /// 获取变换序列
     /// 基于 QReader 和 LoveLy-QRCode-Scanner 的优化策略
     fn get_transform_sequence(&self) -> Vec<TransformType> {
-        vec![
+        let mut transforms = vec![
             // 1. 首先尝试原始图像
             TransformType::Original,
             
@@ -169,7 +333,20 @@ impl EnhancedImageProcessor {
             TransformType::BrightnessContrast(25, 0.7),
             TransformType::BrightnessContrast(-25, 0.7),
             
-            // 4. 中等强度调整
+            // 4. 局部对比度增强、光照校正、去反光和 logo 修复（聚光灯、阴影、光面包装、品牌二维码）
+            TransformType::Clahe(self.config.clahe_clip_limit, self.config.clahe_tile_size),
+            TransformType::Clahe(4.0, 8),
+            TransformType::Clahe(3.0, 16),
+            TransformType::LocalToneMapping(0.7),
+            TransformType::LocalToneMapping(0.9),
+            TransformType::IlluminationCorrection(BackgroundEstimate::MorphClose(0)),
+            TransformType::IlluminationCorrection(BackgroundEstimate::BlackHat(0)),
+            TransformType::IlluminationCorrection(BackgroundEstimate::Blur(0)),
+            TransformType::GlareRemoval(glare::DEFAULT_GLARE_THRESHOLD),
+            TransformType::GlareRemoval(210),
+            TransformType::LogoInpainting,
+            
+            // 5. 中等强度调整
             TransformType::Brightness(40),
             TransformType::Brightness(-40),
             TransformType::Contrast(1.5),
@@ -177,20 +354,42 @@ impl EnhancedImageProcessor {
             TransformType::Gamma(0.5),
             TransformType::Gamma(1.5),
             
-            // 5. 滤波和降噪（对模糊图像有效）
+            // 6. 滤波和降噪（对模糊图像、屏幕翻拍的摩尔纹有效）
             TransformType::BilateralFilter,
             TransformType::MedianBlur(3),
             TransformType::GaussianBlur(3),
             TransformType::MedianBlur(5),
+            TransformType::MoireSuppression(MoireFilter::Notch { min_frequency: 0.15 }),
+            TransformType::MoireSuppression(MoireFilter::Notch { min_frequency: 0.3 }),
+            TransformType::MoireSuppression(MoireFilter::LowPass { cutoff: 0.2 }),
+            TransformType::MoireSuppression(MoireFilter::LowPass { cutoff: 0.1 }),
             
-            // 6. 锐化（对模糊二维码特别有效）
+            // 7. 锐化和反卷积去模糊（运动模糊、失焦）
             TransformType::Sharpen,
-            
-            // 7. 形态学操作（对噪声图像有效）
+            TransformType::DeblurEstimated(DeconvolutionMethod::Wiener { snr: 100.0 }),
+            TransformType::DeblurEstimated(DeconvolutionMethod::RichardsonLucy { iterations: 20 }),
+        ];
+        transforms.extend(
+            deblur::candidate_kernels(None)
+                .into_iter()
+                .map(|kernel| TransformType::Deblur(kernel, DeconvolutionMethod::Wiener { snr: 100.0 })),
+        );
+        transforms.extend([
+            // 8. 形态学操作（对噪声图像有效）
             TransformType::MorphOpen,
             TransformType::MorphClose,
-            
-            // 8. 强烈调整（最后尝试）
+            TransformType::Halftone(HalftoneRepair::MorphClose),
+            TransformType::Halftone(HalftoneRepair::CentreSampling),
+        ]);
+        
+        // 9. 局部二值化（低对比度、光照不均）
+        transforms.extend(
+            BinarizationMethod::local_family(self.config.binarization_window)
+                .into_iter()
+                .map(TransformType::Binarize),
+        );
+        transforms.extend([
+            // 10. 强烈调整（最后尝试）
             TransformType::Brightness(60),
             TransformType::Brightness(-60),
             TransformType::Contrast(2.0),
@@ -198,16 +397,57 @@ impl EnhancedImageProcessor {
             TransformType::Gamma(0.3),
             TransformType::Gamma(2.2),
             
-            // 9. 极端组合变换
+            // 11. 极端组合变换
             TransformType::BrightnessContrast(50, 1.8),
             TransformType::BrightnessContrast(-50, 1.8),
             TransformType::BrightnessContrast(40, 0.5),
             TransformType::BrightnessContrast(-40, 0.5),
             
-            // 10. 模糊处理的最后尝试
+            // 12. 模糊处理的最后尝试
             TransformType::GaussianBlur(5),
             TransformType::GaussianBlur(7),
-        ]
+        ]);
+        
+        // 13. 镜像（透过玻璃拍摄、镜像打印）
+        transforms.push(TransformType::MirrorHorizontal);
+        transforms.push(TransformType::MirrorVertical);
+        
+        // 14. 旋转扫描（检测器能处理 90° 的整数倍，只需扫描中间角度）
+        transforms.extend(
+            geometry::rotation_sweep(geometry::ROTATION_STEP_DEGREES)
+                .into_iter()
+                .filter(|&degrees| degrees != 0)
+                .map(TransformType::Rotate),
+        );
+        
+        transforms
+    }
+    
+    /// 应用一个步骤，变换链依次叠加，返回结果图像和累计的几何变换
+    ///
+    /// 指定 `dump` 时把每个变换的输出保存为 `<文件名>_stepNN`。
+    fn apply_step(&self, image: &Mat, step: &PipelineStep, dump: Option<&Path>) -> Result<(Mat, Option<GeometricTransform>)> {
+        let mut current = image.clone();
+        let mut geometry: Option<GeometricTransform> = None;
+        for (i, &transform) in step.transforms().iter().enumerate() {
+            if let Some(next) = transform.geometry(current.cols(), current.rows()) {
+                geometry = Some(match geometry {
+                    Some(previous) => previous.then(&next),
+                    None => next,
+                });
+            }
+            current = self.apply_transform(&current, transform)?;
+            
+            if let Some(base) = dump {
+                let path = transform_dsl::step_output_path(base, i + 1);
+                imgcodecs::imwrite(&path.to_string_lossy(), &current, &Vector::new())
+                    .map_err(|e| QRDecodeError::image_processing_error(format!("保存图像失败 {}: {}", path.display(), e)))?;
+                if self.config.verbose {
+                    println!("      💾 {} 已保存到: {}", transform.description(), path.display());
+                }
+            }
+        }
+        Ok((current, geometry))
     }
     
     /// 应用指定的变换
@@ -227,6 +467,40 @@ impl EnhancedImageProcessor {
                 let temp = self.adjust_brightness(image, brightness)?;
                 self.adjust_contrast(&temp, contrast)
             }
+            TransformType::Clahe(clip_limit, tile_size) => local_contrast::apply_clahe(image, clip_limit, tile_size),
+            TransformType::LocalToneMapping(strength) => local_contrast::apply_local_tone_mapping(image, strength),
+            TransformType::IlluminationCorrection(estimate) => illumination::normalize_illumination(image, estimate),
+            TransformType::GlareRemoval(threshold) => glare::remove_glare(image, threshold),
+            TransformType::MoireSuppression(filter) => moire::suppress_moire(image, filter),
+            TransformType::Deblur(kernel, method) => deblur::deblur(image, kernel, method),
+            TransformType::DeblurEstimated(method) => deblur::deblur_estimated(image, method),
+            TransformType::LogoInpainting => logo::inpaint_logo(image),
+            TransformType::Halftone(repair) => halftone::repair_halftone(image, repair),
+            TransformType::Grayscale => self.to_grayscale_if_needed(image),
+            TransformType::Invert => {
+                let mut inverted = Mat::default();
+                opencv::core::bitwise_not(image, &mut inverted, &opencv::core::no_array())
+                    .map_err(|e| QRDecodeError::image_processing_error(format!("反色处理失败: {}", e)))?;
+                Ok(inverted)
+            }
+            TransformType::Binarize(method) => {
+                let binary = binarization::binarize(image, method)?;
+                // 如果原图是彩色的，转换回彩色
+                if image.channels() == 3 {
+                    let mut color_result = Mat::default();
+                    cvt_color(&binary, &mut color_result, COLOR_GRAY2BGR, 0, opencv::core::AlgorithmHint::ALGO_HINT_DEFAULT)
+                        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转彩色失败: {}", e)))?;
+                    Ok(color_result)
+                } else {
+                    Ok(binary)
+                }
+            }
+            TransformType::MirrorHorizontal | TransformType::MirrorVertical | TransformType::Rotate(_) | TransformType::Scale(_) => {
+                match transform.geometry(image.cols(), image.rows()) {
+                    Some(geometry) => geometry.apply(image),
+                    None => Ok(image.clone()),
+                }
+            }
         }
     }
     
@@ -401,6 +675,21 @@ impl EnhancedImageProcessor {
         for (transform, count) in stats {
             println!("   {} : {} 次", transform, count);
         }
+        
+        if let Some(steps) = self.learned.as_ref().and_then(|learned| learned.steps(&self.stats_profile)) {
+            println!("📈 累计变换统计 (配置 {}):", self.stats_profile);
+            let mut steps: Vec<_> = steps.iter().filter(|(_, stats)| stats.successes > 0).collect();
+            steps.sort_by(|a, b| b.1.successes.cmp(&a.1.successes));
+            for (step, stats) in steps {
+                println!(
+                    "   {} : {}/{} 次成功，平均 {:.3} 秒",
+                    step,
+                    stats.successes,
+                    stats.attempts,
+                    stats.average_cost().unwrap_or(0.0)
+                );
+            }
+        }
     }
 }
 
@@ -421,6 +710,7 @@ mod tests {
             min_confidence: 0.5,
             save_processed: false,
             processed_output_path: None,
+            ..ProcessingConfig::default()
         }
     }
     
@@ -429,6 +719,30 @@ mod tests {
         assert_eq!(TransformType::Original.description(), "原始图像");
         assert_eq!(TransformType::Brightness(30).description(), "亮度调整: 30");
         assert_eq!(TransformType::Contrast(1.5).description(), "对比度调整: 1.50");
+        assert_eq!(TransformType::Rotate(15).description(), "旋转: 15°");
+        assert_eq!(TransformType::Clahe(2.0, 8).description(), "CLAHE: 限幅 2.0 / 网格 8x8");
+        assert_eq!(
+            TransformType::IlluminationCorrection(BackgroundEstimate::MorphClose(0)).description(),
+            "光照校正: 闭运算 (自动核)"
+        );
+    }
+    
+    #[test]
+    fn test_geometric_transforms_in_sequence() {
+        let processor = EnhancedImageProcessor::new(create_test_config()).unwrap();
+        let transforms = processor.get_transform_sequence();
+        
+        assert!(transforms.contains(&TransformType::MirrorHorizontal));
+        assert!(transforms.contains(&TransformType::MirrorVertical));
+        assert!(transforms.contains(&TransformType::Rotate(45)));
+        assert!(transforms.iter().any(|t| matches!(t, TransformType::Binarize(BinarizationMethod::Sauvola { .. }))));
+        assert!(transforms.contains(&TransformType::GlareRemoval(glare::DEFAULT_GLARE_THRESHOLD)));
+        assert!(transforms.contains(&TransformType::LogoInpainting));
+        assert!(transforms.contains(&TransformType::Halftone(HalftoneRepair::CentreSampling)));
+        assert!(transforms.iter().any(|t| matches!(t, TransformType::MoireSuppression(MoireFilter::Notch { .. }))));
+        assert!(transforms.iter().any(|t| matches!(t, TransformType::Deblur(BlurKernel::Defocus { .. }, _))));
+        assert!(TransformType::Rotate(30).geometry(640, 480).is_some());
+        assert!(TransformType::Sharpen.geometry(640, 480).is_none());
     }
     
     #[test]
@@ -440,4 +754,27 @@ mod tests {
         assert!(!transforms.is_empty());
         assert_eq!(transforms[0], TransformType::Original);
     }
+    
+    #[test]
+    fn test_profile_selects_pipeline() {
+        let config = ProcessingConfig { profile: Some("art-qr".to_string()), ..create_test_config() };
+        let processor = EnhancedImageProcessor::new(config).unwrap();
+        let steps = processor.pipeline.as_ref().unwrap();
+        assert!(steps.iter().any(|step| step.transforms().len() > 1));
+        
+        let config = ProcessingConfig { profile: Some("missing".to_string()), ..create_test_config() };
+        assert!(EnhancedImageProcessor::new(config).is_err());
+    }
+    
+    #[test]
+    fn test_compositions_opt_in() {
+        assert_eq!(create_test_config().composition_limit(), 0);
+        let profiled = ProcessingConfig { profile: Some("art-qr".to_string()), ..create_test_config() };
+        assert_eq!(profiled.composition_limit(), composition::DEFAULT_MAX_COMPOSITIONS);
+        let auto = ProcessingConfig { auto: true, ..create_test_config() };
+        assert_eq!(auto.composition_limit(), composition::DEFAULT_MAX_COMPOSITIONS);
+        let explicit = ProcessingConfig { max_compositions: Some(5), ..create_test_config() };
+        assert_eq!(explicit.composition_limit(), 5);
+        assert_eq!(ProcessingConfig { max_compositions: Some(0), ..auto }.composition_limit(), 0);
+    }
 }
\ No newline at end of file
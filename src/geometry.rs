@@ -0,0 +1,268 @@
+//! 几何变换模块
+//!
+//! 为镜像和旋转搜索提供仿射变换：生成变换后的图像，并把在变换后图像上
+//! 检测到的角点映射回原图坐标系，保证输出的位置信息始终基于原始图像。
+
+use opencv::{
+    core::{Mat, Scalar, Size, BORDER_CONSTANT},
+    imgproc::{warp_affine, INTER_LINEAR},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+use crate::types::{QRCodeResult, QRPosition};
+
+/// 默认的旋转搜索步长（度）
+pub const ROTATION_STEP_DEGREES: i32 = 15;
+
+/// 镜像方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mirror {
+    /// 不镜像
+    None,
+    /// 水平镜像（左右翻转）
+    Horizontal,
+    /// 垂直镜像（上下翻转）
+    Vertical,
+}
+
+impl Mirror {
+    /// 获取镜像方式的描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            Mirror::None => "无",
+            Mirror::Horizontal => "水平",
+            Mirror::Vertical => "垂直",
+        }
+    }
+}
+
+/// 旋转搜索的角度序列
+///
+/// 检测器本身能处理 90° 的整数倍，因此只需覆盖 0°-90° 之间按步长排列的角度。
+pub fn rotation_sweep(step: i32) -> Vec<i32> {
+    if step <= 0 {
+        return vec![0];
+    }
+    (0..90).step_by(step as usize).collect()
+}
+
+/// 原图到变换后图像的仿射变换
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeometricTransform {
+    /// 2x3 仿射矩阵：原图坐标 → 变换后坐标
+    forward: [[f64; 3]; 2],
+    /// 变换后图像的宽高
+    output_size: (i32, i32),
+}
+
+impl GeometricTransform {
+    /// 恒等变换
+    pub fn identity(width: i32, height: i32) -> Self {
+        Self {
+            forward: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            output_size: (width, height),
+        }
+    }
+
+    /// 镜像变换
+    pub fn mirror(mirror: Mirror, width: i32, height: i32) -> Self {
+        let forward = match mirror {
+            Mirror::None => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            Mirror::Horizontal => [[-1.0, 0.0, (width - 1) as f64], [0.0, 1.0, 0.0]],
+            Mirror::Vertical => [[1.0, 0.0, 0.0], [0.0, -1.0, (height - 1) as f64]],
+        };
+        Self {
+            forward,
+            output_size: (width, height),
+        }
+    }
+
+    /// 绕图像中心逆时针旋转，画布扩大到能容纳整幅旋转后的图像
+    pub fn rotation(degrees: f64, width: i32, height: i32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let new_width = (width as f64 * cos.abs() + height as f64 * sin.abs()).round() as i32;
+        let new_height = (width as f64 * sin.abs() + height as f64 * cos.abs()).round() as i32;
+
+        let (cx, cy) = ((width - 1) as f64 / 2.0, (height - 1) as f64 / 2.0);
+        let (ncx, ncy) = ((new_width - 1) as f64 / 2.0, (new_height - 1) as f64 / 2.0);
+        Self {
+            forward: [
+                [cos, sin, ncx - cos * cx - sin * cy],
+                [-sin, cos, ncy + sin * cx - cos * cy],
+            ],
+            output_size: (new_width, new_height),
+        }
+    }
+
+    /// 裁剪出以 (x, y) 为左上角、宽高为 width x height 的区域
+    pub fn crop(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            forward: [[1.0, 0.0, -x as f64], [0.0, 1.0, -y as f64]],
+            output_size: (width, height),
+        }
+    }
+
+    /// 先镜像再旋转
+    pub fn mirror_rotation(mirror: Mirror, degrees: f64, width: i32, height: i32) -> Self {
+        let mirrored = Self::mirror(mirror, width, height);
+        if degrees == 0.0 {
+            return mirrored;
+        }
+        mirrored.then(&Self::rotation(degrees, width, height))
+    }
+
+    /// 在当前变换之后再做一次缩放
+    pub fn scaled(&self, scale: f64) -> Self {
+        Self {
+            forward: self.forward.map(|row| row.map(|v| v * scale)),
+            output_size: (
+                (self.output_size.0 as f64 * scale) as i32,
+                (self.output_size.1 as f64 * scale) as i32,
+            ),
+        }
+    }
+
+    /// 组合变换：先 self 后 next
+    pub fn then(&self, next: &GeometricTransform) -> Self {
+        let a = &next.forward;
+        let b = &self.forward;
+        let mut forward = [[0.0; 3]; 2];
+        for row in 0..2 {
+            for col in 0..3 {
+                forward[row][col] = a[row][0] * b[0][col] + a[row][1] * b[1][col];
+            }
+            forward[row][2] += a[row][2];
+        }
+        Self {
+            forward,
+            output_size: next.output_size,
+        }
+    }
+
+    /// 是否为恒等变换
+    pub fn is_identity(&self) -> bool {
+        self.forward == [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+    }
+
+    /// 变换后图像的宽高
+    pub fn output_size(&self) -> (i32, i32) {
+        self.output_size
+    }
+
+    /// 原图坐标 → 变换后坐标
+    pub fn map_forward(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let [r0, r1] = &self.forward;
+        let (x, y) = (x as f64, y as f64);
+        (
+            (r0[0] * x + r0[1] * y + r0[2]) as f32,
+            (r1[0] * x + r1[1] * y + r1[2]) as f32,
+        )
+    }
+
+    /// 变换后坐标 → 原图坐标
+    pub fn map_back(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let [[a, b, tx], [c, d, ty]] = self.forward;
+        let det = a * d - b * c;
+        let (dx, dy) = (x as f64 - tx, y as f64 - ty);
+        (
+            ((d * dx - b * dy) / det) as f32,
+            ((a * dy - c * dx) / det) as f32,
+        )
+    }
+
+    /// 将变换后图像上的位置映射回原图
+    pub fn map_position(&self, position: &QRPosition) -> QRPosition {
+        let corners = position.corners.clone().unwrap_or_else(|| {
+            let (x, y) = (position.x as f32, position.y as f32);
+            let (w, h) = (position.width as f32, position.height as f32);
+            vec![(x, y), (x + w, y), (x + w, y + h), (x, y + h)]
+        });
+        let mapped: Vec<(f32, f32)> = corners.iter().map(|&p| self.map_back(p)).collect();
+
+        let min_x = mapped.iter().map(|p| p.0).fold(f32::MAX, f32::min);
+        let max_x = mapped.iter().map(|p| p.0).fold(f32::MIN, f32::max);
+        let min_y = mapped.iter().map(|p| p.1).fold(f32::MAX, f32::min);
+        let max_y = mapped.iter().map(|p| p.1).fold(f32::MIN, f32::max);
+        QRPosition::new(
+            min_x.round() as i32,
+            min_y.round() as i32,
+            (max_x - min_x).round() as i32,
+            (max_y - min_y).round() as i32,
+        )
+        .with_corners(mapped)
+    }
+
+    /// 将解码结果的位置映射回原图
+    pub fn map_result(&self, mut result: QRCodeResult) -> QRCodeResult {
+        if !self.is_identity() {
+            result.position = self.map_position(&result.position);
+        }
+        result
+    }
+
+    /// 对图像应用变换，空白区域填充白色以保留静区
+    pub fn apply(&self, image: &Mat) -> Result<Mat> {
+        if self.is_identity() {
+            return Ok(image.clone());
+        }
+
+        let matrix = Mat::from_slice_2d(&self.forward)?;
+        let size = Size::new(self.output_size.0, self.output_size.1);
+        let mut result = Mat::default();
+        warp_affine(image, &mut result, &matrix, size, INTER_LINEAR, BORDER_CONSTANT, Scalar::all(255.0))
+            .map_err(|e| QRDecodeError::image_processing_error(format!("几何变换失败: {}", e)))?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!((a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_mirror_mapping() {
+        let horizontal = GeometricTransform::mirror(Mirror::Horizontal, 100, 50);
+        assert_close(horizontal.map_forward((10.0, 20.0)), (89.0, 20.0));
+        assert_close(horizontal.map_back((89.0, 20.0)), (10.0, 20.0));
+
+        let vertical = GeometricTransform::mirror(Mirror::Vertical, 100, 50);
+        assert_close(vertical.map_forward((10.0, 20.0)), (10.0, 29.0));
+    }
+
+    #[test]
+    fn test_rotation_roundtrip_and_canvas() {
+        let rotation = GeometricTransform::rotation(90.0, 100, 50);
+        assert_eq!(rotation.output_size(), (50, 100));
+        // 逆时针 90°：原图右上角转到左上角
+        assert_close(rotation.map_forward((99.0, 0.0)), (0.0, 0.0));
+
+        let combined = GeometricTransform::mirror_rotation(Mirror::Horizontal, 30.0, 640, 480).scaled(0.5);
+        for &point in &[(0.0, 0.0), (639.0, 0.0), (320.0, 240.0), (17.5, 400.25)] {
+            assert_close(combined.map_back(combined.map_forward(point)), point);
+        }
+    }
+
+    #[test]
+    fn test_map_position_restores_original_box() {
+        let rotation = GeometricTransform::rotation(45.0, 200, 200);
+        let corners = [(50.0, 60.0), (150.0, 60.0), (150.0, 160.0), (50.0, 160.0)];
+        let rotated: Vec<(f32, f32)> = corners.iter().map(|&p| rotation.map_forward(p)).collect();
+        let position = QRPosition::new(0, 0, 0, 0).with_corners(rotated);
+
+        let restored = rotation.map_position(&position);
+        assert_eq!((restored.x, restored.y), (50, 60));
+        assert_eq!((restored.width, restored.height), (100, 100));
+        assert_eq!(rotation_sweep(ROTATION_STEP_DEGREES), vec![0, 15, 30, 45, 60, 75]);
+
+        // 裁剪后放大的图块上的坐标映射回原图
+        let tile = GeometricTransform::crop(300, 200, 100, 50).scaled(2.0);
+        assert_eq!(tile.output_size(), (200, 100));
+        assert_close(tile.map_back((20.0, 10.0)), (310.0, 205.0));
+    }
+}
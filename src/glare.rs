@@ -0,0 +1,191 @@
+//! 镜面反光检测与去除模块
+//!
+//! 覆膜或光面包装上的反光会形成一片比纸面还亮的饱和白斑，把其中的深色模块整块抹掉。
+//! 本模块找出这些亮于纸面的饱和区域，可以用周围的模块结构修补（图像修复），
+//! 也可以把掩码交给模块矩阵采样，将被覆盖的模块作为擦除处理。
+
+use opencv::{
+    core::{self, Mat, Point, Size, BORDER_CONSTANT},
+    imgproc::{self, cvt_color, get_structuring_element, COLOR_BGR2GRAY, MORPH_ELLIPSE, THRESH_BINARY},
+    photo,
+    prelude::*,
+};
+
+use crate::error::{QRDecodeError, Result};
+
+/// 默认反光阈值：灰度高于该值才可能是反光
+pub const DEFAULT_GLARE_THRESHOLD: u8 = 235;
+
+/// 反光需要比纸面（浅色模块的中位灰度）至少亮出的灰度
+const GLARE_MARGIN: u8 = 20;
+
+/// 反光区域向外扩张的半径（像素），覆盖反光边缘的光晕
+const GLARE_DILATION: i32 = 3;
+
+/// 修复时参考的邻域半径（像素）
+const INPAINT_RADIUS: f64 = 5.0;
+
+/// 根据灰度直方图确定实际使用的反光阈值
+///
+/// 以 Otsu 阈值分出浅色部分，取其中位数作为纸面亮度；阈值取 `requested` 与
+/// “纸面亮度 + 余量”中较大者。纸面本身已经饱和时无法区分反光，返回 None。
+pub fn glare_threshold(histogram: &[u64; 256], requested: u8) -> Option<u8> {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let split = otsu_split(histogram, total);
+    let light: u64 = histogram[split..].iter().sum();
+    let mut seen = 0u64;
+    let mut paper = 255usize;
+    for (level, &count) in histogram.iter().enumerate().skip(split) {
+        seen += count;
+        if seen * 2 >= light {
+            paper = level;
+            break;
+        }
+    }
+
+    let threshold = (paper + GLARE_MARGIN as usize).max(requested as usize);
+    if threshold >= 255 {
+        None
+    } else {
+        Some(threshold as u8)
+    }
+}
+
+/// 检测反光区域，返回与输入同尺寸的单通道掩码（255 为反光）
+///
+/// 没有可区分的反光时返回全零掩码。
+pub fn detect_glare(image: &Mat, threshold: u8) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+    let mut histogram = [0u64; 256];
+    for &pixel in gray.data_bytes()? {
+        histogram[pixel as usize] += 1;
+    }
+
+    let mut mask = Mat::default();
+    let Some(threshold) = glare_threshold(&histogram, threshold) else {
+        imgproc::threshold(&gray, &mut mask, 255.0, 255.0, THRESH_BINARY)?;
+        return Ok(mask);
+    };
+
+    imgproc::threshold(&gray, &mut mask, threshold as f64, 255.0, THRESH_BINARY)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("反光检测失败: {}", e)))?;
+
+    let size = GLARE_DILATION * 2 + 1;
+    let kernel = get_structuring_element(MORPH_ELLIPSE, Size::new(size, size), Point::new(-1, -1))?;
+    let mut dilated = Mat::default();
+    imgproc::dilate(&mask, &mut dilated, &kernel, Point::new(-1, -1), 1, BORDER_CONSTANT, imgproc::morphology_default_border_value()?)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("反光区域扩张失败: {}", e)))?;
+    Ok(dilated)
+}
+
+/// 去除反光：检测反光区域后用周围的像素修复
+pub fn remove_glare(image: &Mat, threshold: u8) -> Result<Mat> {
+    let mask = detect_glare(image, threshold)?;
+    if core::count_non_zero(&mask)? == 0 {
+        return Ok(image.clone());
+    }
+
+    let mut result = Mat::default();
+    photo::inpaint(image, &mask, &mut result, INPAINT_RADIUS, photo::INPAINT_TELEA)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("反光修复失败: {}", e)))?;
+    Ok(result)
+}
+
+/// 合并用户提供的遮挡掩码和反光掩码
+pub fn merge_masks(mask: &Mat, glare_mask: &Mat) -> Result<Mat> {
+    if mask.rows() != glare_mask.rows() || mask.cols() != glare_mask.cols() {
+        return Err(QRDecodeError::invalid_input(format!(
+            "遮挡掩码尺寸 {}x{} 与图像尺寸 {}x{} 不一致",
+            mask.cols(),
+            mask.rows(),
+            glare_mask.cols(),
+            glare_mask.rows()
+        )));
+    }
+
+    let gray = to_grayscale(mask)?;
+    let mut merged = Mat::default();
+    core::bitwise_or(&gray, glare_mask, &mut merged, &core::no_array())
+        .map_err(|e| QRDecodeError::image_processing_error(format!("合并掩码失败: {}", e)))?;
+    Ok(merged)
+}
+
+/// 直方图的 Otsu 分界，返回浅色部分的起始灰度
+fn otsu_split(histogram: &[u64; 256], total: u64) -> usize {
+    let sum: f64 = histogram.iter().enumerate().map(|(i, &c)| i as f64 * c as f64).sum();
+    let (mut weight, mut moment) = (0f64, 0f64);
+    let (mut best, mut split) = (0f64, 128usize);
+    for (level, &count) in histogram.iter().enumerate().take(255) {
+        weight += count as f64;
+        moment += level as f64 * count as f64;
+        let rest = total as f64 - weight;
+        if weight == 0.0 || rest == 0.0 {
+            continue;
+        }
+        let diff = moment / weight - (sum - moment) / rest;
+        let variance = weight * rest * diff * diff;
+        if variance > best {
+            best = variance;
+            split = level + 1;
+        }
+    }
+    split
+}
+
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    if image.channels() == 1 {
+        return Ok(image.clone());
+    }
+
+    let mut gray = Mat::default();
+    cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+    Ok(gray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC1};
+
+    #[test]
+    fn test_glare_threshold() {
+        // 深色模块 30、纸面 200、反光 255
+        let mut histogram = [0u64; 256];
+        histogram[30] = 400;
+        histogram[200] = 500;
+        histogram[255] = 100;
+        assert_eq!(glare_threshold(&histogram, DEFAULT_GLARE_THRESHOLD), Some(235));
+        assert_eq!(glare_threshold(&histogram, 180), Some(220));
+
+        // 纸面本身已饱和，无法区分反光
+        histogram[200] = 0;
+        histogram[250] = 500;
+        assert_eq!(glare_threshold(&histogram, DEFAULT_GLARE_THRESHOLD), None);
+    }
+
+    #[test]
+    fn test_remove_glare_restores_modules() {
+        // 8 像素的棋盘格，纸面 190，中间一块反光
+        let mut image = Mat::new_rows_cols_with_default(64, 64, CV_8UC1, Scalar::all(0.0)).unwrap();
+        let data = image.data_bytes_mut().unwrap();
+        for y in 0..64 {
+            for x in 0..64 {
+                let glare = (28..36).contains(&x) && (28..36).contains(&y);
+                let dark = (x / 8 + y / 8) % 2 == 0;
+                data[y * 64 + x] = if glare { 255 } else if dark { 40 } else { 190 };
+            }
+        }
+
+        let mask = detect_glare(&image, DEFAULT_GLARE_THRESHOLD).unwrap();
+        assert_eq!(mask.data_bytes().unwrap()[32 * 64 + 32], 255);
+        assert_eq!(mask.data_bytes().unwrap()[4 * 64 + 12], 0);
+
+        let repaired = remove_glare(&image, DEFAULT_GLARE_THRESHOLD).unwrap();
+        assert!(repaired.data_bytes().unwrap()[32 * 64 + 32] < 255);
+    }
+}
@@ -0,0 +1,284 @@
+//! 文本模块网格输入模块
+//!
+//! 解析手工转录、截图还原或从破损打印件抄录的模块网格（ASCII 字符画或 0/1 矩阵），
+//! 得到模块矩阵后直接交给纯 Rust 解码器，不经过图像处理。`?` 表示无法辨认的模块，
+//! 解码时作为 Reed-Solomon 擦除处理。
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::{QRDecodeError, Result};
+use crate::module_matrix::{self, ModuleMatrix};
+
+/// 网格中的单元格
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Dark,
+    Light,
+    Unknown,
+}
+
+/// 支持的网格文件扩展名
+pub fn grid_extensions() -> Vec<&'static str> {
+    vec!["txt", "csv", "tsv", "grid"]
+}
+
+/// 判断路径是否为网格文本文件
+pub fn is_grid_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| grid_extensions().contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// 从文件读取模块网格
+pub fn load_grid(path: &Path) -> Result<ModuleMatrix> {
+    let text = fs::read_to_string(path).map_err(|e| {
+        QRDecodeError::invalid_input(format!("无法读取网格文件 {}: {}", path.display(), e))
+    })?;
+    parse_grid(&text)
+}
+
+/// 解析文本模块网格
+///
+/// 深色模块: `█ # X x 1 * ■ @`；浅色模块: 空格 `. 0 _ □`；未知模块: `?`。
+/// 支持逗号、分号、制表符或空白分隔的 0/1 矩阵，以及每个模块占两个字符的字符画。
+/// 网格开始前以 `#` 或 `//` 开头的行视为注释，网格开始后遇到注释行即结束，
+/// 因此本工具导出的带注释矩阵可以直接重新导入。四周全浅色的静区会被自动裁掉。
+pub fn parse_grid(text: &str) -> Result<ModuleMatrix> {
+    let lines = grid_lines(text);
+    if lines.is_empty() {
+        return Err(QRDecodeError::invalid_input("网格内容为空".to_string()));
+    }
+
+    let mut rows = Vec::with_capacity(lines.len());
+    for &(line_no, line) in &lines {
+        rows.push(parse_row(line, line_no)?);
+    }
+
+    // 全部由双字符单元组成（如 "██"、"  "、"??"）时按两个字符一个模块合并
+    if rows.iter().all(|row| is_double_width(row)) {
+        rows = rows
+            .into_iter()
+            .map(|row| row.chunks(2).map(|pair| pair[0]).collect())
+            .collect();
+    }
+
+    // 字符画行尾的浅色模块常被编辑器去掉，按最长行补齐
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    for row in rows.iter_mut() {
+        row.resize(width, Cell::Light);
+    }
+
+    let rows = trim_quiet_zone(rows);
+    let size = rows.len();
+    if size == 0 {
+        return Err(QRDecodeError::invalid_input("网格中没有深色模块".to_string()));
+    }
+    if rows[0].len() != size {
+        return Err(QRDecodeError::invalid_input(format!(
+            "网格不是正方形: {} 行 x {} 列",
+            size,
+            rows[0].len()
+        )));
+    }
+    if module_matrix::version_from_size(size).is_none() {
+        return Err(QRDecodeError::invalid_input(format!(
+            "无效的网格尺寸: {}x{} (应为 21-177 之间的 17+4n)",
+            size, size
+        )));
+    }
+
+    let mut matrix = ModuleMatrix::new(size);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &cell) in row.iter().enumerate() {
+            match cell {
+                Cell::Dark => matrix.set(x, y, true),
+                Cell::Light => {}
+                Cell::Unknown => matrix.set_unknown(x, y, true),
+            }
+        }
+    }
+    Ok(matrix)
+}
+
+/// 提取网格所在的行（保留原始行号用于报错）
+fn grid_lines(text: &str) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') && !is_grid_row(trimmed) || trimmed.starts_with("//") {
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if line.trim().is_empty() {
+            // 网格中间的空行在字符画中表示整行浅色模块，网格开始前的空行忽略
+            if !lines.is_empty() {
+                lines.push((index + 1, line));
+            }
+            continue;
+        }
+        lines.push((index + 1, line));
+    }
+
+    // 去掉末尾的空行
+    while lines.last().is_some_and(|(_, line)| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// 以 # 开头的行是否为网格行（只包含网格字符）而非注释
+fn is_grid_row(line: &str) -> bool {
+    line.chars().all(|c| classify(c).is_some() || is_separator(c))
+}
+
+/// 解析单行网格
+fn parse_row(line: &str, line_no: usize) -> Result<Vec<Cell>> {
+    let separated = line.contains(',') || line.contains(';') || line.contains('\t');
+    let tokens: Vec<&str> = if separated {
+        line.split([',', ';', '\t']).map(|t| t.trim()).filter(|t| !t.is_empty()).collect()
+    } else if line.split_whitespace().count() > 1
+        && line.split_whitespace().all(|t| matches!(t, "0" | "1" | "?"))
+    {
+        line.split_whitespace().collect()
+    } else {
+        Vec::new()
+    };
+
+    if !tokens.is_empty() {
+        return tokens
+            .iter()
+            .enumerate()
+            .map(|(col, token)| {
+                let mut chars = token.chars();
+                match (chars.next().and_then(classify), chars.next()) {
+                    (Some(cell), None) => Ok(cell),
+                    _ => Err(invalid_cell(line_no, col + 1, token)),
+                }
+            })
+            .collect();
+    }
+
+    line.chars()
+        .enumerate()
+        .map(|(col, c)| classify(c).ok_or_else(|| invalid_cell(line_no, col + 1, &c.to_string())))
+        .collect()
+}
+
+/// 字符到单元格的映射
+fn classify(c: char) -> Option<Cell> {
+    match c {
+        '█' | '#' | 'X' | 'x' | '1' | '*' | '■' | '@' => Some(Cell::Dark),
+        ' ' | '.' | '0' | '_' | '□' => Some(Cell::Light),
+        '?' => Some(Cell::Unknown),
+        _ => None,
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, ',' | ';' | '\t')
+}
+
+/// 行是否由成对的相同单元组成
+fn is_double_width(row: &[Cell]) -> bool {
+    row.len() % 2 == 0 && row.chunks(2).all(|pair| pair[0] == pair[1])
+}
+
+/// 裁掉四周全浅色的静区
+fn trim_quiet_zone(rows: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
+    let occupied = |cell: &Cell| *cell != Cell::Light;
+    let top = rows.iter().position(|row| row.iter().any(occupied));
+    let top = match top {
+        Some(top) => top,
+        None => return Vec::new(),
+    };
+    let bottom = rows.iter().rposition(|row| row.iter().any(occupied)).unwrap_or(top);
+    let width = rows[0].len();
+    let left = (0..width)
+        .find(|&x| rows.iter().any(|row| occupied(&row[x])))
+        .unwrap_or(0);
+    let right = (0..width)
+        .rev()
+        .find(|&x| rows.iter().any(|row| occupied(&row[x])))
+        .unwrap_or(width - 1);
+
+    rows[top..=bottom]
+        .iter()
+        .map(|row| row[left..=right].to_vec())
+        .collect()
+}
+
+fn invalid_cell(line: usize, col: usize, token: &str) -> QRDecodeError {
+    QRDecodeError::invalid_input(format!(
+        "网格第 {} 行第 {} 列包含无效字符: \"{}\"",
+        line, col, token
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 版本 1 的空白网格（仅定位图形），用于测试解析
+    fn finder_grid(dark: char, light: char) -> String {
+        let mut rows = vec![vec![light; 21]; 21];
+        for &(ox, oy) in &[(0usize, 0usize), (14, 0), (0, 14)] {
+            for y in 0..7 {
+                for x in 0..7 {
+                    let ring = x.min(y).min(6 - x).min(6 - y);
+                    if ring != 1 {
+                        rows[oy + y][ox + x] = dark;
+                    }
+                }
+            }
+        }
+        rows.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_parse_binary_grid() {
+        let matrix = parse_grid(&finder_grid('1', '0')).unwrap();
+        assert_eq!(matrix.size, 21);
+        assert!(matrix.get(0, 0));
+        assert!(!matrix.get(1, 1));
+        assert!(matrix.get(20, 0));
+    }
+
+    #[test]
+    fn test_parse_double_width_ascii_with_quiet_zone() {
+        let grid = finder_grid('█', ' ')
+            .lines()
+            .map(|line| format!("    {}", line.chars().map(|c| format!("{}{}", c, c)).collect::<String>()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text = format!("# 注释\n\n{}\n\n# 作用映射\nFFFFFFF", grid);
+        let matrix = parse_grid(&text).unwrap();
+        assert_eq!(matrix.size, 21);
+        assert!(matrix.get(14, 6));
+    }
+
+    #[test]
+    fn test_parse_csv_with_unknowns() {
+        let grid = finder_grid('1', '0').replacen('0', "?", 1);
+        let csv = grid
+            .lines()
+            .map(|line| line.chars().map(|c| c.to_string()).collect::<Vec<_>>().join(","))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let matrix = parse_grid(&csv).unwrap();
+        assert_eq!(matrix.unknown_count(), 1);
+        assert!(matrix.is_unknown(7, 0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_grid("").is_err());
+        assert!(parse_grid("10\n01").is_err());
+        let err = parse_grid(&finder_grid('1', '0').replacen('0', "z", 1)).unwrap_err();
+        assert!(err.to_string().contains("第 1 行第 8 列"));
+    }
+}
@@ -0,0 +1,199 @@
+//! 半色调与点状模块处理模块
+//!
+//! 设计师二维码常用圆点、心形或小方块代替整块模块，报纸印刷则带有半色调网点，
+//! 两者都会让二值化得到一片离散的小点。本模块先从定位图形的 1:1:3:1:1 比例估计
+//! 模块间距，再按模块尺度把小点合并回整块模块：形态学闭合（合并深色点），或按
+//! 网格逐个模块取中心区域重新绘制。
+
+use opencv::{
+    core::{self, Mat, Point, Size, BORDER_REPLICATE},
+    imgproc::{self, cvt_color, get_structuring_element, COLOR_BGR2GRAY, MORPH_ELLIPSE, MORPH_OPEN, THRESH_BINARY, THRESH_OTSU},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+use crate::module_matrix;
+
+/// 定位图形各段相对模块宽度允许的偏差
+const FINDER_TOLERANCE: f64 = 0.5;
+
+/// 估计模块间距至少需要的定位图形扫描命中数
+const MIN_FINDER_HITS: usize = 3;
+
+/// 二值化前去除半色调网点的高斯模糊 sigma（像素）
+const SCREEN_BLUR_SIGMA: f64 = 1.0;
+
+/// 点状模块的修复方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HalftoneRepair {
+    /// 以约一个模块大小的核闭合深色区域，把同一模块和相邻模块的点连成整块
+    MorphClose,
+    /// 定位符号后按模块网格取每个模块中心区域的灰度，重新绘制为纯色模块
+    CentreSampling,
+}
+
+impl HalftoneRepair {
+    /// 获取修复方式的描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            HalftoneRepair::MorphClose => "模块尺度闭合",
+            HalftoneRepair::CentreSampling => "模块中心采样",
+        }
+    }
+}
+
+/// 修复点状或半色调模块；估计不出模块间距时原样返回
+pub fn repair_halftone(image: &Mat, repair: HalftoneRepair) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+    let Some(pitch) = module_pitch(&gray)? else {
+        return Ok(image.clone());
+    };
+
+    match repair {
+        HalftoneRepair::MorphClose => {
+            // 深色区域的闭运算等价于灰度图上的开运算（先取邻域最小值再取最大值）
+            let size = (pitch.round() as i32 / 2) * 2 + 1;
+            let kernel = get_structuring_element(MORPH_ELLIPSE, Size::new(size, size), Point::new(-1, -1))
+                .map_err(|e| QRDecodeError::image_processing_error(format!("创建形态学核失败: {}", e)))?;
+            let mut result = Mat::default();
+            imgproc::morphology_ex(image, &mut result, MORPH_OPEN, &kernel, Point::new(-1, -1), 1, BORDER_REPLICATE, imgproc::morphology_default_border_value()?)
+                .map_err(|e| QRDecodeError::image_processing_error(format!("模块尺度闭合失败: {}", e)))?;
+            Ok(result)
+        }
+        HalftoneRepair::CentreSampling => {
+            let Some(corners) = module_matrix::locate_symbol(image)? else {
+                return Ok(image.clone());
+            };
+            let size = module_count_from_pitch(&corners, pitch);
+            let matrix = module_matrix::sample_from_corners(&gray, &corners, size)?;
+            let cells = (0..matrix.size).flat_map(|y| (0..matrix.size).map(move |x| (x, y)));
+            let cells: Vec<(usize, usize, bool)> = cells.map(|(x, y)| (x, y, matrix.get(x, y))).collect();
+            module_matrix::paint_modules(image, &corners, matrix.size, cells)
+        }
+    }
+}
+
+/// 从图像中估计模块间距（像素）
+///
+/// 先用轻微的高斯模糊抹平半色调网点，Otsu 二值化后沿行和列扫描定位图形。
+pub fn module_pitch(gray: &Mat) -> Result<Option<f64>> {
+    let mut smoothed = Mat::default();
+    imgproc::gaussian_blur(gray, &mut smoothed, Size::new(0, 0), SCREEN_BLUR_SIGMA, 0.0, BORDER_REPLICATE, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("高斯模糊失败: {}", e)))?;
+    let mut binary = Mat::default();
+    imgproc::threshold(&smoothed, &mut binary, 0.0, 255.0, THRESH_BINARY | THRESH_OTSU)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("二值化失败: {}", e)))?;
+
+    let dark: Vec<bool> = binary.data_bytes()?.iter().map(|&p| p == 0).collect();
+    Ok(estimate_module_pitch(&dark, binary.rows() as usize, binary.cols() as usize))
+}
+
+/// 沿所有行和列扫描定位图形，取命中的模块宽度的中位数
+pub fn estimate_module_pitch(dark: &[bool], rows: usize, cols: usize) -> Option<f64> {
+    let mut pitches = finder_pitches(dark, rows, cols);
+    if pitches.len() < MIN_FINDER_HITS {
+        return None;
+    }
+    pitches.sort_by(f64::total_cmp);
+    Some(pitches[pitches.len() / 2])
+}
+
+/// 沿所有行和列扫描定位图形，返回每处命中的模块宽度
+pub fn finder_pitches(dark: &[bool], rows: usize, cols: usize) -> Vec<f64> {
+    let mut pitches = Vec::new();
+    for y in 0..rows {
+        pitches.extend(scan_finder_pitches(&dark[y * cols..(y + 1) * cols]));
+    }
+    for x in 0..cols {
+        let column: Vec<bool> = (0..rows).map(|y| dark[y * cols + x]).collect();
+        pitches.extend(scan_finder_pitches(&column));
+    }
+    pitches
+}
+
+/// 在一条扫描线上查找 深:浅:深:浅:深 = 1:1:3:1:1 的游程，返回每处命中的模块宽度
+pub fn scan_finder_pitches(line: &[bool]) -> Vec<f64> {
+    let mut runs: Vec<(bool, usize)> = Vec::new();
+    for &dark in line {
+        match runs.last_mut() {
+            Some((value, length)) if *value == dark => *length += 1,
+            _ => runs.push((dark, 1)),
+        }
+    }
+
+    runs.windows(5)
+        .filter(|window| window[0].0)
+        .filter_map(|window| {
+            let total: usize = window.iter().map(|&(_, length)| length).sum();
+            let unit = total as f64 / 7.0;
+            let matches = window
+                .iter()
+                .zip([1.0, 1.0, 3.0, 1.0, 1.0])
+                .all(|(&(_, length), expected)| (length as f64 - expected * unit).abs() <= unit * FINDER_TOLERANCE);
+            matches.then_some(unit)
+        })
+        .collect()
+}
+
+/// 根据角点围成的边长和模块间距推算模块数，取最接近的合法尺寸 (17+4n)
+fn module_count_from_pitch(corners: &[(f32, f32)], pitch: f64) -> Option<usize> {
+    let side = corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|(a, b)| ((a.0 - b.0) as f64).hypot((a.1 - b.1) as f64))
+        .sum::<f64>()
+        / 4.0;
+    let version = ((side / pitch - 17.0) / 4.0).round();
+    module_matrix::size_from_version(version.clamp(1.0, 40.0) as u8)
+}
+
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    if image.channels() == 1 {
+        return Ok(image.clone());
+    }
+
+    let mut gray = Mat::default();
+    cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+    Ok(gray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_finder_pitches() {
+        // 模块宽 4 像素的定位图形，两侧是浅色静区
+        let mut line = vec![false; 8];
+        for (dark, modules) in [(true, 1), (false, 1), (true, 3), (false, 1), (true, 1)] {
+            line.extend(std::iter::repeat_n(dark, modules * 4));
+        }
+        line.extend([false; 8]);
+        assert_eq!(scan_finder_pitches(&line), vec![4.0]);
+
+        // 比例不符的游程不算
+        let noise: Vec<bool> = [true, false, true, true, false, true].iter().flat_map(|&d| [d; 4]).collect();
+        assert!(scan_finder_pitches(&noise).is_empty());
+    }
+
+    #[test]
+    fn test_estimate_pitch_ignores_dots() {
+        // 60x60 图像：左上角 5 像素模块的定位图形，其余为 2 像素的离散圆点
+        let (rows, cols, pitch) = (60, 60, 5);
+        let mut dark = vec![false; rows * cols];
+        for y in 0..rows {
+            for x in 0..cols {
+                let (mx, my) = (x / pitch, y / pitch);
+                dark[y * cols + x] = if mx < 7 && my < 7 {
+                    let ring = mx.min(my).min(6 - mx).min(6 - my);
+                    ring != 1
+                } else {
+                    (mx + my) % 2 == 0 && x % pitch >= 1 && x % pitch < 3 && y % pitch >= 1 && y % pitch < 3
+                };
+            }
+        }
+        assert_eq!(estimate_module_pitch(&dark, rows, cols), Some(5.0));
+    }
+}
@@ -0,0 +1,184 @@
+//! 光照不均与阴影校正模块
+//!
+//! 台灯下或半边处在阴影中的标签，整幅图像上浅色模块的亮度差异可能比深浅模块之间
+//! 的差异还大，全局阈值和自适应阈值都会失效。本模块先估计背景光照（大尺度形态学
+//! 闭运算、黑帽运算或大核模糊），再把它从图像中除去，得到光照均匀的图像。
+
+use opencv::{
+    core::{self, Mat, Point, Size, CV_32F, CV_8U, NORM_MINMAX},
+    imgproc::{
+        self, cvt_color, get_structuring_element, morphology_ex, COLOR_BGR2GRAY, COLOR_GRAY2BGR,
+        MORPH_BLACKHAT, MORPH_CLOSE, MORPH_ELLIPSE,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+
+/// 自动选择核大小时相对图像短边的比例
+const AUTO_KERNEL_RATIO: f64 = 1.0 / 16.0;
+
+/// 自动选择的最小核大小（像素）
+const MIN_KERNEL_SIZE: i32 = 15;
+
+/// 背景光照估计方法，`size` 为核大小（像素），0 表示按图像尺寸自动选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackgroundEstimate {
+    /// 大尺度闭运算：填平深色模块，剩下浅色背景
+    MorphClose(i32),
+    /// 黑帽运算：直接提取比背景暗的细节
+    BlackHat(i32),
+    /// 大核中值模糊
+    Blur(i32),
+}
+
+impl BackgroundEstimate {
+    /// 获取估计方法的描述
+    pub fn description(&self) -> String {
+        let (name, size) = match self {
+            BackgroundEstimate::MorphClose(size) => ("闭运算", size),
+            BackgroundEstimate::BlackHat(size) => ("黑帽", size),
+            BackgroundEstimate::Blur(size) => ("模糊", size),
+        };
+        if *size == 0 {
+            format!("{} (自动核)", name)
+        } else {
+            format!("{} ({}x{})", name, size, size)
+        }
+    }
+
+    fn size(&self) -> i32 {
+        match self {
+            BackgroundEstimate::MorphClose(size)
+            | BackgroundEstimate::BlackHat(size)
+            | BackgroundEstimate::Blur(size) => *size,
+        }
+    }
+}
+
+/// 光照归一化
+///
+/// 闭运算和模糊方法将灰度除以估计的背景后拉伸回 0-255；黑帽方法直接把比背景暗的
+/// 部分反转为深色模块。输入为彩色图像时返回转换回 BGR 的结果。
+pub fn normalize_illumination(image: &Mat, estimate: BackgroundEstimate) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+    let size = kernel_size(estimate.size(), gray.rows(), gray.cols());
+
+    let normalized = match estimate {
+        BackgroundEstimate::MorphClose(_) => {
+            let background = morphology(&gray, MORPH_CLOSE, size)?;
+            divide_background(&gray, &background)?
+        }
+        BackgroundEstimate::Blur(_) => {
+            let mut background = Mat::default();
+            imgproc::median_blur(&gray, &mut background, size)
+                .map_err(|e| QRDecodeError::image_processing_error(format!("背景模糊失败: {}", e)))?;
+            divide_background(&gray, &background)?
+        }
+        BackgroundEstimate::BlackHat(_) => {
+            let black_hat = morphology(&gray, MORPH_BLACKHAT, size)?;
+            let mut inverted = Mat::default();
+            core::bitwise_not(&black_hat, &mut inverted, &core::no_array())
+                .map_err(|e| QRDecodeError::image_processing_error(format!("反色处理失败: {}", e)))?;
+            let mut stretched = Mat::default();
+            core::normalize(&inverted, &mut stretched, 0.0, 255.0, NORM_MINMAX, CV_8U, &core::no_array())
+                .map_err(|e| QRDecodeError::image_processing_error(format!("归一化失败: {}", e)))?;
+            stretched
+        }
+    };
+
+    if image.channels() == 3 {
+        let mut color = Mat::default();
+        cvt_color(&normalized, &mut color, COLOR_GRAY2BGR, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转彩色失败: {}", e)))?;
+        Ok(color)
+    } else {
+        Ok(normalized)
+    }
+}
+
+/// 计算核大小：未指定时取图像短边的 1/16，结果保证为奇数
+pub fn kernel_size(requested: i32, rows: i32, cols: i32) -> i32 {
+    let size = if requested > 0 {
+        requested
+    } else {
+        ((rows.min(cols) as f64 * AUTO_KERNEL_RATIO) as i32).max(MIN_KERNEL_SIZE)
+    };
+    if size % 2 == 0 { size + 1 } else { size }
+}
+
+fn morphology(gray: &Mat, op: i32, size: i32) -> Result<Mat> {
+    let kernel = get_structuring_element(MORPH_ELLIPSE, Size::new(size, size), Point::new(-1, -1))
+        .map_err(|e| QRDecodeError::image_processing_error(format!("创建形态学核失败: {}", e)))?;
+    let mut result = Mat::default();
+    morphology_ex(gray, &mut result, op, &kernel, Point::new(-1, -1), 1, core::BORDER_REPLICATE, imgproc::morphology_default_border_value()?)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("背景估计失败: {}", e)))?;
+    Ok(result)
+}
+
+/// 灰度除以背景，再拉伸到 0-255
+fn divide_background(gray: &Mat, background: &Mat) -> Result<Mat> {
+    let mut gray_f = Mat::default();
+    gray.convert_to(&mut gray_f, CV_32F, 1.0, 0.0)?;
+    let mut background_f = Mat::default();
+    background.convert_to(&mut background_f, CV_32F, 1.0, 1.0)?;
+
+    let mut ratio = Mat::default();
+    core::divide2(&gray_f, &background_f, &mut ratio, 1.0, -1)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("背景除法失败: {}", e)))?;
+
+    let mut result = Mat::default();
+    core::normalize(&ratio, &mut result, 0.0, 255.0, NORM_MINMAX, CV_8U, &core::no_array())
+        .map_err(|e| QRDecodeError::image_processing_error(format!("归一化失败: {}", e)))?;
+    Ok(result)
+}
+
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    if image.channels() == 1 {
+        return Ok(image.clone());
+    }
+
+    let mut gray = Mat::default();
+    cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+    Ok(gray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC1};
+
+    #[test]
+    fn test_kernel_size() {
+        assert_eq!(kernel_size(20, 480, 640), 21);
+        assert_eq!(kernel_size(0, 480, 640), 31);
+        assert_eq!(kernel_size(0, 100, 100), MIN_KERNEL_SIZE);
+    }
+
+    #[test]
+    fn test_normalize_removes_gradient() {
+        // 亮度从左到右由 60 升到 250，叠加 6 像素的深色方块
+        let mut image = Mat::new_rows_cols_with_default(96, 192, CV_8UC1, Scalar::all(0.0)).unwrap();
+        let data = image.data_bytes_mut().unwrap();
+        for y in 0..96 {
+            for x in 0..192 {
+                let light = 60.0 + x as f32;
+                let dark = (x / 6 + y / 6) % 2 == 0;
+                data[y * 192 + x] = if dark { (light * 0.4) as u8 } else { light as u8 };
+            }
+        }
+
+        for estimate in [BackgroundEstimate::MorphClose(15), BackgroundEstimate::BlackHat(15), BackgroundEstimate::Blur(31)] {
+            let result = normalize_illumination(&image, estimate).unwrap();
+            let out = result.data_bytes().unwrap();
+            // 左右两端的浅色方块校正后亮度接近
+            let (left, right) = (48 * 192 + 9, 48 * 192 + 177);
+            assert!((out[left] as i32 - out[right] as i32).abs() < 60, "{:?}: {} vs {}", estimate, out[left], out[right]);
+            // 一个全局阈值即可分开深浅模块
+            let dark = out[48 * 192 + 3] as i32;
+            assert!(dark < out[left] as i32 && dark < out[right] as i32);
+        }
+    }
+}
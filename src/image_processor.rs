@@ -14,6 +14,7 @@ use opencv::{
 use std::path::Path;
 
 use crate::error::{QRDecodeError, Result};
+use crate::local_contrast;
 use crate::types::{ImageProcessingParams, ProcessingConfig};
 
 /// 图像处理器
@@ -29,7 +30,13 @@ impl ImageProcessor {
     pub fn new(config: &ProcessingConfig) -> Self {
         Self {
             config: config.clone(),
-            params: ImageProcessingParams::default(),
+            params: ImageProcessingParams {
+                clahe: config.clahe,
+                clahe_clip_limit: config.clahe_clip_limit,
+                clahe_tile_size: config.clahe_tile_size,
+                tone_mapping: config.tone_mapping,
+                ..ImageProcessingParams::default()
+            },
         }
     }
     
@@ -120,15 +127,29 @@ impl ImageProcessor {
             }
         }
         
-        // 4. 直方图均衡化
-        if self.params.histogram_equalization {
+        // 4. 局部色调映射
+        if let Some(strength) = self.params.tone_mapping {
+            processed = self.apply_local_tone_mapping(&processed, strength)?;
+            if self.config.verbose {
+                println!("   ✓ 局部色调映射 (强度: {:.2})", strength);
+            }
+        }
+        
+        // 5. 直方图均衡化（启用 CLAHE 时改为分块均衡化）
+        if self.params.clahe {
+            processed = self.apply_clahe(&processed, self.params.clahe_clip_limit, self.params.clahe_tile_size)?;
+            if self.config.verbose {
+                println!("   ✓ CLAHE (限幅: {:.1}, 网格: {}x{})",
+                    self.params.clahe_clip_limit, self.params.clahe_tile_size, self.params.clahe_tile_size);
+            }
+        } else if self.params.histogram_equalization {
             processed = self.apply_histogram_equalization(&processed)?;
             if self.config.verbose {
                 println!("   ✓ 直方图均衡化");
             }
         }
         
-        // 5. 自适应阈值
+        // 6. 自适应阈值
         if self.params.adaptive_threshold {
             processed = self.apply_adaptive_threshold(&processed)?;
             if self.config.verbose {
@@ -213,6 +234,16 @@ impl ImageProcessor {
         Ok(equalized)
     }
     
+    /// 应用 CLAHE（限制对比度的自适应直方图均衡化）
+    pub fn apply_clahe(&self, image: &Mat, clip_limit: f64, tile_size: i32) -> Result<Mat> {
+        local_contrast::apply_clahe(image, clip_limit, tile_size)
+    }
+    
+    /// 应用局部色调映射
+    pub fn apply_local_tone_mapping(&self, image: &Mat, strength: f64) -> Result<Mat> {
+        local_contrast::apply_local_tone_mapping(image, strength)
+    }
+    
     /// 应用自适应阈值
     pub fn apply_adaptive_threshold(&self, image: &Mat) -> Result<Mat> {
         // 确保图像是灰度图
@@ -283,10 +314,10 @@ impl ImageProcessor {
             ));
         }
         
-        // 检查图像是否过大
-        if info.total_pixels > 50_000_000 {
+        // 检查图像是否过大（金字塔和分块扫描模式按图块处理，不受限制）
+        if info.total_pixels > 50_000_000 && !self.config.pyramid && !self.config.tiled {
             return Err(QRDecodeError::image_processing_error(
-                "图像尺寸过大，建议缩小后再处理".to_string(),
+                "图像尺寸过大，建议缩小后再处理，或使用 --pyramid / --tiled 分块检测".to_string(),
             ));
         }
         
@@ -343,6 +374,7 @@ mod tests {
             min_confidence: 0.5,
             save_processed: false,
             processed_output_path: None,
+            ..ProcessingConfig::default()
         }
     }
     
@@ -0,0 +1,431 @@
+//! 隐藏数据与填充检查模块
+//!
+//! 面向 CTF 和隐写分析：逐个列出数据码字，检查终止符之后的填充位和填充字节是否符合标准
+//! (0xEC/0x11 交替)，提取终止符之后的字节，检查剩余位，并与按解码内容重新编码的干净符号
+//! 逐模块比较，找出被刻意修改的模块。
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::module_matrix::ModuleMatrix;
+use crate::qr_codec::{self, BlockLayout, Segment, PAD_CODEWORDS};
+use crate::qr_layout::{self, EcLevel, ModuleRole};
+
+/// 数据码字的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CodewordUsage {
+    /// 数据段（模式指示符、字符计数和数据）
+    Segment,
+    /// 包含终止符或字节对齐填充位
+    Terminator,
+    /// 标准填充字节
+    Padding,
+    /// 非标准填充字节
+    NonStandardPadding,
+}
+
+/// 数据码字信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodewordInfo {
+    /// 在数据码字中的序号
+    pub index: usize,
+    /// 纠错后的值
+    pub value: u8,
+    /// 读取到的原始值（与纠错后不同时表示被纠正）
+    pub raw: u8,
+    /// 用途
+    pub usage: CodewordUsage,
+}
+
+/// 被纠错修正的码字（数据码字或纠错码字）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrectedCodeword {
+    /// 纠错块序号
+    pub block: usize,
+    /// 块内下标
+    pub index: usize,
+    /// 是否为纠错码字
+    pub is_ecc: bool,
+    /// 读取到的值
+    pub raw: u8,
+    /// 纠正后的值
+    pub corrected: u8,
+}
+
+/// 与干净重编码不一致的模块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleDiff {
+    /// 列坐标
+    pub x: usize,
+    /// 行坐标
+    pub y: usize,
+    /// 模块作用
+    pub role: ModuleRole,
+    /// 干净重编码中的值（true 为深色）
+    pub expected: bool,
+    /// 实际值
+    pub actual: bool,
+    /// 所在码字的交织序号（剩余位或功能图形为 None）
+    pub codeword: Option<usize>,
+}
+
+/// 隐藏数据检查报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectionReport {
+    /// 版本号
+    pub version: u8,
+    /// 纠错等级
+    pub ec_level: EcLevel,
+    /// 掩码编号
+    pub mask: u8,
+    /// 解码内容
+    pub content: String,
+    /// 数据段
+    pub segments: Vec<Segment>,
+    /// 所有数据码字
+    pub data_codewords: Vec<CodewordInfo>,
+    /// 数据段结束（终止符开始）的位位置
+    pub data_end_bit: usize,
+    /// 终止符和字节对齐填充位中是否有非零位
+    pub nonzero_terminator_bits: bool,
+    /// 终止符之后的所有字节
+    pub trailing_bytes: Vec<u8>,
+    /// 非标准填充字节的序号
+    pub nonstandard_padding: Vec<usize>,
+    /// 终止符之后解析出的隐藏数据段
+    pub hidden_segments: Vec<Segment>,
+    /// 剩余位（去除掩码后）
+    pub remainder_bits: Vec<bool>,
+    /// 被纠错修正的码字
+    pub corrected_codewords: Vec<CorrectedCodeword>,
+    /// 与干净重编码不一致的模块
+    pub module_diffs: Vec<ModuleDiff>,
+}
+
+impl InspectionReport {
+    /// 是否发现任何异常
+    pub fn has_findings(&self) -> bool {
+        self.nonzero_terminator_bits
+            || !self.nonstandard_padding.is_empty()
+            || !self.hidden_segments.is_empty()
+            || self.remainder_bits.iter().any(|&b| b)
+            || !self.corrected_codewords.is_empty()
+            || !self.module_diffs.is_empty()
+    }
+}
+
+/// 检查模块矩阵中的隐藏数据和非标准填充
+pub fn inspect_matrix(matrix: &ModuleMatrix) -> Result<InspectionReport> {
+    let symbol = qr_codec::decode_matrix(matrix)?;
+    let (version, ec_level, mask) = (symbol.version, symbol.ec_level, symbol.mask);
+    let layout = BlockLayout::new(version, ec_level);
+    let data = &symbol.data_codewords;
+
+    // 码字纠错前后对比
+    let (raw_stream, _) = qr_codec::read_codewords(matrix, mask, layout.total_codewords);
+    let clean_stream = qr_codec::interleave_with_ecc(data, version, ec_level);
+    let order = layout.interleave_order();
+    let mut raw_data = data.clone();
+    let mut corrected_codewords = Vec::new();
+    for (i, &(block, index)) in order.iter().enumerate() {
+        let is_ecc = index >= layout.block_data_len(block);
+        if !is_ecc {
+            let offset: usize = (0..block).map(|b| layout.block_data_len(b)).sum();
+            raw_data[offset + index] = raw_stream[i];
+        }
+        if raw_stream[i] != clean_stream[i] {
+            corrected_codewords.push(CorrectedCodeword {
+                block,
+                index,
+                is_ecc,
+                raw: raw_stream[i],
+                corrected: clean_stream[i],
+            });
+        }
+    }
+
+    // 终止符、填充位和填充字节
+    let (_, data_end_bit) = qr_codec::parse_segments(data, version)?;
+    let total_bits = data.len() * 8;
+    let terminator_end = (data_end_bit + 4).min(total_bits);
+    let aligned_end = terminator_end.div_ceil(8) * 8;
+    let nonzero_terminator_bits = (data_end_bit..aligned_end).any(|bit| bit_at(data, bit));
+    let trailing_start = aligned_end / 8;
+    let trailing_bytes = data[trailing_start..].to_vec();
+
+    let mut nonstandard_padding = Vec::new();
+    let mut data_codewords = Vec::with_capacity(data.len());
+    for (index, &value) in data.iter().enumerate() {
+        let usage = if (index + 1) * 8 <= data_end_bit {
+            CodewordUsage::Segment
+        } else if index < trailing_start {
+            CodewordUsage::Terminator
+        } else if value == PAD_CODEWORDS[(index - trailing_start) % 2] {
+            CodewordUsage::Padding
+        } else {
+            nonstandard_padding.push(index);
+            CodewordUsage::NonStandardPadding
+        };
+        data_codewords.push(CodewordInfo {
+            index,
+            value,
+            raw: raw_data[index],
+            usage,
+        });
+    }
+
+    let hidden_segments = if nonzero_terminator_bits || !nonstandard_padding.is_empty() {
+        find_hidden_segments(data, version, data_end_bit, aligned_end)
+    } else {
+        Vec::new()
+    };
+
+    // 剩余位
+    let data_modules = qr_codec::data_module_order(matrix.size);
+    let remainder_bits = data_modules[layout.total_codewords * 8..]
+        .iter()
+        .map(|&(x, y)| matrix.get(x, y) ^ qr_codec::mask_bit(mask, x, y))
+        .collect();
+
+    // 与干净重编码逐模块比较
+    let clean_data = qr_codec::build_data_codewords(&symbol.segments, version, ec_level)?;
+    let clean = qr_codec::encode_matrix(&clean_data, version, ec_level, mask);
+    let module_diffs = diff_modules(matrix, &clean, &data_modules);
+
+    Ok(InspectionReport {
+        version,
+        ec_level,
+        mask,
+        content: symbol.content,
+        segments: symbol.segments,
+        data_codewords,
+        data_end_bit,
+        nonzero_terminator_bits,
+        trailing_bytes,
+        nonstandard_padding,
+        hidden_segments,
+        remainder_bits,
+        corrected_codewords,
+        module_diffs,
+    })
+}
+
+/// 尝试在终止符之后解析出隐藏的数据段（紧接终止符或从下一个字节边界开始）
+fn find_hidden_segments(data: &[u8], version: u8, data_end_bit: usize, aligned_end: usize) -> Vec<Segment> {
+    [data_end_bit + 4, aligned_end]
+        .iter()
+        .filter(|&&start| start < data.len() * 8)
+        .filter_map(|&start| qr_codec::parse_segments_at(data, version, start).ok())
+        .map(|(segments, _)| segments)
+        .find(|segments| segments.iter().any(|s| !s.data.is_empty()))
+        .unwrap_or_default()
+}
+
+/// 逐模块比较，返回不一致的模块（未知模块跳过）
+fn diff_modules(actual: &ModuleMatrix, clean: &ModuleMatrix, data_modules: &[(usize, usize)]) -> Vec<ModuleDiff> {
+    let size = actual.size;
+    let roles = qr_layout::module_roles(size);
+    let mut codeword_at = vec![None; size * size];
+    for (i, &(x, y)) in data_modules.iter().enumerate() {
+        codeword_at[y * size + x] = Some(i / 8);
+    }
+    let total_codewords = data_modules.len() / 8;
+
+    let mut diffs = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            if actual.is_unknown(x, y) || actual.get(x, y) == clean.get(x, y) {
+                continue;
+            }
+            diffs.push(ModuleDiff {
+                x,
+                y,
+                role: roles[y * size + x],
+                expected: clean.get(x, y),
+                actual: actual.get(x, y),
+                codeword: codeword_at[y * size + x].filter(|&c| c < total_codewords),
+            });
+        }
+    }
+    diffs
+}
+
+fn bit_at(data: &[u8], bit: usize) -> bool {
+    (data[bit / 8] >> (7 - bit % 8)) & 1 == 1
+}
+
+/// 生成可读的检查报告
+pub fn format_report(report: &InspectionReport) -> String {
+    let mut output = String::new();
+    output.push_str(&format!(
+        "# 隐藏数据检查: 版本 {}-{}, 掩码 {}\n",
+        report.version, report.ec_level, report.mask
+    ));
+    output.push_str(&format!("# 内容: {}\n", report.content));
+    output.push_str(&format!(
+        "# 数据段结束于第 {} 位，共 {} 个数据码字\n",
+        report.data_end_bit,
+        report.data_codewords.len()
+    ));
+
+    output.push_str("\n## 数据码字\n");
+    for info in &report.data_codewords {
+        let usage = match info.usage {
+            CodewordUsage::Segment => "数据段",
+            CodewordUsage::Terminator => "终止符/对齐",
+            CodewordUsage::Padding => "填充",
+            CodewordUsage::NonStandardPadding => "非标准填充 ⚠",
+        };
+        let corrected = if info.raw != info.value {
+            format!(" (读取值 {:02X}, 已纠正)", info.raw)
+        } else {
+            String::new()
+        };
+        output.push_str(&format!(
+            "{:4}  {:02X}  {:08b}  {}  {}{}\n",
+            info.index,
+            info.value,
+            info.value,
+            printable(info.value),
+            usage,
+            corrected
+        ));
+    }
+
+    output.push_str("\n## 检查结果\n");
+    if report.nonzero_terminator_bits {
+        output.push_str("⚠ 终止符或字节对齐填充位中存在非零位\n");
+    }
+    if !report.nonstandard_padding.is_empty() {
+        output.push_str(&format!(
+            "⚠ {} 个填充字节不是 0xEC/0x11: {:?}\n",
+            report.nonstandard_padding.len(),
+            report.nonstandard_padding
+        ));
+    }
+    if !report.trailing_bytes.is_empty() {
+        output.push_str(&format!(
+            "终止符之后的字节 ({}): {}\n",
+            report.trailing_bytes.len(),
+            hex_string(&report.trailing_bytes)
+        ));
+        output.push_str(&format!(
+            "  作为文本: {}\n",
+            report.trailing_bytes.iter().map(|&b| printable(b)).collect::<String>()
+        ));
+    }
+    if !report.hidden_segments.is_empty() {
+        let text: String = report.hidden_segments.iter().map(|s| s.text.as_str()).collect();
+        output.push_str(&format!("⚠ 终止符之后解析出隐藏数据段: {}\n", text));
+    }
+    let remainder: String = report.remainder_bits.iter().map(|&b| if b { '1' } else { '0' }).collect();
+    if report.remainder_bits.iter().any(|&b| b) {
+        output.push_str(&format!("⚠ 剩余位非零: {}\n", remainder));
+    } else if !remainder.is_empty() {
+        output.push_str(&format!("剩余位: {}\n", remainder));
+    }
+    if !report.corrected_codewords.is_empty() {
+        output.push_str(&format!("⚠ {} 个码字经纠错修正:\n", report.corrected_codewords.len()));
+        for c in &report.corrected_codewords {
+            output.push_str(&format!(
+                "  块 {} 第 {} 个{}码字: {:02X} -> {:02X}\n",
+                c.block + 1,
+                c.index,
+                if c.is_ecc { "纠错" } else { "数据" },
+                c.raw,
+                c.corrected
+            ));
+        }
+    }
+    if !report.module_diffs.is_empty() {
+        output.push_str(&format!(
+            "⚠ {} 个模块与干净重编码不一致:\n",
+            report.module_diffs.len()
+        ));
+        for diff in &report.module_diffs {
+            let codeword = diff
+                .codeword
+                .map(|c| format!(", 码字 {}", c))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "  ({}, {}) {}{}: 应为{}，实际为{}\n",
+                diff.x,
+                diff.y,
+                diff.role.description(),
+                codeword,
+                if diff.expected { "深" } else { "浅" },
+                if diff.actual { "深" } else { "浅" }
+            ));
+        }
+    }
+    if !report.has_findings() {
+        output.push_str("未发现隐藏数据或非标准结构\n");
+    }
+    output
+}
+
+fn printable(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qr_codec::SegmentMode;
+
+    fn byte_segment(text: &str) -> Segment {
+        Segment {
+            mode: SegmentMode::Byte,
+            char_count: text.len(),
+            data: text.as_bytes().to_vec(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_clean_symbol_has_no_findings() {
+        let data = qr_codec::build_data_codewords(&[byte_segment("clean")], 2, EcLevel::M).unwrap();
+        let matrix = qr_codec::encode_matrix(&data, 2, EcLevel::M, 5);
+        let report = inspect_matrix(&matrix).unwrap();
+        assert_eq!(report.content, "clean");
+        assert!(!report.has_findings());
+        assert!(report.trailing_bytes.iter().all(|b| PAD_CODEWORDS.contains(b)));
+    }
+
+    #[test]
+    fn test_hidden_segment_in_padding() {
+        let mut data = qr_codec::build_data_codewords(&[byte_segment("hi")], 2, EcLevel::L).unwrap();
+        // "hi" 占 4+8+16 位，终止符后对齐到第 4 个字节；在填充区写入字节模式段 "flag"
+        let hidden = qr_codec::build_data_codewords(&[byte_segment("flag")], 2, EcLevel::L).unwrap();
+        data[4..4 + 6].copy_from_slice(&hidden[..6]);
+        let matrix = qr_codec::encode_matrix(&data, 2, EcLevel::L, 0);
+
+        let report = inspect_matrix(&matrix).unwrap();
+        assert_eq!(report.content, "hi");
+        assert!(!report.nonstandard_padding.is_empty());
+        assert_eq!(report.hidden_segments[0].text, "flag");
+        assert!(!report.module_diffs.is_empty());
+        assert!(report.module_diffs.iter().all(|d| d.role == ModuleRole::Data));
+    }
+
+    #[test]
+    fn test_modified_module_is_reported() {
+        let data = qr_codec::build_data_codewords(&[byte_segment("tamper")], 3, EcLevel::H).unwrap();
+        let mut matrix = qr_codec::encode_matrix(&data, 3, EcLevel::H, 2);
+        let (x, y) = qr_codec::data_module_order(matrix.size)[40];
+        matrix.set(x, y, !matrix.get(x, y));
+
+        let report = inspect_matrix(&matrix).unwrap();
+        assert_eq!(report.corrected_codewords.len(), 1);
+        assert_eq!(report.module_diffs.len(), 1);
+        assert_eq!(report.module_diffs[0].codeword, Some(5));
+    }
+}
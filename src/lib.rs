@@ -12,6 +12,34 @@ pub mod wechat_qr_decoder;
 pub mod batch_processor;
 pub mod enhanced_processor;
 pub mod brute_force_decoder;
+pub mod module_matrix;
+pub mod qr_layout;
+pub mod matrix_export;
+pub mod reed_solomon;
+pub mod qr_codec;
+pub mod grid_input;
+pub mod inspection;
+pub mod color_layers;
+pub mod geometry;
+pub mod local_contrast;
+pub mod binarization;
+pub mod illumination;
+pub mod glare;
+pub mod moire;
+pub mod deblur;
+pub mod logo;
+pub mod halftone;
+pub mod pipeline;
+pub mod transform_dsl;
+pub mod composition;
+pub mod search_strategy;
+pub mod search_space;
+pub mod recipe;
+pub mod transform_stats;
+pub mod quality;
+pub mod pyramid;
+pub mod tiff_reader;
+pub mod tiled;
 
 
 // 重新导出主要的公共接口
@@ -24,6 +52,7 @@ pub use types::*;
 pub use batch_processor::{BatchProcessor, BatchConfig, BatchResult};
 pub use enhanced_processor::EnhancedImageProcessor;
 pub use brute_force_decoder::BruteForceDecoder;
+pub use module_matrix::ModuleMatrix;
 
 
 /// 库的版本信息
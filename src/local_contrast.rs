@@ -0,0 +1,139 @@
+//! 局部对比度增强模块
+//!
+//! 全局的直方图均衡化和亮度/对比度/伽马调整对整幅图像使用同一条曲线，
+//! 当聚光灯或阴影斜跨二维码时，亮区和暗区的模块无法同时拉开。本模块提供
+//! CLAHE（限制对比度的自适应直方图均衡化）和基于对数域的局部色调映射，
+//! 按局部区域分别增强对比度。
+
+use opencv::{
+    core::{self, Mat, Size, CV_32F, CV_8U, NORM_MINMAX},
+    imgproc::{self, cvt_color, COLOR_BGR2GRAY, COLOR_GRAY2BGR},
+    prelude::*,
+};
+
+use crate::error::{QRDecodeError, Result};
+
+/// 默认 CLAHE 限幅
+pub const DEFAULT_CLAHE_CLIP_LIMIT: f64 = 2.0;
+
+/// 默认 CLAHE 网格大小（每边分块数）
+pub const DEFAULT_CLAHE_TILE_SIZE: i32 = 8;
+
+/// 局部色调映射估计光照时使用的高斯核 sigma（相对于图像短边的比例）
+const TONE_MAPPING_SIGMA_RATIO: f64 = 0.05;
+
+/// 应用 CLAHE
+///
+/// 在灰度图上按 `tile_size x tile_size` 网格分块均衡化，`clip_limit` 限制每块的
+/// 对比度放大倍数以免放大噪声。输入为彩色图像时返回转换回 BGR 的结果。
+pub fn apply_clahe(image: &Mat, clip_limit: f64, tile_size: i32) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+
+    let mut clahe = imgproc::create_clahe(clip_limit, Size::new(tile_size, tile_size))
+        .map_err(|e| QRDecodeError::image_processing_error(format!("创建 CLAHE 失败: {}", e)))?;
+    let mut result = Mat::default();
+    clahe.apply(&gray, &mut result)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("CLAHE 处理失败: {}", e)))?;
+
+    restore_channels(image, result)
+}
+
+/// 应用局部色调映射
+///
+/// 在对数域中用大尺度高斯模糊估计光照分量，按 `strength` (0.0-1.0) 比例减去后
+/// 再拉伸回 0-255，相当于压缩光照的动态范围而保留模块边缘的细节。
+pub fn apply_local_tone_mapping(image: &Mat, strength: f64) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+
+    let mut float = Mat::default();
+    gray.convert_to(&mut float, CV_32F, 1.0, 1.0)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("转换浮点图像失败: {}", e)))?;
+    let mut log = Mat::default();
+    core::log(&float, &mut log)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("对数变换失败: {}", e)))?;
+
+    let sigma = gray.rows().min(gray.cols()) as f64 * TONE_MAPPING_SIGMA_RATIO;
+    let mut illumination = Mat::default();
+    imgproc::gaussian_blur(&log, &mut illumination, Size::new(0, 0), sigma.max(1.0), 0.0, core::BORDER_REPLICATE, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("光照估计失败: {}", e)))?;
+
+    let mut detail = Mat::default();
+    core::add_weighted(&log, 1.0, &illumination, -strength, 0.0, &mut detail, -1)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("色调映射失败: {}", e)))?;
+
+    let mut result = Mat::default();
+    core::normalize(&detail, &mut result, 0.0, 255.0, NORM_MINMAX, CV_8U, &core::no_array())
+        .map_err(|e| QRDecodeError::image_processing_error(format!("归一化失败: {}", e)))?;
+
+    restore_channels(image, result)
+}
+
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    if image.channels() == 1 {
+        return Ok(image.clone());
+    }
+
+    let mut gray = Mat::default();
+    cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+    Ok(gray)
+}
+
+/// 原图是彩色的时候转换回彩色，保持与其他变换相同的通道数
+fn restore_channels(original: &Mat, gray: Mat) -> Result<Mat> {
+    if original.channels() != 3 {
+        return Ok(gray);
+    }
+
+    let mut color = Mat::default();
+    cvt_color(&gray, &mut color, COLOR_GRAY2BGR, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转彩色失败: {}", e)))?;
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::{Scalar, CV_8UC1, CV_8UC3};
+
+    /// 左暗右亮的光照梯度上叠加棋盘格
+    fn shaded_checkerboard() -> Mat {
+        let mut image = Mat::new_rows_cols_with_default(64, 128, CV_8UC1, Scalar::all(0.0)).unwrap();
+        let data = image.data_bytes_mut().unwrap();
+        for y in 0..64 {
+            for x in 0..128 {
+                let light = 20 + x;
+                let dark = (x / 8 + y / 8) % 2 == 0;
+                data[y * 128 + x] = if dark { (light / 3) as u8 } else { light as u8 };
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn test_local_enhancement_keeps_shape() {
+        let gray = shaded_checkerboard();
+        let clahe = apply_clahe(&gray, DEFAULT_CLAHE_CLIP_LIMIT, DEFAULT_CLAHE_TILE_SIZE).unwrap();
+        assert_eq!(clahe.typ(), CV_8UC1);
+        assert_eq!((clahe.rows(), clahe.cols()), (64, 128));
+
+        let mut color = Mat::default();
+        cvt_color(&gray, &mut color, COLOR_GRAY2BGR, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT).unwrap();
+        let toned = apply_local_tone_mapping(&color, 0.8).unwrap();
+        assert_eq!(toned.typ(), CV_8UC3);
+    }
+
+    #[test]
+    fn test_tone_mapping_evens_out_shading() {
+        let gray = shaded_checkerboard();
+        let toned = apply_local_tone_mapping(&gray, 0.9).unwrap();
+        let data = toned.data_bytes().unwrap();
+
+        // 暗区和亮区中浅色方块的亮度差应明显小于原图
+        let original = gray.data_bytes().unwrap();
+        let (left, right) = (8 * 128 + 4, 8 * 128 + 116);
+        let before = original[right] as i32 - original[left] as i32;
+        let after = data[right] as i32 - data[left] as i32;
+        assert!(after.abs() < before.abs(), "{} -> {}", before, after);
+    }
+}
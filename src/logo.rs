@@ -0,0 +1,162 @@
+//! 中心 logo 修复模块
+//!
+//! 品牌二维码常在中心覆盖一块 logo，依靠纠错码恢复被遮住的模块。照片质量较差时，
+//! 大块纯色的 logo 还会干扰检测器。本模块在模块网格中寻找远大于单个模块的纯色
+//! 区域（logo 本身或其底板），可以用深浅相间的中性模块覆盖它，也可以在模块矩阵
+//! 采样时把它标记为擦除。
+
+use opencv::core::Mat;
+
+use crate::error::Result;
+use crate::module_matrix::{self, ModuleMatrix};
+
+/// logo 区域至少包含的纯色窗口边长（模块）
+pub const LOGO_MIN_MODULES: usize = 5;
+
+/// logo 区域边长相对符号边长的上限
+const LOGO_MAX_RATIO: f64 = 0.6;
+
+/// 定位图形及分隔符占据的边长（模块）
+const FINDER_REGION: usize = 8;
+
+/// 模块网格中的 logo 区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogoRegion {
+    /// 左上角模块列
+    pub x: usize,
+    /// 左上角模块行
+    pub y: usize,
+    /// 宽度（模块）
+    pub width: usize,
+    /// 高度（模块）
+    pub height: usize,
+}
+
+impl LogoRegion {
+    /// 区域内的模块坐标
+    pub fn modules(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.y..self.y + self.height).flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+
+    /// 将区域内的模块标记为未知
+    pub fn mark_unknown(&self, matrix: &mut ModuleMatrix) {
+        for (x, y) in self.modules() {
+            matrix.set_unknown(x, y, true);
+        }
+    }
+}
+
+/// 在模块矩阵中查找 logo 区域
+///
+/// 随机分布的数据模块几乎不可能出现 `min_modules x min_modules` 的纯色窗口，
+/// 因此取所有纯色窗口覆盖的模块的外接矩形作为 logo 区域；底板被中间的图案分成
+/// 几块时，图案也会落在外接矩形内。定位图形附近的窗口不参与。
+pub fn find_logo_region(matrix: &ModuleMatrix, min_modules: usize) -> Option<LogoRegion> {
+    let n = matrix.size;
+    let k = min_modules.max(2);
+    if n < FINDER_REGION * 2 + k {
+        return None;
+    }
+
+    let overlaps_finder = |x: usize, y: usize| {
+        let near = |v: usize| v < FINDER_REGION;
+        let far = |v: usize| v + k > n - FINDER_REGION;
+        (near(x) && near(y)) || (far(x) && near(y)) || (near(x) && far(y))
+    };
+
+    let mut solid = vec![false; n * n];
+    for y0 in 0..=n - k {
+        for x0 in 0..=n - k {
+            if overlaps_finder(x0, y0) {
+                continue;
+            }
+            let colour = matrix.get(x0, y0);
+            let uniform = (y0..y0 + k).all(|y| (x0..x0 + k).all(|x| matrix.get(x, y) == colour && !matrix.is_unknown(x, y)));
+            if uniform {
+                for y in y0..y0 + k {
+                    for x in x0..x0 + k {
+                        solid[y * n + x] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    let cells = solid.iter().enumerate().filter(|(_, &covered)| covered).map(|(i, _)| (i % n, i / n));
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (n, n, 0, 0);
+    for (x, y) in cells {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    if min_x > max_x {
+        return None;
+    }
+
+    // 纯色窗口分布过广说明不是 logo（例如采样失败），不做处理
+    let limit = (n as f64 * LOGO_MAX_RATIO) as usize;
+    let region = LogoRegion { x: min_x, y: min_y, width: max_x - min_x + 1, height: max_y - min_y + 1 };
+    (region.width <= limit && region.height <= limit).then_some(region)
+}
+
+/// 用深浅相间的中性模块覆盖 logo 区域
+///
+/// 棋盘格使覆盖区域深浅各半，既不偏向任何一种取值，也不会留下干扰检测的大块纯色。
+pub fn paint_neutral_modules(image: &Mat, corners: &[(f32, f32)], size: usize, region: &LogoRegion) -> Result<Mat> {
+    let cells = region.modules().map(|(x, y)| (x, y, (x + y) % 2 == 0));
+    module_matrix::paint_modules(image, corners, size, cells)
+}
+
+/// 检测并覆盖中心 logo；定位不到符号或找不到 logo 时原样返回
+pub fn inpaint_logo(image: &Mat) -> Result<Mat> {
+    let Some(corners) = module_matrix::locate_symbol(image)? else {
+        return Ok(image.clone());
+    };
+    let matrix = module_matrix::sample_from_corners(image, &corners, None)?;
+    match find_logo_region(&matrix, LOGO_MIN_MODULES) {
+        Some(region) => paint_neutral_modules(image, &corners, matrix.size, &region),
+        None => Ok(image.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_matrix(size: usize) -> ModuleMatrix {
+        let mut matrix = ModuleMatrix::new(size);
+        let mut seed = 2024u32;
+        for y in 0..size {
+            for x in 0..size {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                matrix.set(x, y, (seed >> 16) & 1 == 1);
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn test_find_logo_region() {
+        assert_eq!(find_logo_region(&random_matrix(33), LOGO_MIN_MODULES), None);
+
+        // 中心 11x9 的浅色底板，中间画一个深色图案
+        let mut matrix = random_matrix(33);
+        for y in 12..21 {
+            for x in 11..22 {
+                matrix.set(x, y, false);
+            }
+        }
+        matrix.set(16, 16, true);
+        matrix.set(16, 15, true);
+
+        let region = find_logo_region(&matrix, LOGO_MIN_MODULES).unwrap();
+        assert!(region.x <= 11 && region.y <= 12, "{:?}", region);
+        assert!(region.x + region.width >= 22 && region.y + region.height >= 21, "{:?}", region);
+        assert!(region.width <= 13 && region.height <= 11, "{:?}", region);
+
+        region.mark_unknown(&mut matrix);
+        assert!(matrix.is_unknown(16, 16));
+        assert_eq!(matrix.unknown_count(), region.width * region.height);
+    }
+}
@@ -16,6 +16,36 @@ mod wechat_qr_decoder;
 mod types;
 mod batch_processor;
 mod progress_display;
+mod module_matrix;
+mod qr_layout;
+mod matrix_export;
+mod reed_solomon;
+mod qr_codec;
+mod grid_input;
+mod inspection;
+mod color_layers;
+mod geometry;
+mod local_contrast;
+mod binarization;
+mod illumination;
+mod glare;
+mod moire;
+mod deblur;
+mod logo;
+mod halftone;
+mod pipeline;
+mod transform_dsl;
+mod composition;
+mod search_strategy;
+mod search_space;
+mod recipe;
+mod transform_stats;
+mod quality;
+mod pyramid;
+mod tiff_reader;
+mod tiled;
+
+use opencv::prelude::MatTraitConst;
 
 use cli::Args;
 use error::{QRDecodeError, Result};
@@ -24,7 +54,7 @@ use enhanced_processor::EnhancedImageProcessor;
 use brute_force_decoder::BruteForceDecoder;
 use output::OutputFormatter;
 use qr_decoder::QRDecoder;
-use types::ProcessingConfig;
+use types::{OutputFormat, ProcessingConfig, QRCodeResult};
 use batch_processor::{BatchProcessor, BatchConfig};
 use progress_display::ProgressDisplay;
 
@@ -141,19 +171,73 @@ fn process_image(config: &ProcessingConfig) -> Result<()> {
         }
     }
     
+    // 文本模块网格直接解码，不经过图像处理
+    if config.grid_input {
+        return process_grid(config, &formatter);
+    }
+    
+    // 分块扫描按行带读取图像，不整幅加载
+    if config.tiled {
+        return process_tiled(config, &formatter);
+    }
+    
     // 加载和预处理图像
     let processor = ImageProcessor::new(config);
     let image = processor.load_image(&config.input_path)?;
     
     formatter.output_progress("📷 图像加载完成");
     
+    // 用户提供了角点时直接按网格采样，跳过检测
+    if let Some(corners) = &config.corners {
+        let mut occlusion_mask = match &config.occlusion_mask {
+            Some(path) => Some(processor.load_image(path)?),
+            None => None,
+        };
+        if config.glare_erasures {
+            let glare_mask = glare::detect_glare(&image, glare::DEFAULT_GLARE_THRESHOLD)?;
+            formatter.output_progress(&format!("✨ 检测到 {} 个反光像素", opencv::core::count_non_zero(&glare_mask)?));
+            occlusion_mask = Some(match occlusion_mask {
+                Some(mask) => glare::merge_masks(&mask, &glare_mask)?,
+                None => glare_mask,
+            });
+        }
+        let erasures = module_matrix::ErasureSources {
+            ambiguity_margin: config.ambiguity_margin,
+            occlusion_mask,
+            logo: config.logo_erasures,
+        };
+        return process_corners(config, &formatter, &image, corners, &erasures);
+    }
+    
+    // 彩色分层模式在原始彩色图像上逐层解码，不做灰度预处理
+    if config.color_layers {
+        return process_color_layers(config, &formatter, &image);
+    }
+    
+    // 金字塔模式在原图上逐层分块检测，适合大幅图像中的小码
+    if config.pyramid {
+        return process_pyramid(config, &formatter, &image);
+    }
+    
+    // 自动模式先评估图像质量，按问题选择预处理、变换配置和是否升级到暴力破解
+    let assessment = if config.auto {
+        let report = quality::assess(&image)?;
+        formatter.output_progress(&format!("🩺 图像质量: {}", report.summary()));
+        formatter.output_progress(&format!("🩺 质量问题: {}，处理路径: {}", report.issue_description(), report.recommendation.description()));
+        Some(report)
+    } else {
+        None
+    };
+    let auto_config = assessment.as_ref().map(|report| report.recommendation.apply(config));
+    let config = auto_config.as_ref().unwrap_or(config);
+    
     let processed_image = if config.preprocess {
         formatter.output_progress("🔧 开始图像预处理...");
         let processed = processor.preprocess_image(&image)?;
         formatter.output_progress("✨ 图像预处理完成");
         processed
     } else {
-        image
+        image.clone()
     };
     
     // 保存预处理后的图像（如果需要）
@@ -164,41 +248,183 @@ fn process_image(config: &ProcessingConfig) -> Result<()> {
         }
     }
     
-    formatter.output_progress("🔍 开始增强二维码检测和解码...");
+    // 重放配方和暴力破解共用同一个解码器，本次成功的配方随之记录
+    let mut brute_force_decoder = if config.brute_force || config.recipe.is_some() {
+        Some(BruteForceDecoder::from_processing_config(config)?)
+    } else {
+        None
+    };
+    
+    // 先按成功次数重放已有配方
+    let recipe_results = match brute_force_decoder.as_mut() {
+        Some(decoder) if !decoder.recipes().is_empty() => {
+            formatter.output_progress(&format!("📋 重放 {} 条变换配方...", decoder.recipes().len()));
+            decoder.decode_with_recipes(&processed_image)?
+        }
+        _ => Vec::new(),
+    };
     
     // 使用增强图像处理器进行解码
      let mut enhanced_processor = EnhancedImageProcessor::new(config.clone())?;
-    let filtered_results = enhanced_processor.decode_with_transforms(&processed_image)?;
+    let filtered_results = if recipe_results.is_empty() {
+        formatter.output_progress("🔍 开始增强二维码检测和解码...");
+        enhanced_processor.decode_with_transforms(&processed_image)?
+    } else {
+        recipe_results
+    };
     
     // 如果增强解码没有找到结果且启用了暴力破解，尝试暴力破解解码
-    let final_results = if filtered_results.is_empty() && config.brute_force {
-        formatter.output_progress("🔨 开始暴力破解解码...");
-        let mut brute_force_decoder = BruteForceDecoder::new()?;
-        let brute_results = brute_force_decoder.detect_and_decode(&processed_image)?;
-        formatter.output_progress(&format!(
-            "💪 暴力破解解码完成，找到 {} 个二维码",
-            brute_results.len()
-        ));
-        brute_results
-    } else {
-        filtered_results
+    let final_results = match brute_force_decoder.as_mut() {
+        Some(decoder) if filtered_results.is_empty() && config.brute_force => {
+            formatter.output_progress("🔨 开始暴力破解解码...");
+            let brute_results = decoder.detect_and_decode(&processed_image)?;
+            formatter.output_progress(&format!(
+                "💪 暴力破解解码完成，评估 {} 种参数组合，找到 {} 个二维码",
+                decoder.evaluations(),
+                brute_results.len()
+            ));
+            brute_results
+        }
+        _ => filtered_results,
     };
     
+    // 保存已加载和本次成功的配方
+    if let (Some(path), Some(decoder)) = (&config.save_recipe, &brute_force_decoder) {
+        if !decoder.recipes().is_empty() {
+            decoder.recipes().save(path)?;
+            formatter.output_progress(&format!("📋 {} 条变换配方已保存到: {}", decoder.recipes().len(), path.display()));
+        }
+    }
+    
     // 打印变换统计信息
     if config.verbose {
         enhanced_processor.print_transform_stats();
     }
     
+    // 质量评估报告随结果一起输出，没有结果时单独输出
+    let final_results = match assessment {
+        Some(report) if final_results.is_empty() => {
+            formatter.output_quality_report(&report)?;
+            final_results
+        }
+        Some(report) => final_results.into_iter().map(|result| result.with_quality(report.clone())).collect(),
+        None => final_results,
+    };
+    
+    finish_results(config, &formatter, Some(&image), final_results)
+}
+
+fn process_pyramid(
+    config: &ProcessingConfig,
+    formatter: &OutputFormatter,
+    image: &opencv::core::Mat,
+) -> Result<()> {
+    formatter.output_progress(&format!(
+        "🔭 多尺度金字塔检测 ({}x{}，图块 {} 像素，最大 {:.2} 倍)...",
+        image.cols(),
+        image.rows(),
+        config.pyramid_tile,
+        config.pyramid_max_scale
+    ));
+    
+    let mut decoder = pyramid::tile_decoder(config);
+    let results = pyramid::detect(image, &mut decoder, config.pyramid_tile, config.pyramid_max_scale, |scale, tiles| {
+        formatter.output_progress(&format!("🔭 缩放 {:.3} 倍，{} 个图块", scale, tiles));
+    })?;
+    
+    finish_results(config, formatter, Some(image), results)
+}
+
+fn process_tiled(config: &ProcessingConfig, formatter: &OutputFormatter) -> Result<()> {
+    let limit = config.max_memory_mb << 20;
+    let mut source = tiled::RowSource::open(&config.input_path, limit)?;
+    let tile_size = tiled::fit_tile_size(
+        source.width(),
+        config.tile_size,
+        config.tile_overlap,
+        source.resident_bytes(),
+        limit,
+    )
+    .map_err(|e| {
+        if source.is_streaming() {
+            e
+        } else {
+            QRDecodeError::invalid_input(format!("{}（该格式需要整幅解码，可转换为无压缩或 PackBits 的 TIFF 流式读取）", e))
+        }
+    })?;
+    formatter.output_progress(&format!(
+        "🧩 分块扫描 ({}x{}，{}，图块 {} 像素，重叠 {} 像素，预计峰值 {} MB)...",
+        source.width(),
+        source.height(),
+        if source.is_streaming() { "TIFF 流式读取" } else { "整幅解码" },
+        tile_size,
+        config.tile_overlap,
+        tiled::peak_bytes(source.width(), tile_size, source.resident_bytes()).div_ceil(1 << 20)
+    ));
+    if tile_size < config.tile_size {
+        formatter.output_progress(&format!(
+            "🧩 内存上限 {} MB，图块由 {} 缩小到 {} 像素",
+            config.max_memory_mb, config.tile_size, tile_size
+        ));
+    }
+    
+    let mut decoder = pyramid::tile_decoder(config);
+    let attach_matrices = config.matrix_export.is_some() || config.inspect;
+    let results = tiled::scan(&mut source, &mut decoder, tile_size, config.tile_overlap, attach_matrices, |done, total| {
+        formatter.output_progress(&format!("🧩 已扫描 {}/{} 个图块", done, total));
+    })?;
+    
+    finish_results(config, formatter, None, results)
+}
+
+fn process_color_layers(
+    config: &ProcessingConfig,
+    formatter: &OutputFormatter,
+    image: &opencv::core::Mat,
+) -> Result<()> {
+    formatter.output_progress("🎨 拆分颜色通道并聚类分层解码...");
+    
+    let mut enhanced_processor = EnhancedImageProcessor::new(config.clone())?;
+    let results = color_layers::decode_color_layers(&mut enhanced_processor, image, config.verbose)?;
+    for result in &results {
+        if let Some(layer) = &result.layer {
+            formatter.output_progress(&format!("🧅 图层 {} 解码成功", layer));
+        }
+    }
+    
+    if config.verbose {
+        enhanced_processor.print_transform_stats();
+    }
+    
+    finish_results(config, formatter, Some(image), results)
+}
+
+fn finish_results(
+    config: &ProcessingConfig,
+    formatter: &OutputFormatter,
+    image: Option<&opencv::core::Mat>,
+    mut final_results: Vec<QRCodeResult>,
+) -> Result<()> {
     formatter.output_progress(&format!(
         "🎯 解码完成，找到 {} 个二维码（置信度 >= {:.2}）",
         final_results.len(),
         config.min_confidence
     ));
     
+    // 按角点补充采样模块矩阵，使 JSON 输出也包含矩阵（分块扫描已在图块上采样）
+    if let Some(image) = image.filter(|_| config.matrix_export.is_some() || config.inspect) {
+        matrix_export::attach_module_matrices(image, &mut final_results)?;
+    }
+    inspect_results(config, formatter, &mut final_results);
+    
     // 输出结果
      formatter.output_results(&final_results)?;
      formatter.output_summary(&final_results)?;
     
+    if !final_results.is_empty() {
+        export_module_matrices(config, formatter, &final_results)?;
+    }
+    
     // 如果没有找到二维码，返回特定错误
     if final_results.is_empty() {
         return Err(QRDecodeError::invalid_input("未找到二维码".to_string()));
@@ -207,6 +433,121 @@ fn process_image(config: &ProcessingConfig) -> Result<()> {
     Ok(())
 }
 
+fn process_corners(
+    config: &ProcessingConfig,
+    formatter: &OutputFormatter,
+    image: &opencv::core::Mat,
+    corners: &[(f32, f32)],
+    erasures: &module_matrix::ErasureSources,
+) -> Result<()> {
+    formatter.output_progress("📐 根据用户提供的角点采样模块矩阵...");
+    
+    let matrix = module_matrix::sample_with_erasures(image, corners, config.module_count, erasures)?;
+    formatter.output_progress(&format!(
+        "🧩 采样得到 {}x{} 模块矩阵，未知模块 {} 个",
+        matrix.size, matrix.size, matrix.unknown_count()
+    ));
+    
+    let mut decoder = QRDecoder::new(config);
+    match decoder.decode_module_matrix(&matrix, corners) {
+        Ok(result) => {
+            // JSON 和详细格式本身已包含模块矩阵
+            let embedded = matches!(config.output_format, OutputFormat::Json | OutputFormat::Verbose);
+            if !embedded && config.matrix_export.is_none() {
+                formatter.output_module_matrix(&matrix);
+            }
+            let mut results = vec![result];
+            report_correction(formatter, &results);
+            inspect_results(config, formatter, &mut results);
+            formatter.output_results(&results)?;
+            formatter.output_summary(&results)?;
+            export_module_matrices(config, formatter, &results)
+        }
+        Err(err) => {
+            // 解码失败时仍然输出采样结果，便于排查
+            formatter.output_module_matrix(&matrix);
+            Err(err)
+        }
+    }
+}
+
+fn process_grid(config: &ProcessingConfig, formatter: &OutputFormatter) -> Result<()> {
+    formatter.output_progress("📝 读取文本模块网格...");
+    
+    let matrix = grid_input::load_grid(&config.input_path)?;
+    formatter.output_progress(&format!(
+        "🧩 网格为 {}x{} 模块矩阵，未知模块 {} 个",
+        matrix.size, matrix.size, matrix.unknown_count()
+    ));
+    
+    let mut decoder = QRDecoder::new(config);
+    let mut results = vec![decoder.decode_module_grid(&matrix)?];
+    report_correction(formatter, &results);
+    inspect_results(config, formatter, &mut results);
+    formatter.output_results(&results)?;
+    formatter.output_summary(&results)?;
+    export_module_matrices(config, formatter, &results)
+}
+
+fn report_correction(formatter: &OutputFormatter, results: &[QRCodeResult]) {
+    for correction in results.iter().filter_map(|r| r.correction.as_ref()) {
+        formatter.output_progress(&format!(
+            "🩹 版本 {}-{}: 纠正擦除 {} 个、错误 {} 个码字 ({} 块，每块 {} 个纠错码字)",
+            correction.version, correction.ec_level,
+            correction.erasures_corrected, correction.errors_corrected,
+            correction.blocks, correction.ecc_per_block
+        ));
+    }
+}
+
+fn inspect_results(config: &ProcessingConfig, formatter: &OutputFormatter, results: &mut [QRCodeResult]) {
+    if !config.inspect {
+        return;
+    }
+    
+    for result in results.iter_mut() {
+        let matrix = match &result.module_matrix {
+            Some(matrix) => matrix,
+            None => {
+                formatter.output_progress("⚠️ 结果缺少角点信息，无法采样模块矩阵进行隐藏数据检查");
+                continue;
+            }
+        };
+        match inspection::inspect_matrix(matrix) {
+            Ok(report) => {
+                if report.has_findings() {
+                    formatter.output_progress("🕵️ 隐藏数据检查发现异常");
+                }
+                result.inspection = Some(report);
+            }
+            Err(err) => formatter.output_progress(&format!("⚠️ 隐藏数据检查失败: {}", err)),
+        }
+    }
+}
+
+fn export_module_matrices(
+    config: &ProcessingConfig,
+    formatter: &OutputFormatter,
+    results: &[QRCodeResult],
+) -> Result<()> {
+    let format = match config.matrix_export {
+        Some(format) => format,
+        None => return Ok(()),
+    };
+    
+    let written = matrix_export::export_matrices(
+        results,
+        format,
+        config.matrix_output_path.as_deref(),
+        &config.input_path,
+    )?;
+    for path in written {
+        formatter.output_progress(&format!("🧩 模块矩阵已导出到: {}", path.display()));
+    }
+    
+    Ok(())
+}
+
 fn process_batch(args: &Args) -> Result<()> {
     // 获取批量处理目录
     let directory = args.get_batch_directory()
@@ -224,6 +565,7 @@ fn process_batch(args: &Args) -> Result<()> {
         colored_output: args.is_colored_output(),
         verbose: args.verbose,
         quiet: args.quiet,
+        processing: ProcessingConfig::from_args(args)?,
     };
     
     // 创建批量处理器
@@ -0,0 +1,235 @@
+//! 模块矩阵导出模块
+//!
+//! 将解码器实际采样到的模块矩阵导出为 ASCII 字符画、0/1 文本网格或重新渲染的 PNG，
+//! 并标注格式信息、版本信息和数据区，便于取证分析和排查受损符号。
+
+use opencv::{
+    core::{Mat, Scalar, CV_8UC3},
+    prelude::*,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{QRDecodeError, Result};
+use crate::module_matrix::{self, ModuleMatrix};
+use crate::qr_layout::{self, ModuleRole};
+use crate::types::{MatrixExportFormat, QRCodeResult};
+
+/// PNG 导出时每个模块的像素数
+const PNG_MODULE_PX: i32 = 12;
+
+/// PNG 导出时的静区宽度（模块数）
+const PNG_QUIET_ZONE: usize = 4;
+
+/// PNG 导出时未知模块的 BGR 颜色
+const UNKNOWN_COLOR: [u8; 3] = [200, 0, 200];
+
+/// 为缺少模块矩阵的结果按角点补充采样
+pub fn attach_module_matrices(image: &Mat, results: &mut [QRCodeResult]) -> Result<()> {
+    for result in results.iter_mut() {
+        if result.module_matrix.is_some() {
+            continue;
+        }
+        if let Some(corners) = result.position.corners.as_ref().filter(|c| c.len() == 4) {
+            let matrix = module_matrix::sample_from_corners(image, corners, None)?;
+            result.module_matrix = Some(matrix);
+        }
+    }
+    Ok(())
+}
+
+/// 导出所有结果的模块矩阵
+///
+/// 文本格式未指定输出路径时写入标准输出；PNG 格式未指定路径时保存在输入图像旁。
+pub fn export_matrices(
+    results: &[QRCodeResult],
+    format: MatrixExportFormat,
+    output_path: Option<&Path>,
+    input_path: &Path,
+) -> Result<Vec<PathBuf>> {
+    let matrices: Vec<&ModuleMatrix> = results.iter().filter_map(|r| r.module_matrix.as_ref()).collect();
+    if matrices.is_empty() {
+        return Err(QRDecodeError::output_error("没有可导出的模块矩阵".to_string()));
+    }
+
+    match format {
+        MatrixExportFormat::Ascii | MatrixExportFormat::Binary => {
+            let text = matrices
+                .iter()
+                .map(|matrix| match format {
+                    MatrixExportFormat::Ascii => to_annotated_ascii(matrix),
+                    _ => to_annotated_binary(matrix),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            match output_path {
+                Some(path) => {
+                    fs::write(path, text).map_err(|e| {
+                        QRDecodeError::output_error(format!("写入模块矩阵失败 {}: {}", path.display(), e))
+                    })?;
+                    Ok(vec![path.to_path_buf()])
+                }
+                None => {
+                    println!("{}", text);
+                    Ok(Vec::new())
+                }
+            }
+        }
+        MatrixExportFormat::Png => {
+            let base = match output_path {
+                Some(path) => path.to_path_buf(),
+                None => {
+                    let stem = input_path.file_stem().and_then(|s| s.to_str()).unwrap_or("qr");
+                    input_path.with_file_name(format!("{}_matrix.png", stem))
+                }
+            };
+
+            let mut written = Vec::new();
+            for (i, matrix) in matrices.iter().enumerate() {
+                let path = if matrices.len() == 1 {
+                    base.clone()
+                } else {
+                    numbered_path(&base, i + 1)
+                };
+                let image = render_annotated(matrix, PNG_MODULE_PX)?;
+                let path_str = path.to_string_lossy();
+                opencv::imgcodecs::imwrite(&path_str, &image, &opencv::core::Vector::new())
+                    .map_err(|e| QRDecodeError::output_error(format!("保存模块矩阵图像失败 {}: {}", path_str, e)))?;
+                written.push(path);
+            }
+            Ok(written)
+        }
+    }
+}
+
+/// 生成注释头：尺寸、版本、格式信息和版本信息
+pub fn annotation_header(matrix: &ModuleMatrix) -> String {
+    let mut header = String::new();
+    let version = matrix
+        .version()
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "未知".to_string());
+    header.push_str(&format!("# 模块矩阵 {}x{} (版本 {})\n", matrix.size, matrix.size, version));
+
+    match qr_layout::read_format_info(matrix) {
+        Some(info) => header.push_str(&format!(
+            "# 格式信息: 纠错等级 {}, 掩码 {} (汉明距离 {})\n",
+            info.ec_level, info.mask, info.distance
+        )),
+        None => header.push_str("# 格式信息: 无法识别\n"),
+    }
+
+    match matrix.version() {
+        Some(v) if v >= 7 => match qr_layout::read_version_info(matrix) {
+            Some(read) if read == v => header.push_str(&format!("# 版本信息: {} (与尺寸一致)\n", read)),
+            Some(read) => header.push_str(&format!("# 版本信息: {} (与尺寸推算的版本 {} 不一致)\n", read, v)),
+            None => header.push_str("# 版本信息: 无法识别\n"),
+        },
+        _ => header.push_str("# 版本信息: 无 (版本 7 以下不含版本信息)\n"),
+    }
+
+    let data_modules = qr_layout::module_roles(matrix.size)
+        .iter()
+        .filter(|&&role| role == ModuleRole::Data)
+        .count();
+    header.push_str(&format!("# 数据区模块: {}\n", data_modules));
+    if matrix.unknown_count() > 0 {
+        header.push_str(&format!("# 未知模块: {}\n", matrix.unknown_count()));
+    }
+    header
+}
+
+/// 生成作用映射网格及图例
+pub fn role_map(matrix: &ModuleMatrix) -> String {
+    let roles = qr_layout::module_roles(matrix.size);
+    let legend = [
+        ModuleRole::Finder,
+        ModuleRole::Separator,
+        ModuleRole::Timing,
+        ModuleRole::Alignment,
+        ModuleRole::FormatInfo,
+        ModuleRole::VersionInfo,
+        ModuleRole::DarkModule,
+        ModuleRole::Data,
+    ]
+    .iter()
+    .map(|role| format!("{}={}", role.symbol(), role.description()))
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    let mut output = format!("# 作用映射: {}\n", legend);
+    for row in roles.chunks(matrix.size) {
+        output.extend(row.iter().map(|role| role.symbol()));
+        output.push('\n');
+    }
+    output
+}
+
+/// 带注释的 ASCII 字符画
+pub fn to_annotated_ascii(matrix: &ModuleMatrix) -> String {
+    format!("{}{}{}", annotation_header(matrix), matrix.to_ascii(), role_map(matrix))
+}
+
+/// 带注释的 0/1 文本网格
+pub fn to_annotated_binary(matrix: &ModuleMatrix) -> String {
+    let mut grid = String::with_capacity(matrix.size * (matrix.size + 1));
+    for y in 0..matrix.size {
+        grid.extend((0..matrix.size).map(|x| matrix.cell_char(x, y, '1', '0')));
+        grid.push('\n');
+    }
+    format!("{}{}{}", annotation_header(matrix), grid, role_map(matrix))
+}
+
+/// 渲染带颜色标注的模块矩阵图像
+///
+/// 数据区为黑白，功能图形为灰色，格式信息为红色，版本信息为蓝色，未知模块为洋红色。
+pub fn render_annotated(matrix: &ModuleMatrix, module_px: i32) -> Result<Mat> {
+    let roles = qr_layout::module_roles(matrix.size);
+    let total = (matrix.size + PNG_QUIET_ZONE * 2) as i32 * module_px;
+    let mut image = Mat::new_rows_cols_with_default(total, total, CV_8UC3, Scalar::all(255.0))?;
+
+    let data = image.data_bytes_mut()?;
+    let stride = total as usize * 3;
+    let module_px = module_px as usize;
+    for y in 0..matrix.size {
+        for x in 0..matrix.size {
+            let color = if matrix.is_unknown(x, y) {
+                UNKNOWN_COLOR
+            } else {
+                role_color(roles[y * matrix.size + x], matrix.get(x, y))
+            };
+            let top = (y + PNG_QUIET_ZONE) * module_px;
+            let left = (x + PNG_QUIET_ZONE) * module_px;
+            for row in top..top + module_px {
+                for col in left..left + module_px {
+                    let offset = row * stride + col * 3;
+                    data[offset..offset + 3].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+/// 模块角色对应的 BGR 颜色
+fn role_color(role: ModuleRole, dark: bool) -> [u8; 3] {
+    match (role, dark) {
+        (ModuleRole::Data, true) => [0, 0, 0],
+        (ModuleRole::Data, false) => [255, 255, 255],
+        (ModuleRole::FormatInfo, true) => [0, 0, 170],
+        (ModuleRole::FormatInfo, false) => [180, 180, 255],
+        (ModuleRole::VersionInfo, true) => [170, 40, 0],
+        (ModuleRole::VersionInfo, false) => [255, 210, 170],
+        (_, true) => [70, 70, 70],
+        (_, false) => [215, 215, 215],
+    }
+}
+
+/// 为多个符号生成带序号的输出路径
+fn numbered_path(base: &Path, index: usize) -> PathBuf {
+    let stem = base.file_stem().and_then(|s| s.to_str()).unwrap_or("matrix");
+    let extension = base.extension().and_then(|s| s.to_str()).unwrap_or("png");
+    base.with_file_name(format!("{}_{}.{}", stem, index, extension))
+}
@@ -0,0 +1,571 @@
+//! 模块矩阵采样模块
+//!
+//! 根据用户提供的四个角点，直接从图像中按网格采样二维码模块，
+//! 跳过自动检测步骤。采样得到的模块矩阵可以重新渲染为干净的图像用于解码。
+
+use opencv::{
+    core::{self, Mat, Point, Point2f, Scalar, Size, Vector, BORDER_REPLICATE, CV_8UC1, DECOMP_LU},
+    imgproc::{cvt_color, fill_convex_poly, get_perspective_transform, warp_perspective, COLOR_BGR2GRAY, INTER_LINEAR, LINE_8},
+    objdetect::QRCodeDetector,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+use crate::logo;
+
+/// 采样时每个模块对应的像素数
+const SAMPLE_MODULE_PX: i32 = 9;
+
+/// 自动估计模块数时使用的矫正图像边长
+const ESTIMATE_SIDE_PX: i32 = 885;
+
+/// 二维码模块矩阵
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "MatrixRepr", try_from = "MatrixRepr")]
+pub struct ModuleMatrix {
+    /// 每边的模块数
+    pub size: usize,
+    /// 按行存储的模块值，true 表示深色模块
+    pub modules: Vec<bool>,
+    /// 按行存储的未知标记，true 表示该模块的值无法确定
+    pub unknown: Vec<bool>,
+}
+
+/// 模块矩阵的序列化表示（每行一个 0/1 字符串，未知模块为 ?）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MatrixRepr {
+    size: usize,
+    rows: Vec<String>,
+}
+
+impl From<ModuleMatrix> for MatrixRepr {
+    fn from(matrix: ModuleMatrix) -> Self {
+        let rows = (0..matrix.size)
+            .map(|y| {
+                (0..matrix.size)
+                    .map(|x| matrix.cell_char(x, y, '1', '0'))
+                    .collect()
+            })
+            .collect();
+        Self { size: matrix.size, rows }
+    }
+}
+
+impl TryFrom<MatrixRepr> for ModuleMatrix {
+    type Error = String;
+
+    fn try_from(repr: MatrixRepr) -> std::result::Result<Self, Self::Error> {
+        if repr.rows.len() != repr.size {
+            return Err(format!("模块矩阵行数 {} 与尺寸 {} 不符", repr.rows.len(), repr.size));
+        }
+        let mut matrix = ModuleMatrix::new(repr.size);
+        for (y, row) in repr.rows.iter().enumerate() {
+            if row.chars().count() != repr.size {
+                return Err(format!("模块矩阵第 {} 行长度与尺寸 {} 不符", y + 1, repr.size));
+            }
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '1' => matrix.set(x, y, true),
+                    '0' => {}
+                    '?' => matrix.set_unknown(x, y, true),
+                    _ => return Err(format!("模块矩阵第 {} 行包含无效字符: {}", y + 1, c)),
+                }
+            }
+        }
+        Ok(matrix)
+    }
+}
+
+impl ModuleMatrix {
+    /// 创建全部为浅色模块的矩阵
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            modules: vec![false; size * size],
+            unknown: vec![false; size * size],
+        }
+    }
+
+    /// 获取模块值
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// 设置模块值
+    pub fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+    }
+
+    /// 模块值是否未知
+    pub fn is_unknown(&self, x: usize, y: usize) -> bool {
+        self.unknown[y * self.size + x]
+    }
+
+    /// 标记模块值是否未知
+    pub fn set_unknown(&mut self, x: usize, y: usize, unknown: bool) {
+        self.unknown[y * self.size + x] = unknown;
+    }
+
+    /// 未知模块数
+    pub fn unknown_count(&self) -> usize {
+        self.unknown.iter().filter(|&&u| u).count()
+    }
+
+    /// 按模块状态选择字符，未知模块为 ?
+    pub fn cell_char(&self, x: usize, y: usize, dark: char, light: char) -> char {
+        if self.is_unknown(x, y) {
+            '?'
+        } else if self.get(x, y) {
+            dark
+        } else {
+            light
+        }
+    }
+
+    /// 根据尺寸推算二维码版本号（尺寸不合法时返回 None）
+    pub fn version(&self) -> Option<u8> {
+        version_from_size(self.size)
+    }
+
+    /// 转换为 ASCII 字符画（深色模块为 █，浅色为空格，未知为 ?）
+    pub fn to_ascii(&self) -> String {
+        let mut output = String::with_capacity(self.size * (self.size * 2 + 1));
+        for y in 0..self.size {
+            for x in 0..self.size {
+                output.push_str(if self.is_unknown(x, y) {
+                    "??"
+                } else if self.get(x, y) {
+                    "██"
+                } else {
+                    "  "
+                });
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    /// 渲染为带静区的干净灰度图像
+    pub fn render(&self, module_px: i32, quiet_zone: usize) -> Result<Mat> {
+        let total_modules = (self.size + quiet_zone * 2) as i32;
+        let side = total_modules * module_px;
+        let mut image = Mat::new_rows_cols_with_default(side, side, CV_8UC1, Scalar::all(255.0))?;
+
+        let data = image.data_bytes_mut()?;
+        let side = side as usize;
+        let module_px = module_px as usize;
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if !self.get(x, y) {
+                    continue;
+                }
+                let top = (y + quiet_zone) * module_px;
+                let left = (x + quiet_zone) * module_px;
+                for row in top..top + module_px {
+                    data[row * side + left..row * side + left + module_px].fill(0);
+                }
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+/// 根据模块数推算版本号
+pub fn version_from_size(size: usize) -> Option<u8> {
+    if (21..=177).contains(&size) && (size - 17) % 4 == 0 {
+        Some(((size - 17) / 4) as u8)
+    } else {
+        None
+    }
+}
+
+/// 根据版本号推算模块数
+pub fn size_from_version(version: u8) -> Option<usize> {
+    if (1..=40).contains(&version) {
+        Some(17 + 4 * version as usize)
+    } else {
+        None
+    }
+}
+
+/// 采样时将模块标记为未知（擦除）的来源
+#[derive(Debug, Clone, Default)]
+pub struct ErasureSources {
+    /// 歧义阈值：模块灰度均值与 Otsu 阈值之差小于深浅两类均值差的该比例时视为未知，
+    /// 用于艺术二维码等模块内容不纯的符号 (0 表示关闭)
+    pub ambiguity_margin: f32,
+    /// 遮挡掩码：与输入图像同尺寸，非零像素表示被遮挡（如 logo、撕裂、污损区域）
+    pub occlusion_mask: Option<Mat>,
+    /// 是否把符号中心远大于单个模块的纯色区域（logo 或其底板）视为擦除
+    pub logo: bool,
+}
+
+/// 从四个角点（左上、右上、右下、左下）采样模块矩阵
+///
+/// `module_count` 为 None 时根据定位图形和时序图形自动估计模块数。
+pub fn sample_from_corners(
+    image: &Mat,
+    corners: &[(f32, f32)],
+    module_count: Option<usize>,
+) -> Result<ModuleMatrix> {
+    sample_with_erasures(image, corners, module_count, &ErasureSources::default())
+}
+
+/// 从四个角点采样模块矩阵，并按歧义程度、遮挡掩码和中心 logo 标记未知模块
+pub fn sample_with_erasures(
+    image: &Mat,
+    corners: &[(f32, f32)],
+    module_count: Option<usize>,
+    erasures: &ErasureSources,
+) -> Result<ModuleMatrix> {
+    if corners.len() != 4 {
+        return Err(QRDecodeError::invalid_input(format!(
+            "需要 4 个角点，实际提供了 {} 个",
+            corners.len()
+        )));
+    }
+
+    if let Some(size) = module_count {
+        if version_from_size(size).is_none() {
+            return Err(QRDecodeError::invalid_input(format!(
+                "无效的模块数: {} (应为 21-177 之间的 17+4n)",
+                size
+            )));
+        }
+    }
+
+    let gray = to_grayscale(image)?;
+    let size = match module_count {
+        Some(size) => size,
+        None => estimate_module_count(&gray, corners)?,
+    };
+
+    let side = size as i32 * SAMPLE_MODULE_PX;
+    let warped = warp_to_square(&gray, corners, side)?;
+    let pixels = warped.data_bytes()?;
+    let side = side as usize;
+    let module_px = SAMPLE_MODULE_PX as usize;
+
+    // 取每个模块中心区域的平均灰度，避免边缘混叠
+    let margin = module_px / 4;
+    let mut means = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let mut sum = 0u32;
+            let mut count = 0u32;
+            for row in y * module_px + margin..(y + 1) * module_px - margin {
+                for col in x * module_px + margin..(x + 1) * module_px - margin {
+                    sum += pixels[row * side + col] as u32;
+                    count += 1;
+                }
+            }
+            means.push(sum as f32 / count as f32);
+        }
+    }
+
+    let threshold = otsu_threshold(&means);
+    let mut matrix = ModuleMatrix::new(size);
+    for (i, mean) in means.iter().enumerate() {
+        matrix.modules[i] = *mean < threshold;
+    }
+
+    if erasures.ambiguity_margin > 0.0 {
+        for (i, ambiguous) in ambiguity_map(&means, threshold, erasures.ambiguity_margin).into_iter().enumerate() {
+            matrix.unknown[i] |= ambiguous;
+        }
+    }
+
+    if let Some(mask) = &erasures.occlusion_mask {
+        for (i, occluded) in occlusion_map(mask, image, corners, size)?.into_iter().enumerate() {
+            matrix.unknown[i] |= occluded;
+        }
+    }
+
+    if erasures.logo {
+        if let Some(region) = logo::find_logo_region(&matrix, logo::LOGO_MIN_MODULES) {
+            region.mark_unknown(&mut matrix);
+        }
+    }
+
+    Ok(matrix)
+}
+
+/// 根据模块灰度均值生成歧义映射
+///
+/// 先按阈值分为深浅两类，再以两类均值的中点为参照、两类均值之差归一化，
+/// 距离小于 `margin` 的模块视为歧义。
+pub fn ambiguity_map(means: &[f32], threshold: f32, margin: f32) -> Vec<bool> {
+    let class_mean = |dark: bool| {
+        let values: Vec<f32> = means.iter().cloned().filter(|&m| (m < threshold) == dark).collect();
+        if values.is_empty() {
+            threshold
+        } else {
+            values.iter().sum::<f32>() / values.len() as f32
+        }
+    };
+    let (dark_mean, light_mean) = (class_mean(true), class_mean(false));
+    let midpoint = (dark_mean + light_mean) / 2.0;
+    let contrast = (light_mean - dark_mean).max(1.0);
+
+    means
+        .iter()
+        .map(|&mean| (mean - midpoint).abs() / contrast < margin)
+        .collect()
+}
+
+/// 将遮挡掩码按相同的角点采样为模块级映射，模块中心区域过半被遮挡即视为未知
+fn occlusion_map(mask: &Mat, image: &Mat, corners: &[(f32, f32)], size: usize) -> Result<Vec<bool>> {
+    if mask.rows() != image.rows() || mask.cols() != image.cols() {
+        return Err(QRDecodeError::invalid_input(format!(
+            "遮挡掩码尺寸 {}x{} 与图像尺寸 {}x{} 不一致",
+            mask.cols(),
+            mask.rows(),
+            image.cols(),
+            image.rows()
+        )));
+    }
+
+    let gray = to_grayscale(mask)?;
+    let side = size as i32 * SAMPLE_MODULE_PX;
+    let warped = warp_to_square(&gray, corners, side)?;
+    let pixels = warped.data_bytes()?;
+    let side = side as usize;
+    let module_px = SAMPLE_MODULE_PX as usize;
+    let margin = module_px / 4;
+
+    let mut occluded = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let mut covered = 0usize;
+            let mut count = 0usize;
+            for row in y * module_px + margin..(y + 1) * module_px - margin {
+                for col in x * module_px + margin..(x + 1) * module_px - margin {
+                    covered += (pixels[row * side + col] > 0) as usize;
+                    count += 1;
+                }
+            }
+            occluded.push(covered * 2 > count);
+        }
+    }
+
+    Ok(occluded)
+}
+
+/// 用 OpenCV 检测器定位符号的四个角点（左上、右上、右下、左下），无法解码时也能定位
+pub fn locate_symbol(image: &Mat) -> Result<Option<Vec<(f32, f32)>>> {
+    let detector = QRCodeDetector::default()
+        .map_err(|e| QRDecodeError::decode_error(format!("无法创建 QRCodeDetector: {}", e)))?;
+    let mut points = Vector::<Point2f>::new();
+    if !detector.detect(image, &mut points)? || points.len() != 4 {
+        return Ok(None);
+    }
+    Ok(Some(points.iter().map(|p| (p.x, p.y)).collect()))
+}
+
+/// 按角点把指定模块画回原图，`cells` 为 (列, 行, 是否深色)
+///
+/// 模块四边形经透视变换映射到图像坐标后填充纯黑或纯白，图像尺寸和二维码位置不变。
+pub fn paint_modules(
+    image: &Mat,
+    corners: &[(f32, f32)],
+    size: usize,
+    cells: impl IntoIterator<Item = (usize, usize, bool)>,
+) -> Result<Mat> {
+    let n = size as f32;
+    let src: Vector<Point2f> = [(0.0, 0.0), (n, 0.0), (n, n), (0.0, n)]
+        .iter()
+        .map(|&(x, y)| Point2f::new(x, y))
+        .collect();
+    let dst: Vector<Point2f> = corners.iter().map(|&(x, y)| Point2f::new(x, y)).collect();
+    let transform = get_perspective_transform(&src, &dst, DECOMP_LU)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("计算透视变换失败: {}", e)))?;
+
+    let mut result = image.try_clone()?;
+    for (x, y, dark) in cells {
+        let (x, y) = (x as f32, y as f32);
+        let quad: Vector<Point2f> = [(x, y), (x + 1.0, y), (x + 1.0, y + 1.0), (x, y + 1.0)]
+            .iter()
+            .map(|&(x, y)| Point2f::new(x, y))
+            .collect();
+        let mut mapped = Vector::<Point2f>::new();
+        core::perspective_transform(&quad, &mut mapped, &transform)?;
+        let polygon: Vector<Point> = mapped.iter().map(|p| Point::new(p.x.round() as i32, p.y.round() as i32)).collect();
+
+        let colour = if dark { 0.0 } else { 255.0 };
+        fill_convex_poly(&mut result, &polygon, Scalar::all(colour), LINE_8, 0)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("绘制模块失败: {}", e)))?;
+    }
+    Ok(result)
+}
+
+/// 通过比较各版本的定位图形和时序图形匹配度估计模块数
+fn estimate_module_count(gray: &Mat, corners: &[(f32, f32)]) -> Result<usize> {
+    let warped = warp_to_square(gray, corners, ESTIMATE_SIDE_PX)?;
+    let pixels = warped.data_bytes()?;
+    let side = ESTIMATE_SIDE_PX as usize;
+    let threshold = otsu_threshold(&pixels.iter().map(|&p| p as f32).collect::<Vec<_>>());
+
+    let mut best_size = 21;
+    let mut best_score = f32::MIN;
+    for version in 1..=40u8 {
+        let size = 17 + 4 * version as usize;
+        let module_px = side as f32 / size as f32;
+        let is_dark = |x: usize, y: usize| {
+            let px = (((x as f32 + 0.5) * module_px) as usize).min(side - 1);
+            let py = (((y as f32 + 0.5) * module_px) as usize).min(side - 1);
+            (pixels[py * side + px] as f32) < threshold
+        };
+
+        let mut matches = 0usize;
+        let mut total = 0usize;
+
+        // 时序图形：第 6 行和第 6 列深浅交替
+        for i in 8..size - 8 {
+            let expected = i % 2 == 0;
+            matches += (is_dark(i, 6) == expected) as usize;
+            matches += (is_dark(6, i) == expected) as usize;
+            total += 2;
+        }
+
+        // 三个定位图形
+        for &(ox, oy) in &[(0, 0), (size - 7, 0), (0, size - 7)] {
+            for dy in 0..7 {
+                for dx in 0..7 {
+                    let ring = dx.min(dy).min(6 - dx).min(6 - dy);
+                    let expected = ring != 1;
+                    matches += (is_dark(ox + dx, oy + dy) == expected) as usize;
+                    total += 1;
+                }
+            }
+        }
+
+        let score = matches as f32 / total as f32;
+        if score > best_score + 1e-4 {
+            best_score = score;
+            best_size = size;
+        }
+    }
+
+    Ok(best_size)
+}
+
+/// 将角点围成的四边形透视矫正为正方形
+fn warp_to_square(gray: &Mat, corners: &[(f32, f32)], side: i32) -> Result<Mat> {
+    let src: Vector<Point2f> = corners.iter().map(|&(x, y)| Point2f::new(x, y)).collect();
+    let s = side as f32;
+    let dst: Vector<Point2f> = [(0.0, 0.0), (s, 0.0), (s, s), (0.0, s)]
+        .iter()
+        .map(|&(x, y)| Point2f::new(x, y))
+        .collect();
+
+    let transform = get_perspective_transform(&src, &dst, DECOMP_LU)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("计算透视变换失败: {}", e)))?;
+
+    let mut warped = Mat::default();
+    warp_perspective(
+        gray,
+        &mut warped,
+        &transform,
+        Size::new(side, side),
+        INTER_LINEAR,
+        BORDER_REPLICATE,
+        Scalar::default(),
+    )
+    .map_err(|e| QRDecodeError::image_processing_error(format!("透视矫正失败: {}", e)))?;
+
+    Ok(warped)
+}
+
+/// 转换为灰度图
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    if image.channels() == 1 {
+        return Ok(image.clone());
+    }
+
+    let mut gray = Mat::default();
+    cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, opencv::core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+    Ok(gray)
+}
+
+/// 对一组灰度值计算 Otsu 阈值
+fn otsu_threshold(values: &[f32]) -> f32 {
+    let mut histogram = [0usize; 256];
+    for &value in values {
+        histogram[value.clamp(0.0, 255.0) as usize] += 1;
+    }
+
+    let total = values.len() as f64;
+    let sum_all: f64 = histogram.iter().enumerate().map(|(i, &c)| i as f64 * c as f64).sum();
+
+    let mut best_threshold = 128.0;
+    let mut best_variance = -1.0;
+    let mut weight_bg = 0.0;
+    let mut sum_bg = 0.0;
+    for (i, &count) in histogram.iter().enumerate() {
+        weight_bg += count as f64;
+        if weight_bg == 0.0 {
+            continue;
+        }
+        let weight_fg = total - weight_bg;
+        if weight_fg == 0.0 {
+            break;
+        }
+        sum_bg += i as f64 * count as f64;
+        let mean_bg = sum_bg / weight_bg;
+        let mean_fg = (sum_all - sum_bg) / weight_fg;
+        let variance = weight_bg * weight_fg * (mean_bg - mean_fg).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = i as f32 + 0.5;
+        }
+    }
+
+    best_threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_size_conversion() {
+        assert_eq!(version_from_size(21), Some(1));
+        assert_eq!(version_from_size(177), Some(40));
+        assert_eq!(version_from_size(22), None);
+        assert_eq!(size_from_version(2), Some(25));
+        assert_eq!(size_from_version(41), None);
+    }
+
+    #[test]
+    fn test_ambiguity_map() {
+        let means = vec![10.0, 12.0, 14.0, 16.0, 240.0, 242.0, 244.0, 246.0, 125.0];
+        let threshold = otsu_threshold(&means);
+        let map = ambiguity_map(&means, threshold, 0.1);
+        assert_eq!(map.iter().filter(|&&a| a).count(), 1);
+        assert!(map[8]);
+        assert!(ambiguity_map(&means, threshold, 0.0).iter().all(|&a| !a));
+    }
+
+    #[test]
+    fn test_otsu_threshold() {
+        let values = vec![10.0, 12.0, 15.0, 200.0, 210.0, 220.0];
+        let threshold = otsu_threshold(&values);
+        assert!(threshold > 15.0 && threshold < 200.0);
+    }
+
+    #[test]
+    fn test_matrix_serialization_roundtrip() {
+        let mut matrix = ModuleMatrix::new(21);
+        matrix.set(0, 0, true);
+        matrix.set(20, 3, true);
+
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert!(json.contains("100000000000000000000"));
+
+        let restored: ModuleMatrix = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, matrix);
+    }
+}
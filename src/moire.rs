@@ -0,0 +1,253 @@
+//! 摩尔纹抑制模块
+//!
+//! 用手机翻拍显示器上的二维码时，屏幕像素网格与相机传感器网格互相干涉，形成覆盖全图
+//! 的摩尔纹。摩尔纹在频域中表现为少数几个孤立的尖峰，而二维码模块的能量分布较宽。
+//! 本模块在频域中处理：陷波滤波只去掉这些周期性尖峰，低通滤波则直接去掉屏幕像素
+//! 网格所在的高频部分。
+
+use opencv::{
+    core::{self, Mat, Scalar, Vector, BORDER_CONSTANT, CV_32F, CV_8U, DFT_COMPLEX_OUTPUT, DFT_INVERSE, DFT_REAL_OUTPUT, DFT_SCALE},
+    imgproc::{cvt_color, COLOR_BGR2GRAY, COLOR_GRAY2BGR},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+
+/// 频谱尖峰需要超过背景频谱中位数的倍数
+const PEAK_FACTOR: f32 = 12.0;
+
+/// 最多去除的尖峰数（含共轭对称的一半）
+const MAX_PEAKS: usize = 32;
+
+/// 陷波半径（频域采样点）
+const NOTCH_RADIUS: f64 = 3.0;
+
+/// 摩尔纹滤波方式，频率单位为周期/像素（0.0-0.5）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MoireFilter {
+    /// 陷波：去掉高于 `min_frequency` 的孤立频谱尖峰，低于该频率的部分保留给二维码模块
+    Notch { min_frequency: f64 },
+    /// 高斯低通：`cutoff` 处的频率响应衰减到 1/e
+    LowPass { cutoff: f64 },
+}
+
+impl MoireFilter {
+    /// 获取滤波方式的描述
+    pub fn description(&self) -> String {
+        match self {
+            MoireFilter::Notch { min_frequency } => format!("陷波 (≥{:.2} 周期/像素)", min_frequency),
+            MoireFilter::LowPass { cutoff } => format!("低通 (截止 {:.2} 周期/像素)", cutoff),
+        }
+    }
+}
+
+/// 抑制摩尔纹，输入为彩色图像时返回转换回 BGR 的结果
+pub fn suppress_moire(image: &Mat, filter: MoireFilter) -> Result<Mat> {
+    let gray = to_grayscale(image)?;
+    let (rows, cols) = (gray.rows(), gray.cols());
+
+    // 扩展到 DFT 的最优尺寸
+    let dft_rows = core::get_optimal_dft_size(rows)?;
+    let dft_cols = core::get_optimal_dft_size(cols)?;
+    let mut padded = Mat::default();
+    core::copy_make_border(&gray, &mut padded, 0, dft_rows - rows, 0, dft_cols - cols, BORDER_CONSTANT, Scalar::all(255.0))?;
+    let mut float = Mat::default();
+    padded.convert_to(&mut float, CV_32F, 1.0, 0.0)?;
+
+    let mut spectrum = Mat::default();
+    core::dft(&float, &mut spectrum, DFT_COMPLEX_OUTPUT, 0)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("傅里叶变换失败: {}", e)))?;
+
+    let (rows_u, cols_u) = (dft_rows as usize, dft_cols as usize);
+    let mask = match filter {
+        MoireFilter::Notch { min_frequency } => {
+            let magnitudes = magnitude(&spectrum)?;
+            let peaks = find_spectral_peaks(&magnitudes, rows_u, cols_u, min_frequency);
+            if peaks.is_empty() {
+                return Ok(image.clone());
+            }
+            notch_mask(rows_u, cols_u, &peaks, NOTCH_RADIUS)
+        }
+        MoireFilter::LowPass { cutoff } => low_pass_mask(rows_u, cols_u, cutoff),
+    };
+
+    // 复数频谱的实部和虚部乘以同一个掩码
+    let mask = Mat::new_rows_cols_with_data(dft_rows, dft_cols, &mask)?.try_clone()?;
+    let mut planes = Vector::<Mat>::new();
+    planes.push(mask.clone());
+    planes.push(mask);
+    let mut complex_mask = Mat::default();
+    core::merge(&planes, &mut complex_mask)?;
+    let mut filtered = Mat::default();
+    core::multiply(&spectrum, &complex_mask, &mut filtered, 1.0, -1)?;
+
+    let mut restored = Mat::default();
+    core::dft(&filtered, &mut restored, DFT_INVERSE | DFT_SCALE | DFT_REAL_OUTPUT, 0)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("傅里叶逆变换失败: {}", e)))?;
+
+    let cropped = Mat::roi(&restored, core::Rect::new(0, 0, cols, rows))?.try_clone()?;
+    let mut result = Mat::default();
+    cropped.convert_to(&mut result, CV_8U, 1.0, 0.0)?;
+
+    if image.channels() == 3 {
+        let mut color = Mat::default();
+        cvt_color(&result, &mut color, COLOR_GRAY2BGR, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转彩色失败: {}", e)))?;
+        Ok(color)
+    } else {
+        Ok(result)
+    }
+}
+
+/// 频域采样点 (y, x) 对应的频率（周期/像素），频谱未做中心化
+fn frequency(y: usize, x: usize, rows: usize, cols: usize) -> f64 {
+    let fy = y.min(rows - y) as f64 / rows as f64;
+    let fx = x.min(cols - x) as f64 / cols as f64;
+    (fy * fy + fx * fx).sqrt()
+}
+
+/// 在未中心化的幅度谱中查找周期性尖峰
+///
+/// 只考虑频率不低于 `min_frequency` 的区域；尖峰须为 3x3 邻域内的局部最大值，
+/// 且超过该区域幅度中位数的 `PEAK_FACTOR` 倍。按幅度从大到小最多返回 `MAX_PEAKS` 个。
+pub fn find_spectral_peaks(magnitudes: &[f32], rows: usize, cols: usize, min_frequency: f64) -> Vec<(usize, usize)> {
+    let mut band: Vec<f32> = Vec::new();
+    for y in 0..rows {
+        for x in 0..cols {
+            if frequency(y, x, rows, cols) >= min_frequency {
+                band.push(magnitudes[y * cols + x]);
+            }
+        }
+    }
+    if band.is_empty() {
+        return Vec::new();
+    }
+    let middle = band.len() / 2;
+    let median = *band.select_nth_unstable_by(middle, f32::total_cmp).1;
+    let threshold = median.max(f32::EPSILON) * PEAK_FACTOR;
+
+    let mut peaks = Vec::new();
+    for y in 0..rows {
+        for x in 0..cols {
+            let value = magnitudes[y * cols + x];
+            if value < threshold || frequency(y, x, rows, cols) < min_frequency {
+                continue;
+            }
+            let is_maximum = (-1isize..=1).all(|dy| {
+                (-1isize..=1).all(|dx| {
+                    let ny = (y as isize + dy).rem_euclid(rows as isize) as usize;
+                    let nx = (x as isize + dx).rem_euclid(cols as isize) as usize;
+                    magnitudes[ny * cols + nx] <= value
+                })
+            });
+            if is_maximum {
+                peaks.push((value, y, x));
+            }
+        }
+    }
+
+    peaks.sort_by(|a, b| b.0.total_cmp(&a.0));
+    peaks.into_iter().take(MAX_PEAKS).map(|(_, y, x)| (y, x)).collect()
+}
+
+/// 生成陷波掩码：尖峰周围 `radius` 内按高斯形状衰减到 0，其余为 1
+pub fn notch_mask(rows: usize, cols: usize, peaks: &[(usize, usize)], radius: f64) -> Vec<f32> {
+    let mut mask = vec![1.0f32; rows * cols];
+    let reach = (radius * 3.0).ceil() as isize;
+    for &(py, px) in peaks {
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let y = (py as isize + dy).rem_euclid(rows as isize) as usize;
+                let x = (px as isize + dx).rem_euclid(cols as isize) as usize;
+                let distance_sq = (dy * dy + dx * dx) as f64;
+                let attenuation = 1.0 - (-distance_sq / (2.0 * radius * radius)).exp();
+                let cell = &mut mask[y * cols + x];
+                *cell = cell.min(attenuation as f32);
+            }
+        }
+    }
+    mask
+}
+
+/// 生成高斯低通掩码
+pub fn low_pass_mask(rows: usize, cols: usize, cutoff: f64) -> Vec<f32> {
+    let cutoff = cutoff.max(f64::EPSILON);
+    let mut mask = Vec::with_capacity(rows * cols);
+    for y in 0..rows {
+        for x in 0..cols {
+            let ratio = frequency(y, x, rows, cols) / cutoff;
+            mask.push((-ratio * ratio).exp() as f32);
+        }
+    }
+    mask
+}
+
+/// 复数频谱的幅度
+fn magnitude(spectrum: &Mat) -> Result<Vec<f32>> {
+    let mut planes = Vector::<Mat>::new();
+    core::split(spectrum, &mut planes)?;
+    let mut magnitude = Mat::default();
+    core::magnitude(&planes.get(0)?, &planes.get(1)?, &mut magnitude)?;
+    Ok(magnitude.data_typed::<f32>()?.to_vec())
+}
+
+fn to_grayscale(image: &Mat) -> Result<Mat> {
+    if image.channels() == 1 {
+        return Ok(image.clone());
+    }
+
+    let mut gray = Mat::default();
+    cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+    Ok(gray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opencv::core::CV_8UC1;
+
+    #[test]
+    fn test_find_peaks_and_masks() {
+        let (rows, cols) = (32, 32);
+        let mut magnitudes = vec![1.0f32; rows * cols];
+        magnitudes[0] = 1000.0; // 直流分量
+        magnitudes[2 * cols + 3] = 200.0; // 低频，属于二维码模块
+        magnitudes[12 * cols + 12] = 100.0; // 高频尖峰
+        magnitudes[20 * cols + 20] = 100.0; // 共轭对称的尖峰
+
+        let peaks = find_spectral_peaks(&magnitudes, rows, cols, 0.2);
+        assert_eq!(peaks.len(), 2);
+        assert!(peaks.contains(&(12, 12)) && peaks.contains(&(20, 20)));
+
+        let mask = notch_mask(rows, cols, &peaks, NOTCH_RADIUS);
+        assert_eq!(mask[12 * cols + 12], 0.0);
+        assert_eq!(mask[2 * cols + 3], 1.0);
+
+        let low_pass = low_pass_mask(rows, cols, 0.1);
+        assert_eq!(low_pass[0], 1.0);
+        assert!(low_pass[16 * cols + 16] < 0.01);
+    }
+
+    #[test]
+    fn test_notch_removes_screen_pattern() {
+        // 16 像素的棋盘格上叠加周期 3 像素的条纹
+        let mut image = Mat::new_rows_cols_with_default(128, 128, CV_8UC1, Scalar::all(0.0)).unwrap();
+        let data = image.data_bytes_mut().unwrap();
+        for y in 0..128 {
+            for x in 0..128 {
+                let base = if (x / 16 + y / 16) % 2 == 0 { 60.0 } else { 190.0 };
+                let stripe = 40.0 * (2.0 * std::f64::consts::PI * x as f64 / 3.0).cos();
+                data[y * 128 + x] = (base + stripe) as u8;
+            }
+        }
+
+        let filtered = suppress_moire(&image, MoireFilter::Notch { min_frequency: 0.2 }).unwrap();
+        let (before, after) = (image.data_bytes().unwrap(), filtered.data_bytes().unwrap());
+        // 同一浅色方块中部相邻像素的起伏明显变小
+        let row = 8 * 128 + 20;
+        let ripple = |d: &[u8]| (0..6).map(|i| (d[row + i] as i32 - d[row + i + 1] as i32).abs()).max().unwrap();
+        assert!(ripple(after) < ripple(before) / 2, "{} -> {}", ripple(before), ripple(after));
+    }
+}
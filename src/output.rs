@@ -9,6 +9,9 @@ use std::io::{self, Write};
 use std::path::Path;
 
 use crate::error::{QRDecodeError, Result};
+use crate::inspection;
+use crate::module_matrix::ModuleMatrix;
+use crate::quality::QualityReport;
 use crate::types::{OutputFormat, ProcessingConfig, QRCodeResult};
 
 /// 输出格式化器
@@ -48,6 +51,47 @@ impl OutputFormatter {
         Ok(())
     }
     
+    /// 输出质量评估报告
+    ///
+    /// 有解码结果时报告已随每个结果输出；没有结果时由本方法单独输出，JSON 和详细格式
+    /// 下测量值不会因解码失败而丢失。文本和 CSV 格式不输出。
+    pub fn output_quality_report(&self, report: &QualityReport) -> Result<()> {
+        let Some(formatted_output) = self.format_quality_report(report)? else {
+            return Ok(());
+        };
+        
+        if let Some(output_path) = &self.config.output_path {
+            self.write_to_file(&formatted_output, output_path)?;
+        } else {
+            self.write_to_stdout(&formatted_output)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// 按输出格式格式化没有解码结果时的质量评估报告
+    fn format_quality_report(&self, report: &QualityReport) -> Result<Option<String>> {
+        let output = match self.config.output_format {
+            OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                "results": [],
+                "quality": report,
+            }))?,
+            OutputFormat::Verbose => {
+                let mut output = String::new();
+                output.push_str("二维码解码结果报告\n");
+                output.push_str(&format!("生成时间: {}\n", Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
+                output.push_str("检测到的二维码数量: 0\n\n");
+                output.push_str(&format!("图像质量: {}\n", report.summary()));
+                output.push_str(&format!("质量问题: {}\n", report.issue_description()));
+                output.push_str(&format!("处理路径: {}\n", report.recommendation.description()));
+                output
+            }
+            OutputFormat::Text | OutputFormat::Csv => return Ok(None),
+        };
+        
+        Ok(Some(output))
+    }
+    
     /// 格式化为纯文本
     fn format_as_text(&self, results: &[QRCodeResult]) -> Result<String> {
         let mut output = String::new();
@@ -57,6 +101,9 @@ impl OutputFormatter {
                 output.push_str(&format!("=== 二维码 {} ===\n", i + 1));
             }
             
+            if let Some(layer) = &result.layer {
+                output.push_str(&format!("[{}] ", layer));
+            }
             output.push_str(&result.content);
             
             if self.config.show_position {
@@ -69,6 +116,11 @@ impl OutputFormatter {
                 ));
             }
             
+            if let Some(report) = &result.inspection {
+                output.push('\n');
+                output.push_str(&inspection::format_report(report));
+            }
+            
             if results.len() > 1 {
                 output.push('\n');
             }
@@ -143,6 +195,17 @@ impl OutputFormatter {
         for (i, result) in results.iter().enumerate() {
             output.push_str(&format!("┌─ 二维码 #{} ─────────────────────────────────────┐\n", i + 1));
             output.push_str(&format!("│ 类型: {}\n", result.qr_type));
+            if let Some(layer) = &result.layer {
+                output.push_str(&format!("│ 图层: {}\n", layer));
+            }
+            if let Some(recipe) = &result.recipe {
+                output.push_str(&format!("│ 配方: {}\n", recipe.description()));
+            }
+            if let Some(quality) = &result.quality {
+                output.push_str(&format!("│ 图像质量: {}\n", quality.summary()));
+                output.push_str(&format!("│ 质量问题: {}\n", quality.issue_description()));
+                output.push_str(&format!("│ 处理路径: {}\n", quality.recommendation.description()));
+            }
             output.push_str(&format!("│ 置信度: {:.3}\n", result.confidence));
             output.push_str(&format!("│ 解码时间: {}\n", result.timestamp.format("%Y-%m-%d %H:%M:%S UTC")));
             
@@ -169,6 +232,32 @@ impl OutputFormatter {
                 output.push_str(&format!("│ 原始字节长度: {} 字节\n", raw_bytes.len()));
             }
             
+            if let Some(correction) = &result.correction {
+                output.push_str(&format!(
+                    "│ 纠错: 版本 {}-{}, 掩码 {}, {} 块 x {} 纠错码字\n",
+                    correction.version, correction.ec_level, correction.mask,
+                    correction.blocks, correction.ecc_per_block
+                ));
+                output.push_str(&format!(
+                    "│   未知模块 {} 个，纠正擦除 {} 个、错误 {} 个码字\n",
+                    correction.unknown_modules, correction.erasures_corrected, correction.errors_corrected
+                ));
+            }
+            
+            if let Some(report) = &result.inspection {
+                output.push_str("│ 隐藏数据检查:\n");
+                for line in inspection::format_report(report).lines() {
+                    output.push_str(&format!("│   {}\n", line));
+                }
+            }
+            
+            if let Some(matrix) = &result.module_matrix {
+                output.push_str(&format!("│ 模块矩阵: {}x{}\n", matrix.size, matrix.size));
+                for line in matrix.to_ascii().lines() {
+                    output.push_str(&format!("│   {}\n", line));
+                }
+            }
+            
             // 内容预览
             let content_preview = if result.content.len() > 100 {
                 format!("{}...", &result.content[..97])
@@ -263,6 +352,12 @@ impl OutputFormatter {
         Ok(())
     }
     
+    /// 输出采样得到的模块矩阵（写入标准错误，不影响结果输出）
+    pub fn output_module_matrix(&self, matrix: &ModuleMatrix) {
+        eprintln!("🧩 模块矩阵 {}x{}:", matrix.size, matrix.size);
+        eprint!("{}", matrix.to_ascii());
+    }
+    
     /// 输出错误信息
     pub fn output_error(&self, error: &QRDecodeError) {
         eprintln!("❌ 错误: {}", error);
@@ -333,6 +428,7 @@ mod tests {
             min_confidence: 0.5,
             save_processed: false,
             processed_output_path: None,
+            ..ProcessingConfig::default()
         }
     }
     
@@ -362,6 +458,28 @@ mod tests {
         assert!(output.contains("confidence"));
     }
     
+    #[test]
+    fn test_quality_report_without_results() {
+        let metrics = crate::quality::measure_gray(&[128; 64], 8, 8, 0.0, None, None);
+        let issues = crate::quality::diagnose(&metrics);
+        let recommendation = crate::quality::recommend(&issues);
+        let report = QualityReport { metrics, issues, recommendation };
+        
+        let mut config = create_test_config();
+        assert_eq!(OutputFormatter::new(&config).format_quality_report(&report).unwrap(), None);
+        
+        config.output_format = OutputFormat::Json;
+        let output = OutputFormatter::new(&config).format_quality_report(&report).unwrap().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["results"], serde_json::json!([]));
+        assert_eq!(value["quality"]["metrics"]["brightness"], 128.0);
+        
+        config.output_format = OutputFormat::Verbose;
+        let output = OutputFormatter::new(&config).format_quality_report(&report).unwrap().unwrap();
+        assert!(output.contains("检测到的二维码数量: 0"));
+        assert!(output.contains(&report.summary()));
+    }
+    
     #[test]
     fn test_csv_field_escaping() {
         let config = create_test_config();
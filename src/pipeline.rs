@@ -0,0 +1,368 @@
+//! 声明式变换流水线模块
+//!
+//! 增强解码默认按代码中固定的变换序列逐个尝试。本模块允许用 JSON 文件描述变换序列：
+//! 文件中可以定义多个命名配置（profile），每个配置是一组按顺序尝试的步骤，步骤可以是
+//! 单个变换，也可以是依次叠加的变换链。文件在加载时完成校验，出错时指出配置名和步骤序号。
+//!
+//! ```json
+//! {
+//!   "default_profile": "screen-photo",
+//!   "profiles": {
+//!     "screen-photo": [
+//!       "Original",
+//!       { "MoireSuppression": { "Notch": { "min_frequency": 0.15 } } },
+//!       { "chain": [{ "MoireSuppression": { "LowPass": { "cutoff": 0.2 } } }, { "Clahe": [2.0, 8] }] }
+//!     ]
+//!   }
+//! }
+//! ```
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::binarization::BinarizationMethod;
+use crate::deblur::{BlurKernel, DeconvolutionMethod};
+use crate::enhanced_processor::TransformType;
+use crate::error::{QRDecodeError, Result};
+use crate::glare::DEFAULT_GLARE_THRESHOLD;
+use crate::halftone::HalftoneRepair;
+use crate::illumination::BackgroundEstimate;
+use crate::moire::MoireFilter;
+
+/// 内置配置名
+pub const BUILTIN_PROFILES: [&str; 3] = ["screen-photo", "art-qr", "print-scan"];
+
+/// 流水线中的一个步骤
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum PipelineStep {
+    /// 单个变换
+    Single(TransformType),
+    /// 依次叠加的变换链，以最后的结果解码
+    Chain { chain: Vec<TransformType> },
+}
+
+impl PipelineStep {
+    /// 步骤包含的变换
+    pub fn transforms(&self) -> &[TransformType] {
+        match self {
+            PipelineStep::Single(transform) => std::slice::from_ref(transform),
+            PipelineStep::Chain { chain } => chain,
+        }
+    }
+
+    /// 获取步骤的描述
+    pub fn description(&self) -> String {
+        self.transforms().iter().map(TransformType::description).collect::<Vec<_>>().join(" → ")
+    }
+
+    /// 从 JSON 值解析步骤：变换本身，或 `{"chain": [...]}`
+    pub fn from_value(value: &Value) -> std::result::Result<Self, String> {
+        if let Some(object) = value.as_object() {
+            if let Some(chain) = object.get("chain") {
+                if object.len() != 1 {
+                    return Err("变换链只能包含 \"chain\" 字段".to_string());
+                }
+                let items = chain.as_array().ok_or("\"chain\" 必须是变换数组")?;
+                if items.is_empty() {
+                    return Err("变换链不能为空".to_string());
+                }
+                let chain = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| parse_transform(item).map_err(|e| format!("链中第 {} 个变换: {}", i + 1, e)))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                return Ok(PipelineStep::Chain { chain });
+            }
+        }
+        parse_transform(value).map(PipelineStep::Single)
+    }
+}
+
+/// 流水线配置文件
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PipelineFile {
+    /// 未指定 `--profile` 时使用的配置
+    pub default_profile: Option<String>,
+    /// 命名配置
+    pub profiles: BTreeMap<String, Vec<PipelineStep>>,
+}
+
+impl PipelineFile {
+    /// 从文件加载并校验
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            QRDecodeError::invalid_input(format!("无法读取流水线文件 {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text).map_err(|e| QRDecodeError::invalid_input(format!("流水线文件 {} 无效: {}", path.display(), e)))
+    }
+
+    /// 解析并校验 JSON 文本
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let root: Value = serde_json::from_str(text).map_err(|e| format!("JSON 语法错误: {}", e))?;
+        let root = root.as_object().ok_or("顶层必须是对象")?;
+        if let Some(key) = root.keys().find(|key| !matches!(key.as_str(), "default_profile" | "profiles")) {
+            return Err(format!("未知字段 \"{}\" (可用字段: default_profile, profiles)", key));
+        }
+
+        let default_profile = match root.get("default_profile") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(name)) => Some(name.clone()),
+            Some(_) => return Err("\"default_profile\" 必须是字符串".to_string()),
+        };
+        let raw_profiles = root
+            .get("profiles")
+            .ok_or("缺少 \"profiles\" 字段")?
+            .as_object()
+            .ok_or("\"profiles\" 必须是对象")?;
+        if raw_profiles.is_empty() {
+            return Err("\"profiles\" 中没有任何配置".to_string());
+        }
+
+        let mut profiles = BTreeMap::new();
+        for (name, steps) in raw_profiles {
+            let steps = steps.as_array().ok_or_else(|| format!("配置 \"{}\" 必须是步骤数组", name))?;
+            if steps.is_empty() {
+                return Err(format!("配置 \"{}\" 没有任何步骤", name));
+            }
+            let steps = steps
+                .iter()
+                .enumerate()
+                .map(|(i, step)| {
+                    let step = PipelineStep::from_value(step)
+                        .and_then(|step| step.transforms().iter().try_for_each(validate_transform).map(|_| step));
+                    step.map_err(|e| format!("配置 \"{}\" 第 {} 步: {}", name, i + 1, e))
+                })
+                .collect::<std::result::Result<Vec<_>, String>>()?;
+            profiles.insert(name.clone(), steps);
+        }
+
+        if let Some(name) = &default_profile {
+            if !profiles.contains_key(name) {
+                return Err(format!("默认配置 \"{}\" 不存在 (可用配置: {})", name, join_names(profiles.keys())));
+            }
+        }
+
+        Ok(Self { default_profile, profiles })
+    }
+}
+
+/// 按 `--pipeline` 和 `--profile` 确定增强解码使用的步骤，两者都未指定时返回 None（使用内置序列）
+///
+/// 配置名先在文件中查找，再查找内置配置。文件未指定 `--profile` 时使用其
+/// `default_profile`，文件只有一个配置时直接使用该配置。`window` 为内置配置中局部二值化的窗口。
+pub fn resolve_pipeline(path: Option<&Path>, profile: Option<&str>, window: i32) -> Result<Option<Vec<PipelineStep>>> {
+    let file = match path {
+        Some(path) => Some(PipelineFile::load(path)?),
+        None => None,
+    };
+
+    let name = match (profile, &file) {
+        (Some(name), _) => name.to_string(),
+        (None, None) => return Ok(None),
+        (None, Some(file)) => match (&file.default_profile, file.profiles.len()) {
+            (Some(name), _) => name.clone(),
+            (None, 1) => file.profiles.keys().next().cloned().unwrap_or_default(),
+            (None, _) => {
+                return Err(QRDecodeError::invalid_input(format!(
+                    "流水线文件包含多个配置且未设置 default_profile，请用 --profile 指定: {}",
+                    join_names(file.profiles.keys())
+                )))
+            }
+        },
+    };
+
+    if let Some(steps) = file.as_ref().and_then(|file| file.profiles.get(&name)) {
+        return Ok(Some(steps.clone()));
+    }
+    builtin_profile(&name, window).map(Some).ok_or_else(|| {
+        let mut available: Vec<String> = file.iter().flat_map(|file| file.profiles.keys().cloned()).collect();
+        available.extend(BUILTIN_PROFILES.iter().map(|name| name.to_string()));
+        QRDecodeError::invalid_input(format!("未知的流水线配置 \"{}\" (可用配置: {})", name, join_names(available.iter())))
+    })
+}
+
+/// 内置配置，局部二值化使用 `window` 像素的窗口
+pub fn builtin_profile(name: &str, window: i32) -> Option<Vec<PipelineStep>> {
+    use PipelineStep::{Chain, Single};
+    use TransformType::*;
+
+    let sauvola = Binarize(BinarizationMethod::Sauvola { window, k: 0.34 });
+    let wiener = DeconvolutionMethod::Wiener { snr: 100.0 };
+    let steps = match name {
+        // 翻拍屏幕：摩尔纹、屏幕反光和轻微的运动模糊
+        "screen-photo" => vec![
+            Single(Original),
+            Single(MoireSuppression(MoireFilter::Notch { min_frequency: 0.15 })),
+            Single(MoireSuppression(MoireFilter::LowPass { cutoff: 0.2 })),
+            Chain { chain: vec![MoireSuppression(MoireFilter::Notch { min_frequency: 0.15 }), Clahe(2.0, 8)] },
+            Chain { chain: vec![MoireSuppression(MoireFilter::LowPass { cutoff: 0.1 }), Sharpen] },
+            Single(GlareRemoval(DEFAULT_GLARE_THRESHOLD)),
+            Single(DeblurEstimated(wiener)),
+            Single(GaussianBlur(5)),
+        ],
+        // 艺术二维码：中心 logo、点状模块和非黑白配色
+        "art-qr" => vec![
+            Single(Original),
+            Single(LogoInpainting),
+            Single(Halftone(HalftoneRepair::MorphClose)),
+            Single(Halftone(HalftoneRepair::CentreSampling)),
+            Chain { chain: vec![LogoInpainting, Halftone(HalftoneRepair::CentreSampling)] },
+            Single(Binarize(BinarizationMethod::MultiOtsu { classes: 3, level: 0 })),
+            Single(Binarize(BinarizationMethod::MultiOtsu { classes: 3, level: 1 })),
+            Chain { chain: vec![Clahe(3.0, 8), sauvola] },
+        ],
+        // 打印后扫描或拍照：光照不均、覆膜反光和散焦
+        "print-scan" => vec![
+            Single(Original),
+            Single(IlluminationCorrection(BackgroundEstimate::MorphClose(0))),
+            Chain { chain: vec![IlluminationCorrection(BackgroundEstimate::MorphClose(0)), sauvola] },
+            Single(GlareRemoval(DEFAULT_GLARE_THRESHOLD)),
+            Single(Deblur(BlurKernel::Defocus { radius: 2.0 }, wiener)),
+            Single(DeblurEstimated(wiener)),
+            Single(MedianBlur(3)),
+            Single(MorphClose),
+        ],
+        _ => return None,
+    };
+    Some(steps)
+}
+
+/// 校验变换参数
+pub fn validate_transform(transform: &TransformType) -> std::result::Result<(), String> {
+    let check = |ok: bool, message: &str| if ok { Ok(()) } else { Err(format!("{}: {}", transform.description(), message)) };
+    let odd_kernel = |k: i32| (3..=31).contains(&k) && k % 2 == 1;
+
+    match *transform {
+        TransformType::Brightness(value) => check((-255..=255).contains(&value), "亮度必须在 -255 到 255 之间"),
+        TransformType::Contrast(value) | TransformType::Gamma(value) => check(value.is_finite() && value > 0.0, "系数必须大于 0"),
+        TransformType::BrightnessContrast(brightness, contrast) => check(
+            (-255..=255).contains(&brightness) && contrast.is_finite() && contrast > 0.0,
+            "亮度必须在 -255 到 255 之间，对比度必须大于 0",
+        ),
+        TransformType::GaussianBlur(kernel) | TransformType::MedianBlur(kernel) => check(odd_kernel(kernel), "核大小必须是 3 到 31 之间的奇数"),
+        TransformType::Rotate(degrees) => check((-180..=180).contains(&degrees), "旋转角度必须在 -180 到 180 之间"),
+        TransformType::Scale(factor) => check((0.1..=4.0).contains(&factor), "缩放比例必须在 0.1 到 4.0 之间"),
+        TransformType::Clahe(clip, tiles) => check(clip > 0.0 && (1..=64).contains(&tiles), "限幅必须大于 0，网格大小必须在 1 到 64 之间"),
+        TransformType::LocalToneMapping(strength) => check((0.0..=1.0).contains(&strength), "强度必须在 0.0 到 1.0 之间"),
+        TransformType::Binarize(method) => match method {
+            BinarizationMethod::Otsu => Ok(()),
+            BinarizationMethod::Niblack { window, .. }
+            | BinarizationMethod::Sauvola { window, .. }
+            | BinarizationMethod::Wolf { window, .. } => check((3..=255).contains(&window), "窗口必须在 3 到 255 像素之间"),
+            BinarizationMethod::MultiOtsu { classes, level } => {
+                check((2..=8).contains(&classes) && level < classes - 1, "类数必须在 2 到 8 之间，分界序号必须小于类数减一")
+            }
+        },
+        TransformType::IlluminationCorrection(estimate) => {
+            let size = match estimate {
+                BackgroundEstimate::MorphClose(size) | BackgroundEstimate::BlackHat(size) | BackgroundEstimate::Blur(size) => size,
+            };
+            check(size >= 0, "核大小不能为负 (0 表示自动)")
+        }
+        TransformType::MoireSuppression(filter) => match filter {
+            MoireFilter::Notch { min_frequency: f } | MoireFilter::LowPass { cutoff: f } => {
+                check(f > 0.0 && f <= 0.5, "频率必须在 0 到 0.5 周期/像素之间")
+            }
+        },
+        TransformType::Deblur(kernel, method) => {
+            let kernel_ok = match kernel {
+                BlurKernel::Motion { length, angle } => length > 0.0 && length <= 100.0 && angle.is_finite(),
+                BlurKernel::Defocus { radius } => radius > 0.0 && radius <= 50.0,
+            };
+            check(kernel_ok, "模糊核长度必须在 0 到 100 像素之间，散焦半径必须在 0 到 50 像素之间")?;
+            validate_deconvolution(transform, method)
+        }
+        TransformType::DeblurEstimated(method) => validate_deconvolution(transform, method),
+        _ => Ok(()),
+    }
+}
+
+fn validate_deconvolution(transform: &TransformType, method: DeconvolutionMethod) -> std::result::Result<(), String> {
+    let ok = match method {
+        DeconvolutionMethod::Wiener { snr } => snr > 0.0 && snr.is_finite(),
+        DeconvolutionMethod::RichardsonLucy { iterations } => (1..=200).contains(&iterations),
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("{}: 信噪比必须大于 0，迭代次数必须在 1 到 200 之间", transform.description()))
+    }
+}
+
+/// 把 JSON 值解析为变换，错误信息保留 serde 给出的期望类型或可选变体
+fn parse_transform(value: &Value) -> std::result::Result<TransformType, String> {
+    serde_json::from_value(value.clone()).map_err(|e| format!("无法识别的变换 {}: {}", value, e))
+}
+
+fn join_names<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    names.map(String::as_str).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binarization::DEFAULT_BINARIZATION_WINDOW;
+
+    #[test]
+    fn test_parse_pipeline_file() {
+        let file = PipelineFile::parse(
+            r#"{
+                "default_profile": "fast",
+                "profiles": {
+                    "fast": ["Original", {"Brightness": 20}, {"chain": [{"Clahe": [2.0, 8]}, "Sharpen"]}],
+                    "art": [{"Halftone": "CentreSampling"}]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(file.default_profile.as_deref(), Some("fast"));
+        let fast = &file.profiles["fast"];
+        assert_eq!(fast[0], PipelineStep::Single(TransformType::Original));
+        assert_eq!(fast[1], PipelineStep::Single(TransformType::Brightness(20)));
+        assert_eq!(fast[2].transforms(), &[TransformType::Clahe(2.0, 8), TransformType::Sharpen]);
+        assert_eq!(file.profiles["art"][0], PipelineStep::Single(TransformType::Halftone(HalftoneRepair::CentreSampling)));
+    }
+
+    #[test]
+    fn test_invalid_pipeline_reports_location() {
+        let error = PipelineFile::parse(r#"{"profiles": {"p": ["Original", {"GaussianBlur": 4}]}}"#).unwrap_err();
+        assert!(error.contains("\"p\" 第 2 步") && error.contains("奇数"), "{}", error);
+
+        let error = PipelineFile::parse(r#"{"profiles": {"p": [{"chain": ["Original", "Blurry"]}]}}"#).unwrap_err();
+        assert!(error.contains("链中第 2 个变换") && error.contains("Blurry"), "{}", error);
+
+        let error = PipelineFile::parse(r#"{"default_profile": "q", "profiles": {"p": ["Original"]}}"#).unwrap_err();
+        assert!(error.contains("\"q\" 不存在"), "{}", error);
+
+        assert!(PipelineFile::parse(r#"{"profiles": {"p": []}}"#).is_err());
+        assert!(PipelineFile::parse(r#"{"profile": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_builtin_profiles_are_valid() {
+        for name in BUILTIN_PROFILES {
+            let steps = resolve_pipeline(None, Some(name), DEFAULT_BINARIZATION_WINDOW).unwrap().unwrap();
+            for step in &steps {
+                assert!(step.transforms().iter().all(|t| validate_transform(t).is_ok()), "{}: {}", name, step.description());
+            }
+        }
+        assert!(resolve_pipeline(None, None, DEFAULT_BINARIZATION_WINDOW).unwrap().is_none());
+        assert!(resolve_pipeline(None, Some("nope"), DEFAULT_BINARIZATION_WINDOW).is_err());
+
+        // 内置配置使用 --binarize-window 指定的窗口
+        let steps = builtin_profile("print-scan", 41).unwrap();
+        let windows: Vec<i32> = steps
+            .iter()
+            .flat_map(|step| step.transforms())
+            .filter_map(|t| match t {
+                TransformType::Binarize(BinarizationMethod::Sauvola { window, .. }) => Some(*window),
+                _ => None,
+            })
+            .collect();
+        assert!(!windows.is_empty() && windows.iter().all(|&w| w == 41), "{:?}", windows);
+    }
+}
@@ -0,0 +1,233 @@
+//! 多尺度金字塔检测模块
+//!
+//! 大幅扫描件中的小二维码在原始分辨率下每个模块只有一两个像素，检测器会直接漏掉；
+//! 整幅放大又会占用大量内存。本模块从粗到细逐层扫描：最粗一层把整幅图缩小到一个图块
+//! 以内，用于检测大码；之后按 2 的幂逐层放大到最大倍数，超出图块大小的层切成相互
+//! 重叠的图块，每个图块从原图裁剪后再缩放，不需要生成整层图像。各层的检测结果映射回
+//! 原图坐标后合并去重。
+
+use opencv::{
+    core::{Mat, Rect, Size},
+    imgproc::{self, INTER_AREA, INTER_CUBIC},
+    prelude::*,
+};
+
+use crate::error::{QRDecodeError, Result};
+use crate::geometry::GeometricTransform;
+use crate::matrix_export;
+use crate::qr_decoder::QRDecoder;
+use crate::types::{ProcessingConfig, QRCodeResult};
+
+/// 默认图块边长（缩放后的像素）
+pub const DEFAULT_TILE_SIZE: i32 = 1024;
+
+/// 默认最大放大倍数
+pub const DEFAULT_MAX_SCALE: f64 = 2.0;
+
+/// 相邻图块重叠的比例，边长不超过该比例图块的二维码总能完整落在某个图块中
+const TILE_OVERLAP: f64 = 0.25;
+
+/// 外接框交并比超过该值视为同一位置
+const SAME_LOCATION_IOU: f64 = 0.3;
+
+/// 一个待检测的图块
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tile {
+    /// 所在层的缩放倍数
+    pub scale: f64,
+    /// 在原图中的区域
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Tile {
+    /// 原图到缩放后图块的几何映射
+    pub fn geometry(&self) -> GeometricTransform {
+        GeometricTransform::crop(self.x, self.y, self.width, self.height).scaled(self.scale)
+    }
+
+    /// 解码切出并缩放后的图块图像，结果映射回原图坐标
+    ///
+    /// 单个图块解码失败不影响其他图块，此时返回空列表。`attach_matrices` 为真时在图块上
+    /// 采样模块矩阵。
+    pub fn decode(&self, decoder: &mut QRDecoder, image: &Mat, attach_matrices: bool) -> Result<Vec<QRCodeResult>> {
+        let Ok(mut found) = decoder.decode_qr_codes(image) else {
+            return Ok(Vec::new());
+        };
+        if attach_matrices {
+            matrix_export::attach_module_matrices(image, &mut found)?;
+        }
+        let geometry = self.geometry();
+        Ok(found.into_iter().map(|result| geometry.map_result(result)).collect())
+    }
+}
+
+/// 逐块解码使用的解码器：图块数量多，逐块的解码日志关闭
+pub fn tile_decoder(config: &ProcessingConfig) -> QRDecoder {
+    QRDecoder::new(&ProcessingConfig { verbose: false, ..config.clone() })
+}
+
+/// 金字塔各层的缩放倍数，从粗到细
+///
+/// 第一层把整幅图缩小到 `tile_size` 以内（小图则为原尺寸），之后取大于第一层的 2 的幂，
+/// 直到 `max_scale`。
+pub fn levels(width: i32, height: i32, tile_size: i32, max_scale: f64) -> Vec<f64> {
+    let fit = (tile_size as f64 / width.max(height).max(1) as f64).min(1.0);
+    let mut scales = vec![fit];
+    let mut scale = 2f64.powi(fit.log2().floor() as i32 + 1);
+    while scale <= max_scale {
+        scales.push(scale);
+        scale *= 2.0;
+    }
+    scales
+}
+
+/// 按层切分图块，每层的图块覆盖整幅原图
+pub fn plan(width: i32, height: i32, tile_size: i32, max_scale: f64) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for scale in levels(width, height, tile_size, max_scale) {
+        // 图块在原图中的边长和步长
+        let window = (tile_size as f64 / scale).round() as i32;
+        let stride = ((window as f64 * (1.0 - TILE_OVERLAP)).floor() as i32).max(1);
+        for y in starts(height, window, stride) {
+            for x in starts(width, window, stride) {
+                tiles.push(Tile {
+                    scale,
+                    x,
+                    y,
+                    width: window.min(width),
+                    height: window.min(height),
+                });
+            }
+        }
+    }
+    tiles
+}
+
+/// 一维方向上的图块起点，最后一块与边缘对齐
+pub fn starts(length: i32, window: i32, stride: i32) -> Vec<i32> {
+    if window >= length {
+        return vec![0];
+    }
+    let mut starts: Vec<i32> = (0..length - window).step_by(stride as usize).collect();
+    starts.push(length - window);
+    starts
+}
+
+/// 两组角点是否指向同一位置：外接框交并比足够大，或一方的中心落在另一方框内
+pub fn same_location(a: &[(f32, f32)], b: &[(f32, f32)]) -> bool {
+    let (Some(a), Some(b)) = (bounding_box(a), bounding_box(b)) else {
+        return false;
+    };
+    let intersection = (a.2.min(b.2) - a.0.max(b.0)).max(0.0) * (a.3.min(b.3) - a.1.max(b.1)).max(0.0);
+    let area = |r: (f32, f32, f32, f32)| (r.2 - r.0) * (r.3 - r.1);
+    let union = area(a) + area(b) - intersection;
+    let contains = |r: (f32, f32, f32, f32), (cx, cy): (f32, f32)| r.0 <= cx && cx <= r.2 && r.1 <= cy && cy <= r.3;
+    let centre = |r: (f32, f32, f32, f32)| ((r.0 + r.2) / 2.0, (r.1 + r.3) / 2.0);
+    (union > 0.0 && (intersection / union) as f64 >= SAME_LOCATION_IOU) || contains(a, centre(b)) || contains(b, centre(a))
+}
+
+/// 外接框 (min_x, min_y, max_x, max_y)
+fn bounding_box(points: &[(f32, f32)]) -> Option<(f32, f32, f32, f32)> {
+    if points.is_empty() {
+        return None;
+    }
+    let min_x = points.iter().map(|p| p.0).fold(f32::MAX, f32::min);
+    let max_x = points.iter().map(|p| p.0).fold(f32::MIN, f32::max);
+    let min_y = points.iter().map(|p| p.1).fold(f32::MAX, f32::min);
+    let max_y = points.iter().map(|p| p.1).fold(f32::MIN, f32::max);
+    Some((min_x, min_y, max_x, max_y))
+}
+
+/// 合并各层、各图块的检测结果：内容相同且位置重合的只保留置信度最高的一个
+pub fn merge_detections(results: Vec<QRCodeResult>) -> Vec<QRCodeResult> {
+    let corners = |result: &QRCodeResult| {
+        let position = &result.position;
+        position.corners.clone().unwrap_or_else(|| {
+            let (x, y) = (position.x as f32, position.y as f32);
+            vec![(x, y), (x + position.width as f32, y + position.height as f32)]
+        })
+    };
+
+    let mut merged: Vec<QRCodeResult> = Vec::new();
+    for result in results {
+        let existing = merged
+            .iter_mut()
+            .find(|kept| kept.content == result.content && same_location(&corners(kept), &corners(&result)));
+        match existing {
+            Some(kept) if result.confidence > kept.confidence => *kept = result,
+            Some(_) => {}
+            None => merged.push(result),
+        }
+    }
+    merged
+}
+
+/// 逐层逐块检测，结果为原图坐标
+///
+/// `progress` 在每层开始时调用，参数为缩放倍数和该层图块数。
+pub fn detect(
+    image: &Mat,
+    decoder: &mut QRDecoder,
+    tile_size: i32,
+    max_scale: f64,
+    mut progress: impl FnMut(f64, usize),
+) -> Result<Vec<QRCodeResult>> {
+    let tiles = plan(image.cols(), image.rows(), tile_size, max_scale);
+    let mut results = Vec::new();
+    let mut current_scale = None;
+    for tile in &tiles {
+        if current_scale != Some(tile.scale) {
+            current_scale = Some(tile.scale);
+            progress(tile.scale, tiles.iter().filter(|t| t.scale == tile.scale).count());
+        }
+
+        let region = Mat::roi(image, Rect::new(tile.x, tile.y, tile.width, tile.height))?.try_clone()?;
+        let scaled = if tile.scale == 1.0 {
+            region
+        } else {
+            let (width, height) = tile.geometry().output_size();
+            let interpolation = if tile.scale < 1.0 { INTER_AREA } else { INTER_CUBIC };
+            let mut scaled = Mat::default();
+            imgproc::resize(&region, &mut scaled, Size::new(width, height), 0.0, 0.0, interpolation)
+                .map_err(|e| QRDecodeError::image_processing_error(format!("金字塔缩放失败: {}", e)))?;
+            scaled
+        };
+        results.extend(tile.decode(decoder, &scaled, false)?);
+    }
+    Ok(merge_detections(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_covers_every_level() {
+        assert_eq!(levels(4000, 3000, 1024, 2.0), vec![0.256, 0.5, 1.0, 2.0]);
+        assert_eq!(levels(800, 600, 1024, 2.0), vec![1.0, 2.0]);
+
+        let tiles = plan(4000, 3000, 1024, 2.0);
+        assert_eq!(tiles[0], Tile { scale: 0.256, x: 0, y: 0, width: 4000, height: 3000 });
+        for scale in [0.5, 1.0, 2.0] {
+            let level: Vec<&Tile> = tiles.iter().filter(|t| t.scale == scale).collect();
+            assert!(level.iter().all(|t| (t.width as f64 * scale).round() as i32 <= 1024));
+            assert!(level.iter().all(|t| t.x + t.width <= 4000 && t.y + t.height <= 3000));
+            assert!(level.iter().any(|t| t.x + t.width == 4000 && t.y + t.height == 3000));
+        }
+        // 相邻图块重叠
+        let level: Vec<&Tile> = tiles.iter().filter(|t| t.scale == 1.0 && t.y == 0).collect();
+        assert!(level.windows(2).all(|w| w[1].x < w[0].x + w[0].width));
+    }
+
+    #[test]
+    fn test_same_location() {
+        let square = |x: f32, y: f32, side: f32| vec![(x, y), (x + side, y), (x + side, y + side), (x, y + side)];
+        assert!(same_location(&square(100.0, 100.0, 40.0), &square(104.0, 98.0, 42.0)));
+        // 粗层检测到的框略大，仍包含细层检测框的中心
+        assert!(same_location(&square(90.0, 90.0, 80.0), &square(110.0, 110.0, 20.0)));
+        assert!(!same_location(&square(100.0, 100.0, 40.0), &square(300.0, 100.0, 40.0)));
+    }
+}
@@ -0,0 +1,764 @@
+//! 二维码模块矩阵解码模块
+//!
+//! 不依赖 OpenCV，直接从模块矩阵读取格式信息、去除掩码、提取码字、
+//! 按块进行 Reed-Solomon 纠错并解析数据段。未知模块所在的码字作为擦除处理。
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+use crate::module_matrix::ModuleMatrix;
+use crate::qr_layout::{self, EcLevel, ModuleRole};
+use crate::reed_solomon::{self, RsCorrection};
+
+/// 每块纠错码字数，按 [L, M, Q, H][版本] 索引
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+/// 纠错块数，按 [L, M, Q, H][版本] 索引
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+/// 字母数字模式字符表
+const ALPHANUMERIC_CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// 数据段模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SegmentMode {
+    /// 数字模式
+    Numeric,
+    /// 字母数字模式
+    Alphanumeric,
+    /// 字节模式
+    Byte,
+    /// 汉字模式 (Shift JIS)
+    Kanji,
+    /// 扩展解释 (ECI)
+    Eci,
+    /// 结构链接
+    StructuredAppend,
+    /// FNC1 标识
+    Fnc1,
+}
+
+impl SegmentMode {
+    /// 模式指示符
+    pub fn indicator(&self) -> u32 {
+        match self {
+            SegmentMode::Numeric => 0b0001,
+            SegmentMode::Alphanumeric => 0b0010,
+            SegmentMode::Byte => 0b0100,
+            SegmentMode::Kanji => 0b1000,
+            SegmentMode::Eci => 0b0111,
+            SegmentMode::StructuredAppend => 0b0011,
+            SegmentMode::Fnc1 => 0b0101,
+        }
+    }
+
+    /// 字符计数指示符的位数
+    pub fn char_count_bits(&self, version: u8) -> usize {
+        let column = match version {
+            1..=9 => 0,
+            10..=26 => 1,
+            _ => 2,
+        };
+        match self {
+            SegmentMode::Numeric => [10, 12, 14][column],
+            SegmentMode::Alphanumeric => [9, 11, 13][column],
+            SegmentMode::Byte => [8, 16, 16][column],
+            SegmentMode::Kanji => [8, 10, 12][column],
+            _ => 0,
+        }
+    }
+}
+
+/// 解析出的数据段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Segment {
+    /// 段模式
+    pub mode: SegmentMode,
+    /// 字符计数（ECI 段为 ECI 编号）
+    pub char_count: usize,
+    /// 段承载的原始字节
+    pub data: Vec<u8>,
+    /// 解码后的文本
+    pub text: String,
+}
+
+/// 模块矩阵的解码结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedSymbol {
+    /// 版本号
+    pub version: u8,
+    /// 纠错等级
+    pub ec_level: EcLevel,
+    /// 掩码编号
+    pub mask: u8,
+    /// 拼接后的文本内容
+    pub content: String,
+    /// 所有数据段
+    pub segments: Vec<Segment>,
+    /// 纠错后的数据码字
+    pub data_codewords: Vec<u8>,
+    /// 各数据段承载的原始字节
+    pub raw_bytes: Vec<u8>,
+    /// 纠错过程中参与纠正的擦除数（标记为不可读的码字全部计入）
+    pub erasures_corrected: usize,
+    /// 纠错过程中修正的错误数
+    pub errors_corrected: usize,
+}
+
+/// 纠错块结构
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockLayout {
+    /// 总码字数
+    pub total_codewords: usize,
+    /// 块数
+    pub num_blocks: usize,
+    /// 每块纠错码字数
+    pub ecc_per_block: usize,
+    /// 短块数（长块比短块多一个数据码字）
+    pub num_short_blocks: usize,
+    /// 短块的数据码字数
+    pub short_data_len: usize,
+}
+
+impl BlockLayout {
+    /// 计算指定版本和纠错等级的块结构
+    pub fn new(version: u8, ec_level: EcLevel) -> Self {
+        let total_codewords = num_raw_data_modules(version) / 8;
+        let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[ec_level.ordinal()][version as usize] as usize;
+        let ecc_per_block = ECC_CODEWORDS_PER_BLOCK[ec_level.ordinal()][version as usize] as usize;
+        let num_short_blocks = num_blocks - total_codewords % num_blocks;
+        let short_data_len = total_codewords / num_blocks - ecc_per_block;
+
+        Self {
+            total_codewords,
+            num_blocks,
+            ecc_per_block,
+            num_short_blocks,
+            short_data_len,
+        }
+    }
+
+    /// 数据码字总数
+    pub fn data_codewords(&self) -> usize {
+        self.total_codewords - self.num_blocks * self.ecc_per_block
+    }
+
+    /// 第 `block` 块的数据码字数
+    pub fn block_data_len(&self, block: usize) -> usize {
+        self.short_data_len + (block >= self.num_short_blocks) as usize
+    }
+
+    /// 交织顺序：返回每个码字所属的 (块序号, 块内下标)
+    pub fn interleave_order(&self) -> Vec<(usize, usize)> {
+        let mut order = Vec::with_capacity(self.total_codewords);
+        let long_data_len = self.short_data_len + 1;
+
+        // 数据码字：按列交织，短块在最后一列没有码字
+        for i in 0..long_data_len {
+            for block in 0..self.num_blocks {
+                if i < self.block_data_len(block) {
+                    order.push((block, i));
+                }
+            }
+        }
+        // 纠错码字
+        for i in 0..self.ecc_per_block {
+            for block in 0..self.num_blocks {
+                order.push((block, self.block_data_len(block) + i));
+            }
+        }
+        order
+    }
+}
+
+/// 版本对应的数据模块数（含剩余位）
+pub fn num_raw_data_modules(version: u8) -> usize {
+    let v = version as usize;
+    let mut result = (16 * v + 128) * v + 64;
+    if v >= 2 {
+        let num_align = v / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if v >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+/// 判断掩码图形在 (x, y) 处是否翻转
+pub fn mask_bit(mask: u8, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y) % 2 == 0,
+        1 => y % 2 == 0,
+        2 => x % 3 == 0,
+        3 => (x + y) % 3 == 0,
+        4 => (x / 3 + y / 2) % 2 == 0,
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3) % 2 == 0,
+        _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+    }
+}
+
+/// 按 Z 字形顺序列出数据区模块坐标 (x, y)
+pub fn data_module_order(size: usize) -> Vec<(usize, usize)> {
+    let roles = qr_layout::module_roles(size);
+    let mut order = Vec::new();
+
+    let mut right = size as isize - 1;
+    while right >= 1 {
+        if right == 6 {
+            right = 5;
+        }
+        let upward = ((right + 1) & 2) == 0;
+        for vert in 0..size {
+            let y = if upward { size - 1 - vert } else { vert };
+            for j in 0..2 {
+                let x = right as usize - j;
+                if roles[y * size + x] == ModuleRole::Data {
+                    order.push((x, y));
+                }
+            }
+        }
+        right -= 2;
+    }
+    order
+}
+
+/// 解码模块矩阵
+pub fn decode_matrix(matrix: &ModuleMatrix) -> Result<DecodedSymbol> {
+    let version = matrix.version().ok_or_else(|| {
+        QRDecodeError::decode_error(format!("无效的模块矩阵尺寸: {}", matrix.size))
+    })?;
+
+    let format = qr_layout::read_format_info(matrix)
+        .ok_or_else(|| QRDecodeError::decode_error("格式信息无法识别".to_string()))?;
+
+    let layout = BlockLayout::new(version, format.ec_level);
+    let (codewords, erased) = read_codewords(matrix, format.mask, layout.total_codewords);
+
+    // 解交织到各块
+    let mut blocks: Vec<Vec<u8>> = (0..layout.num_blocks)
+        .map(|b| vec![0u8; layout.block_data_len(b) + layout.ecc_per_block])
+        .collect();
+    let mut block_erasures: Vec<Vec<usize>> = vec![Vec::new(); layout.num_blocks];
+    for (i, &(block, index)) in layout.interleave_order().iter().enumerate() {
+        blocks[block][index] = codewords[i];
+        if erased[i] {
+            block_erasures[block].push(index);
+        }
+    }
+
+    let mut data_codewords = Vec::with_capacity(layout.data_codewords());
+    let mut total = RsCorrection::default();
+    for (b, block) in blocks.iter_mut().enumerate() {
+        let correction = reed_solomon::decode(block, layout.ecc_per_block, &block_erasures[b])
+            .map_err(|e| QRDecodeError::decode_error(format!("第 {} 个纠错块: {}", b + 1, e)))?;
+        total.erasures += correction.erasures;
+        total.errors += correction.errors;
+        data_codewords.extend_from_slice(&block[..layout.block_data_len(b)]);
+    }
+
+    let (segments, _) = parse_segments(&data_codewords, version)?;
+    let content = segments.iter().map(|s| s.text.as_str()).collect::<String>();
+    let raw_bytes = segments
+        .iter()
+        .filter(|s| !matches!(s.mode, SegmentMode::Eci | SegmentMode::StructuredAppend | SegmentMode::Fnc1))
+        .flat_map(|s| s.data.iter().cloned())
+        .collect();
+
+    Ok(DecodedSymbol {
+        version,
+        ec_level: format.ec_level,
+        mask: format.mask,
+        content,
+        segments,
+        data_codewords,
+        raw_bytes,
+        erasures_corrected: total.erasures,
+        errors_corrected: total.errors,
+    })
+}
+
+/// 去除掩码并按顺序读取码字，返回码字及其是否包含未知模块
+pub fn read_codewords(matrix: &ModuleMatrix, mask: u8, count: usize) -> (Vec<u8>, Vec<bool>) {
+    let mut codewords = vec![0u8; count];
+    let mut erased = vec![false; count];
+
+    for (i, &(x, y)) in data_module_order(matrix.size).iter().enumerate().take(count * 8) {
+        let byte = i / 8;
+        if matrix.is_unknown(x, y) {
+            erased[byte] = true;
+            continue;
+        }
+        if matrix.get(x, y) ^ mask_bit(mask, x, y) {
+            codewords[byte] |= 0x80 >> (i % 8);
+        }
+    }
+
+    (codewords, erased)
+}
+
+/// 按位读取的数据流
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// 创建新的位读取器
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// 剩余位数
+    pub fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+
+    /// 当前位置（位）
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// 跳过 `count` 位
+    pub fn skip(&mut self, count: usize) -> Result<()> {
+        if count > self.remaining() {
+            return Err(QRDecodeError::decode_error(format!(
+                "数据流长度不足 {} 位",
+                self.position + count
+            )));
+        }
+        self.position += count;
+        Ok(())
+    }
+
+    /// 读取 `count` 位（最多 32 位）
+    pub fn read(&mut self, count: usize) -> Result<u32> {
+        if count > self.remaining() {
+            return Err(QRDecodeError::decode_error(format!(
+                "数据流在第 {} 位意外结束",
+                self.position
+            )));
+        }
+        let mut value = 0u32;
+        for _ in 0..count {
+            let bit = (self.data[self.position / 8] >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// 解析数据码字中的数据段，返回数据段和数据段结束（终止符开始）的位置（位）
+pub fn parse_segments(data: &[u8], version: u8) -> Result<(Vec<Segment>, usize)> {
+    parse_segments_at(data, version, 0)
+}
+
+/// 从指定位位置开始解析数据段
+pub fn parse_segments_at(data: &[u8], version: u8, start_bit: usize) -> Result<(Vec<Segment>, usize)> {
+    let mut reader = BitReader::new(data);
+    reader.skip(start_bit)?;
+    let mut segments = Vec::new();
+    let mut eci: Option<u32> = None;
+
+    while reader.remaining() >= 4 {
+        let segment_start = reader.position();
+        let indicator = reader.read(4)?;
+        let segment = match indicator {
+            0b0000 => return Ok((segments, segment_start)),
+            0b0001 => read_numeric(&mut reader, version)?,
+            0b0010 => read_alphanumeric(&mut reader, version)?,
+            0b0100 => read_byte(&mut reader, version, eci)?,
+            0b1000 => read_kanji(&mut reader, version)?,
+            0b0111 => {
+                let value = read_eci_designator(&mut reader)?;
+                eci = Some(value);
+                Segment {
+                    mode: SegmentMode::Eci,
+                    char_count: value as usize,
+                    data: Vec::new(),
+                    text: String::new(),
+                }
+            }
+            0b0011 => {
+                let header = reader.read(16)?;
+                Segment {
+                    mode: SegmentMode::StructuredAppend,
+                    char_count: ((header >> 12) & 0xF) as usize,
+                    data: vec![(header >> 8) as u8, header as u8],
+                    text: String::new(),
+                }
+            }
+            0b0101 => Segment {
+                mode: SegmentMode::Fnc1,
+                char_count: 0,
+                data: Vec::new(),
+                text: String::new(),
+            },
+            0b1001 => {
+                let application = reader.read(8)?;
+                Segment {
+                    mode: SegmentMode::Fnc1,
+                    char_count: 0,
+                    data: vec![application as u8],
+                    text: String::new(),
+                }
+            }
+            other => {
+                return Err(QRDecodeError::decode_error(format!(
+                    "未知的模式指示符 {:04b} (位置 {})",
+                    other,
+                    reader.position() - 4
+                )))
+            }
+        };
+        segments.push(segment);
+    }
+
+    Ok((segments, reader.position()))
+}
+
+fn read_numeric(reader: &mut BitReader, version: u8) -> Result<Segment> {
+    let count = reader.read(SegmentMode::Numeric.char_count_bits(version))? as usize;
+    let mut text = String::with_capacity(count);
+    let mut left = count;
+    while left > 0 {
+        let (bits, digits) = match left {
+            1 => (4, 1),
+            2 => (7, 2),
+            _ => (10, 3),
+        };
+        let value = reader.read(bits)?;
+        if value >= 10u32.pow(digits as u32) {
+            return Err(QRDecodeError::decode_error(format!("无效的数字模式数据: {}", value)));
+        }
+        text.push_str(&format!("{:0width$}", value, width = digits));
+        left -= digits;
+    }
+
+    Ok(Segment {
+        mode: SegmentMode::Numeric,
+        char_count: count,
+        data: text.as_bytes().to_vec(),
+        text,
+    })
+}
+
+fn read_alphanumeric(reader: &mut BitReader, version: u8) -> Result<Segment> {
+    let count = reader.read(SegmentMode::Alphanumeric.char_count_bits(version))? as usize;
+    let mut bytes = Vec::with_capacity(count);
+    let mut left = count;
+    while left > 0 {
+        if left >= 2 {
+            let value = reader.read(11)? as usize;
+            if value >= 45 * 45 {
+                return Err(QRDecodeError::decode_error(format!("无效的字母数字模式数据: {}", value)));
+            }
+            bytes.push(ALPHANUMERIC_CHARSET[value / 45]);
+            bytes.push(ALPHANUMERIC_CHARSET[value % 45]);
+            left -= 2;
+        } else {
+            let value = reader.read(6)? as usize;
+            if value >= 45 {
+                return Err(QRDecodeError::decode_error(format!("无效的字母数字模式数据: {}", value)));
+            }
+            bytes.push(ALPHANUMERIC_CHARSET[value]);
+            left -= 1;
+        }
+    }
+
+    Ok(Segment {
+        mode: SegmentMode::Alphanumeric,
+        char_count: count,
+        text: String::from_utf8_lossy(&bytes).into_owned(),
+        data: bytes,
+    })
+}
+
+fn read_byte(reader: &mut BitReader, version: u8, eci: Option<u32>) -> Result<Segment> {
+    let count = reader.read(SegmentMode::Byte.char_count_bits(version))? as usize;
+    let mut bytes = Vec::with_capacity(count);
+    for _ in 0..count {
+        bytes.push(reader.read(8)? as u8);
+    }
+
+    Ok(Segment {
+        mode: SegmentMode::Byte,
+        char_count: count,
+        text: decode_byte_text(&bytes, eci),
+        data: bytes,
+    })
+}
+
+fn read_kanji(reader: &mut BitReader, version: u8) -> Result<Segment> {
+    let count = reader.read(SegmentMode::Kanji.char_count_bits(version))? as usize;
+    let mut bytes = Vec::with_capacity(count * 2);
+    for _ in 0..count {
+        let value = reader.read(13)?;
+        let mut sjis = ((value / 0xC0) << 8) | (value % 0xC0);
+        sjis += if sjis < 0x1F00 { 0x8140 } else { 0xC140 };
+        bytes.push((sjis >> 8) as u8);
+        bytes.push(sjis as u8);
+    }
+
+    let (text, _, _) = encoding_rs::SHIFT_JIS.decode(&bytes);
+    Ok(Segment {
+        mode: SegmentMode::Kanji,
+        char_count: count,
+        text: text.into_owned(),
+        data: bytes,
+    })
+}
+
+fn read_eci_designator(reader: &mut BitReader) -> Result<u32> {
+    let first = reader.read(8)?;
+    if first & 0x80 == 0 {
+        Ok(first & 0x7F)
+    } else if first & 0xC0 == 0x80 {
+        Ok(((first & 0x3F) << 8) | reader.read(8)?)
+    } else if first & 0xE0 == 0xC0 {
+        Ok(((first & 0x1F) << 16) | reader.read(16)?)
+    } else {
+        Err(QRDecodeError::decode_error(format!("无效的 ECI 指示符: {:#04x}", first)))
+    }
+}
+
+/// 按 ECI 解释字节模式数据；未指定 ECI 时优先尝试 UTF-8，失败则按 ISO-8859-1 解释
+fn decode_byte_text(bytes: &[u8], eci: Option<u32>) -> String {
+    match eci {
+        Some(20) => encoding_rs::SHIFT_JIS.decode(bytes).0.into_owned(),
+        Some(1) | Some(3) => bytes.iter().map(|&b| b as char).collect(),
+        Some(26) => String::from_utf8_lossy(bytes).into_owned(),
+        _ => match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|&b| b as char).collect(),
+        },
+    }
+}
+
+/// 数据码字的标准填充字节
+pub const PAD_CODEWORDS: [u8; 2] = [0xEC, 0x11];
+
+/// 重新编码数据段，按标准方式补充终止符和填充字节，得到数据码字
+pub fn build_data_codewords(segments: &[Segment], version: u8, ec_level: EcLevel) -> Result<Vec<u8>> {
+    let capacity = BlockLayout::new(version, ec_level).data_codewords();
+    let mut bits: Vec<bool> = Vec::new();
+
+    for segment in segments {
+        match segment.mode {
+            SegmentMode::Numeric => {
+                push_bits(&mut bits, 0b0001, 4);
+                push_bits(&mut bits, segment.char_count as u32, SegmentMode::Numeric.char_count_bits(version));
+                for chunk in segment.data.chunks(3) {
+                    let value = chunk.iter().fold(0u32, |acc, &d| acc * 10 + (d - b'0') as u32);
+                    push_bits(&mut bits, value, chunk.len() * 3 + 1);
+                }
+            }
+            SegmentMode::Alphanumeric => {
+                push_bits(&mut bits, 0b0010, 4);
+                push_bits(&mut bits, segment.char_count as u32, SegmentMode::Alphanumeric.char_count_bits(version));
+                let index = |c: u8| ALPHANUMERIC_CHARSET.iter().position(|&a| a == c).unwrap_or(0) as u32;
+                for chunk in segment.data.chunks(2) {
+                    match chunk {
+                        [a, b] => push_bits(&mut bits, index(*a) * 45 + index(*b), 11),
+                        [a] => push_bits(&mut bits, index(*a), 6),
+                        _ => {}
+                    }
+                }
+            }
+            SegmentMode::Byte => {
+                push_bits(&mut bits, 0b0100, 4);
+                push_bits(&mut bits, segment.char_count as u32, SegmentMode::Byte.char_count_bits(version));
+                for &byte in &segment.data {
+                    push_bits(&mut bits, byte as u32, 8);
+                }
+            }
+            SegmentMode::Kanji => {
+                push_bits(&mut bits, 0b1000, 4);
+                push_bits(&mut bits, segment.char_count as u32, SegmentMode::Kanji.char_count_bits(version));
+                for pair in segment.data.chunks(2) {
+                    let sjis = ((pair[0] as u32) << 8) | *pair.get(1).unwrap_or(&0) as u32;
+                    let offset = if sjis >= 0xC140 { sjis - 0xC140 } else { sjis - 0x8140 };
+                    push_bits(&mut bits, (offset >> 8) * 0xC0 + (offset & 0xFF), 13);
+                }
+            }
+            SegmentMode::Eci => {
+                push_bits(&mut bits, 0b0111, 4);
+                let value = segment.char_count as u32;
+                if value < 1 << 7 {
+                    push_bits(&mut bits, value, 8);
+                } else if value < 1 << 14 {
+                    push_bits(&mut bits, 0b10, 2);
+                    push_bits(&mut bits, value, 14);
+                } else {
+                    push_bits(&mut bits, 0b110, 3);
+                    push_bits(&mut bits, value, 21);
+                }
+            }
+            SegmentMode::StructuredAppend => {
+                push_bits(&mut bits, 0b0011, 4);
+                for &byte in &segment.data {
+                    push_bits(&mut bits, byte as u32, 8);
+                }
+            }
+            SegmentMode::Fnc1 => match segment.data.first() {
+                Some(&application) => {
+                    push_bits(&mut bits, 0b1001, 4);
+                    push_bits(&mut bits, application as u32, 8);
+                }
+                None => push_bits(&mut bits, 0b0101, 4),
+            },
+        }
+    }
+
+    let capacity_bits = capacity * 8;
+    if bits.len() > capacity_bits {
+        return Err(QRDecodeError::decode_error(format!(
+            "数据段需要 {} 位，超出版本 {}-{} 的容量 {} 位",
+            bits.len(),
+            version,
+            ec_level,
+            capacity_bits
+        )));
+    }
+
+    // 终止符（容量不足时截断）和字节对齐
+    let terminator = (capacity_bits - bits.len()).min(4);
+    push_bits(&mut bits, 0, terminator);
+    let padding = (8 - bits.len() % 8) % 8;
+    push_bits(&mut bits, 0, padding);
+
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0u8, |acc, &bit| (acc << 1) | bit as u8))
+        .collect();
+    let mut pad = PAD_CODEWORDS.iter().cycle();
+    while codewords.len() < capacity {
+        codewords.push(*pad.next().unwrap_or(&PAD_CODEWORDS[0]));
+    }
+
+    Ok(codewords)
+}
+
+/// 按高位在前追加 `count` 位
+fn push_bits(bits: &mut Vec<bool>, value: u32, count: usize) {
+    for i in (0..count).rev() {
+        bits.push((value >> i) & 1 == 1);
+    }
+}
+
+/// 为数据码字计算各块纠错码并按交织顺序排列
+pub fn interleave_with_ecc(data_codewords: &[u8], version: u8, ec_level: EcLevel) -> Vec<u8> {
+    let layout = BlockLayout::new(version, ec_level);
+    let mut blocks = Vec::with_capacity(layout.num_blocks);
+    let mut offset = 0;
+    for b in 0..layout.num_blocks {
+        let len = layout.block_data_len(b);
+        let mut block = data_codewords[offset..offset + len].to_vec();
+        block.extend(reed_solomon::encode(&block, layout.ecc_per_block));
+        blocks.push(block);
+        offset += len;
+    }
+
+    layout
+        .interleave_order()
+        .iter()
+        .map(|&(block, index)| blocks[block][index])
+        .collect()
+}
+
+/// 由数据码字生成完整的模块矩阵（功能图形、纠错码、掩码均按标准生成，剩余位为 0）
+pub fn encode_matrix(data_codewords: &[u8], version: u8, ec_level: EcLevel, mask: u8) -> ModuleMatrix {
+    let size = 17 + 4 * version as usize;
+    let mut matrix = ModuleMatrix::new(size);
+    qr_layout::draw_function_patterns(&mut matrix, ec_level, mask);
+
+    let codewords = interleave_with_ecc(data_codewords, version, ec_level);
+    for (i, &(x, y)) in data_module_order(size).iter().enumerate() {
+        let bit = codewords
+            .get(i / 8)
+            .is_some_and(|&byte| (byte >> (7 - i % 8)) & 1 == 1);
+        matrix.set(x, y, bit ^ mask_bit(mask, x, y));
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_layout() {
+        let layout = BlockLayout::new(1, EcLevel::M);
+        assert_eq!(layout.total_codewords, 26);
+        assert_eq!(layout.data_codewords(), 16);
+
+        // 版本 5-Q: 2 个 15 码字块 + 2 个 16 码字块，每块 18 个纠错码字
+        let layout = BlockLayout::new(5, EcLevel::Q);
+        assert_eq!(layout.num_blocks, 4);
+        assert_eq!(layout.num_short_blocks, 2);
+        assert_eq!(layout.short_data_len, 15);
+        assert_eq!(layout.data_codewords(), 62);
+        assert_eq!(layout.interleave_order().len(), 134);
+
+        assert_eq!(BlockLayout::new(40, EcLevel::L).data_codewords(), 2956);
+        assert_eq!(BlockLayout::new(40, EcLevel::H).data_codewords(), 1276);
+    }
+
+    #[test]
+    fn test_raw_data_modules_match_layout() {
+        for version in 1..=40u8 {
+            let size = 17 + 4 * version as usize;
+            assert_eq!(data_module_order(size).len(), num_raw_data_modules(version));
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let segments = vec![Segment {
+            mode: SegmentMode::Byte,
+            char_count: 5,
+            data: b"hello".to_vec(),
+            text: "hello".to_string(),
+        }];
+        let data = build_data_codewords(&segments, 2, EcLevel::Q).unwrap();
+        let mut matrix = encode_matrix(&data, 2, EcLevel::Q, 3);
+
+        // 标记一个码字为未知，再翻转另一个码字中的模块
+        let order = data_module_order(matrix.size);
+        for &(x, y) in &order[0..8] {
+            matrix.set_unknown(x, y, true);
+        }
+        let (x, y) = order[100];
+        matrix.set(x, y, !matrix.get(x, y));
+
+        let symbol = decode_matrix(&matrix).unwrap();
+        assert_eq!(symbol.content, "hello");
+        assert_eq!(symbol.mask, 3);
+        assert_eq!(symbol.data_codewords, data);
+        assert_eq!(symbol.erasures_corrected, 1);
+        assert_eq!(symbol.errors_corrected, 1);
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        // 版本 1 示例 "01234567" 的数据码字
+        let data = [0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11];
+        let (segments, end) = parse_segments(&data, 1).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].mode, SegmentMode::Numeric);
+        assert_eq!(segments[0].text, "01234567");
+        assert_eq!(end, 4 + 10 + 10 + 10 + 7);
+    }
+}
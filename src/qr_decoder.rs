@@ -10,7 +10,9 @@ use opencv::{
 use std::collections::HashMap;
 
 use crate::error::{QRDecodeError, Result};
-use crate::types::{ProcessingConfig, QRCodeResult, QRPosition};
+use crate::module_matrix::{self, ModuleMatrix};
+use crate::qr_codec::{self, BlockLayout};
+use crate::types::{CorrectionReport, ProcessingConfig, QRCodeResult, QRPosition};
 use crate::wechat_qr_decoder::WeChatQRDecoder;
 
 /// 二维码解码器
@@ -187,6 +189,97 @@ impl QRDecoder {
         Ok(result)
     }
     
+    /// 根据用户提供的四个角点直接采样并解码，跳过检测步骤
+    pub fn decode_from_corners(
+        &mut self,
+        image: &Mat,
+        corners: &[(f32, f32)],
+        module_count: Option<usize>,
+    ) -> Result<QRCodeResult> {
+        let matrix = module_matrix::sample_from_corners(image, corners, module_count)?;
+        self.decode_module_matrix(&matrix, corners)
+    }
+    
+    /// 解码已采样的模块矩阵，结果位置使用原图中的角点
+    pub fn decode_module_matrix(&mut self, matrix: &ModuleMatrix, corners: &[(f32, f32)]) -> Result<QRCodeResult> {
+        let position = self.calculate_position_from_corners(corners)?;
+        self.decode_matrix_at(matrix, position, "CORNER_SAMPLED_QR_CODE")
+    }
+    
+    /// 解码文本网格得到的模块矩阵，结果位置以模块为单位
+    pub fn decode_module_grid(&mut self, matrix: &ModuleMatrix) -> Result<QRCodeResult> {
+        let side = matrix.size as f32;
+        let corners = [(0.0, 0.0), (side, 0.0), (side, side), (0.0, side)];
+        let position = self.calculate_position_from_corners(&corners)?;
+        self.decode_matrix_at(matrix, position, "MODULE_GRID_QR_CODE")
+    }
+    
+    /// 解码模块矩阵：优先使用纯 Rust 解码器（支持未知模块擦除），失败后渲染为图像交给标准解码器
+    fn decode_matrix_at(&mut self, matrix: &ModuleMatrix, position: QRPosition, qr_type: &str) -> Result<QRCodeResult> {
+        if self.config.verbose {
+            println!("🧩 解码 {}x{} 模块矩阵 (版本 {}, 未知模块 {})", matrix.size, matrix.size,
+                matrix.version().map(|v| v.to_string()).unwrap_or_else(|| "未知".to_string()),
+                matrix.unknown_count());
+        }
+        
+        self.stats.total_attempts += 1;
+        
+        let codec_error = match qr_codec::decode_matrix(matrix) {
+            Ok(symbol) => {
+                if self.config.verbose {
+                    println!("✅ 模块矩阵解码成功: 版本 {}-{}, 掩码 {}", symbol.version, symbol.ec_level, symbol.mask);
+                }
+                self.stats.successful_decodes += 1;
+                self.stats.total_qr_codes_found += 1;
+                
+                let layout = BlockLayout::new(symbol.version, symbol.ec_level);
+                let correction = CorrectionReport {
+                    version: symbol.version,
+                    ec_level: symbol.ec_level,
+                    mask: symbol.mask,
+                    unknown_modules: matrix.unknown_count(),
+                    erasures_corrected: symbol.erasures_corrected,
+                    errors_corrected: symbol.errors_corrected,
+                    blocks: layout.num_blocks,
+                    ecc_per_block: layout.ecc_per_block,
+                };
+                if self.config.verbose {
+                    println!("🩹 纠正擦除 {} 个、错误 {} 个码字", correction.erasures_corrected, correction.errors_corrected);
+                }
+                
+                let result = QRCodeResult::new(symbol.content, position, 1.0, qr_type.to_string())
+                    .with_raw_bytes(symbol.raw_bytes)
+                    .with_module_matrix(matrix.clone())
+                    .with_correction(correction);
+                return Ok(result);
+            }
+            Err(e) => e,
+        };
+        
+        if self.config.verbose {
+            println!("⚠️ 模块矩阵直接解码失败: {}，尝试渲染后解码", codec_error);
+        }
+        
+        // 将模块矩阵渲染为干净的图像后交给标准解码器
+        let rendered = matrix.render(8, 4)?;
+        let decoded = self.detect_and_decode_single(&rendered)
+            .map_err(|e| QRDecodeError::decode_error(format!(
+                "{}x{} 模块矩阵解码失败: {}; {}", matrix.size, matrix.size, codec_error, e
+            )))?;
+        
+        self.stats.successful_decodes += 1;
+        self.stats.total_qr_codes_found += 1;
+        
+        let result = QRCodeResult::new(
+            decoded.content,
+            position,
+            decoded.confidence,
+            qr_type.to_string(),
+        ).with_module_matrix(matrix.clone());
+        
+        Ok(result)
+    }
+    
     /// 检测并解码多个二维码
     fn detect_and_decode_multi(&mut self, image: &Mat) -> Result<Vec<QRCodeResult>> {
         let mut decoded_infos = Vector::<String>::new();
@@ -494,6 +587,7 @@ mod tests {
             min_confidence: 0.5,
             save_processed: false,
             processed_output_path: None,
+            ..ProcessingConfig::default()
         }
     }
     
@@ -0,0 +1,464 @@
+//! 二维码结构布局模块
+//!
+//! 描述二维码各版本的功能图形位置（定位图形、时序图形、校正图形等），
+//! 并负责读取格式信息和版本信息。
+
+use serde::{Deserialize, Serialize};
+
+use crate::module_matrix::{version_from_size, ModuleMatrix};
+
+/// 格式信息 BCH 生成多项式
+const FORMAT_GENERATOR: u32 = 0x537;
+/// 格式信息掩码
+const FORMAT_MASK: u32 = 0x5412;
+/// 版本信息 BCH 生成多项式
+const VERSION_GENERATOR: u32 = 0x1F25;
+/// 格式/版本信息副本参与比对所需的最少已知位数
+const MIN_KNOWN_INFO_BITS: u32 = 10;
+
+/// 纠错等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EcLevel {
+    /// 约 7% 纠错能力
+    L,
+    /// 约 15% 纠错能力
+    M,
+    /// 约 25% 纠错能力
+    Q,
+    /// 约 30% 纠错能力
+    H,
+}
+
+impl EcLevel {
+    /// 格式信息中使用的 2 位编码
+    pub fn format_bits(&self) -> u32 {
+        match self {
+            EcLevel::L => 1,
+            EcLevel::M => 0,
+            EcLevel::Q => 3,
+            EcLevel::H => 2,
+        }
+    }
+
+    /// 从格式信息中的 2 位编码还原纠错等级
+    pub fn from_format_bits(bits: u32) -> Self {
+        match bits & 0b11 {
+            1 => EcLevel::L,
+            0 => EcLevel::M,
+            3 => EcLevel::Q,
+            _ => EcLevel::H,
+        }
+    }
+
+    /// 在纠错表中的序号 (L, M, Q, H)
+    pub fn ordinal(&self) -> usize {
+        match self {
+            EcLevel::L => 0,
+            EcLevel::M => 1,
+            EcLevel::Q => 2,
+            EcLevel::H => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for EcLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EcLevel::L => "L",
+            EcLevel::M => "M",
+            EcLevel::Q => "Q",
+            EcLevel::H => "H",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 格式信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatInfo {
+    /// 纠错等级
+    pub ec_level: EcLevel,
+    /// 掩码编号 (0-7)
+    pub mask: u8,
+    /// 与读取到的格式位之间的最小汉明距离
+    pub distance: u32,
+}
+
+/// 模块在符号中的作用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModuleRole {
+    /// 定位图形
+    Finder,
+    /// 定位图形分隔符
+    Separator,
+    /// 时序图形
+    Timing,
+    /// 校正图形
+    Alignment,
+    /// 格式信息
+    FormatInfo,
+    /// 版本信息
+    VersionInfo,
+    /// 固定暗模块
+    DarkModule,
+    /// 数据和纠错码区域
+    Data,
+}
+
+impl ModuleRole {
+    /// 在注释网格中使用的字符
+    pub fn symbol(&self) -> char {
+        match self {
+            ModuleRole::Finder => 'F',
+            ModuleRole::Separator => 's',
+            ModuleRole::Timing => 'T',
+            ModuleRole::Alignment => 'A',
+            ModuleRole::FormatInfo => 'f',
+            ModuleRole::VersionInfo => 'v',
+            ModuleRole::DarkModule => 'D',
+            ModuleRole::Data => '.',
+        }
+    }
+
+    /// 角色的中文描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            ModuleRole::Finder => "定位图形",
+            ModuleRole::Separator => "分隔符",
+            ModuleRole::Timing => "时序图形",
+            ModuleRole::Alignment => "校正图形",
+            ModuleRole::FormatInfo => "格式信息",
+            ModuleRole::VersionInfo => "版本信息",
+            ModuleRole::DarkModule => "暗模块",
+            ModuleRole::Data => "数据区",
+        }
+    }
+}
+
+/// 计算指定版本的校正图形中心坐标
+pub fn alignment_positions(version: u8) -> Vec<usize> {
+    if version <= 1 {
+        return Vec::new();
+    }
+
+    let version = version as usize;
+    let size = 17 + 4 * version;
+    let count = version / 7 + 2;
+    let step = if version == 32 {
+        26
+    } else {
+        (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2
+    };
+
+    let mut positions = vec![0; count];
+    positions[0] = 6;
+    let mut pos = size - 7;
+    for slot in positions.iter_mut().skip(1).rev() {
+        *slot = pos;
+        pos = pos.saturating_sub(step);
+    }
+    positions
+}
+
+/// 两份格式信息的模块坐标 (x, y)，下标 i 对应格式位的第 i 位
+pub fn format_info_positions(size: usize) -> [[(usize, usize); 15]; 2] {
+    let mut first = [(0, 0); 15];
+    let mut second = [(0, 0); 15];
+
+    for (i, slot) in first.iter_mut().enumerate() {
+        *slot = match i {
+            0..=5 => (8, i),
+            6 => (8, 7),
+            7 => (8, 8),
+            8 => (7, 8),
+            _ => (14 - i, 8),
+        };
+    }
+
+    for (i, slot) in second.iter_mut().enumerate() {
+        *slot = if i < 8 {
+            (size - 1 - i, 8)
+        } else {
+            (8, size - 15 + i)
+        };
+    }
+
+    [first, second]
+}
+
+/// 两份版本信息的模块坐标 (x, y)，下标 i 对应版本位的第 i 位（版本 7 以下为空）
+pub fn version_info_positions(size: usize) -> Vec<[(usize, usize); 2]> {
+    match version_from_size(size) {
+        Some(version) if version >= 7 => (0..18)
+            .map(|i| {
+                let a = size - 11 + i % 3;
+                let b = i / 3;
+                [(a, b), (b, a)]
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 生成所有模块的作用映射（按行存储）
+pub fn module_roles(size: usize) -> Vec<ModuleRole> {
+    let mut roles = vec![ModuleRole::Data; size * size];
+    let version = match version_from_size(size) {
+        Some(version) => version,
+        None => return roles,
+    };
+    let mut mark = |x: usize, y: usize, role: ModuleRole| roles[y * size + x] = role;
+
+    // 时序图形
+    for i in 0..size {
+        mark(6, i, ModuleRole::Timing);
+        mark(i, 6, ModuleRole::Timing);
+    }
+
+    // 定位图形及分隔符
+    for &(ox, oy) in &[(0usize, 0usize), (size - 7, 0), (0, size - 7)] {
+        for dy in -1i32..=7 {
+            for dx in -1i32..=7 {
+                let x = ox as i32 + dx;
+                let y = oy as i32 + dy;
+                if x < 0 || y < 0 || x >= size as i32 || y >= size as i32 {
+                    continue;
+                }
+                let role = if (0..7).contains(&dx) && (0..7).contains(&dy) {
+                    ModuleRole::Finder
+                } else {
+                    ModuleRole::Separator
+                };
+                mark(x as usize, y as usize, role);
+            }
+        }
+    }
+
+    // 校正图形（跳过与定位图形重叠的三个角）
+    let positions = alignment_positions(version);
+    let last = positions.len().saturating_sub(1);
+    for (i, &cy) in positions.iter().enumerate() {
+        for (j, &cx) in positions.iter().enumerate() {
+            if (i == 0 && (j == 0 || j == last)) || (i == last && j == 0) {
+                continue;
+            }
+            for y in cy - 2..=cy + 2 {
+                for x in cx - 2..=cx + 2 {
+                    mark(x, y, ModuleRole::Alignment);
+                }
+            }
+        }
+    }
+
+    // 格式信息和版本信息
+    for copy in format_info_positions(size).iter() {
+        for &(x, y) in copy.iter() {
+            mark(x, y, ModuleRole::FormatInfo);
+        }
+    }
+    for pair in version_info_positions(size) {
+        for &(x, y) in pair.iter() {
+            mark(x, y, ModuleRole::VersionInfo);
+        }
+    }
+
+    mark(8, size - 8, ModuleRole::DarkModule);
+    roles
+}
+
+/// 在矩阵中绘制所有功能图形、格式信息和版本信息（数据区保持不变）
+pub fn draw_function_patterns(matrix: &mut ModuleMatrix, ec_level: EcLevel, mask: u8) {
+    let size = matrix.size;
+    let version = match version_from_size(size) {
+        Some(version) => version,
+        None => return,
+    };
+    let roles = module_roles(size);
+    let alignment = alignment_positions(version);
+
+    for y in 0..size {
+        for x in 0..size {
+            let dark = match roles[y * size + x] {
+                ModuleRole::Finder => {
+                    let dx = if x >= size - 7 { x - (size - 7) } else { x };
+                    let dy = if y >= size - 7 { y - (size - 7) } else { y };
+                    dx.min(dy).min(6 - dx).min(6 - dy) != 1
+                }
+                ModuleRole::Separator => false,
+                ModuleRole::Timing => (x + y) % 2 == 0,
+                ModuleRole::Alignment => {
+                    let nearest = |v: usize| {
+                        alignment
+                            .iter()
+                            .map(|&c| (v as i32 - c as i32).abs())
+                            .min()
+                            .unwrap_or(0)
+                    };
+                    nearest(x).max(nearest(y)) != 1
+                }
+                ModuleRole::DarkModule => true,
+                _ => continue,
+            };
+            matrix.set(x, y, dark);
+        }
+    }
+
+    let format_bits = encode_format_bits(ec_level, mask);
+    for copy in format_info_positions(size).iter() {
+        for (i, &(x, y)) in copy.iter().enumerate() {
+            matrix.set(x, y, (format_bits >> i) & 1 == 1);
+        }
+    }
+
+    let version_bits = encode_version_bits(version);
+    for (i, pair) in version_info_positions(size).iter().enumerate() {
+        for &(x, y) in pair.iter() {
+            matrix.set(x, y, (version_bits >> i) & 1 == 1);
+        }
+    }
+}
+
+/// 计算格式信息的 15 位编码（含 BCH 校验和掩码）
+pub fn encode_format_bits(ec_level: EcLevel, mask: u8) -> u32 {
+    let data = (ec_level.format_bits() << 3) | mask as u32;
+    let mut remainder = data;
+    for _ in 0..10 {
+        remainder = (remainder << 1) ^ ((remainder >> 9) * FORMAT_GENERATOR);
+    }
+    ((data << 10) | remainder) ^ FORMAT_MASK
+}
+
+/// 计算版本信息的 18 位编码（含 BCH 校验）
+pub fn encode_version_bits(version: u8) -> u32 {
+    let data = version as u32;
+    let mut remainder = data;
+    for _ in 0..12 {
+        remainder = (remainder << 1) ^ ((remainder >> 11) * VERSION_GENERATOR);
+    }
+    (data << 12) | remainder
+}
+
+/// 读取并纠正格式信息，两份副本中取汉明距离最小者（最多容忍 3 位错误，未知模块不计入）
+pub fn read_format_info(matrix: &ModuleMatrix) -> Option<FormatInfo> {
+    version_from_size(matrix.size)?;
+
+    // 每份副本记录 (位值, 已知位掩码)，未知模块不参与距离计算
+    let copies: Vec<(u32, u32)> = format_info_positions(matrix.size)
+        .iter()
+        .map(|positions| read_bits(matrix, positions.iter().cloned()))
+        .filter(|&(_, known)| known.count_ones() >= MIN_KNOWN_INFO_BITS)
+        .collect();
+
+    let mut best: Option<FormatInfo> = None;
+    for &ec_level in &[EcLevel::L, EcLevel::M, EcLevel::Q, EcLevel::H] {
+        for mask in 0..8u8 {
+            let expected = encode_format_bits(ec_level, mask);
+            let distance = copies
+                .iter()
+                .map(|&(bits, known)| ((bits ^ expected) & known).count_ones())
+                .min()
+                .unwrap_or(u32::MAX);
+            if best.is_none_or(|b| distance < b.distance) {
+                best = Some(FormatInfo { ec_level, mask, distance });
+            }
+        }
+    }
+
+    best.filter(|info| info.distance <= 3)
+}
+
+/// 读取并纠正版本信息（仅版本 7 及以上有效，最多容忍 3 位错误）
+pub fn read_version_info(matrix: &ModuleMatrix) -> Option<u8> {
+    let positions = version_info_positions(matrix.size);
+    if positions.is_empty() {
+        return None;
+    }
+
+    let copies: Vec<(u32, u32)> = (0..2)
+        .map(|copy| read_bits(matrix, positions.iter().map(|pair| pair[copy])))
+        .filter(|&(_, known)| known.count_ones() >= MIN_KNOWN_INFO_BITS)
+        .collect();
+
+    (7..=40u8)
+        .map(|version| {
+            let expected = encode_version_bits(version);
+            let distance = copies
+                .iter()
+                .map(|&(bits, known)| ((bits ^ expected) & known).count_ones())
+                .min()
+                .unwrap_or(u32::MAX);
+            (version, distance)
+        })
+        .min_by_key(|&(_, distance)| distance)
+        .filter(|&(_, distance)| distance <= 3)
+        .map(|(version, _)| version)
+}
+
+/// 按位置顺序读取位值，返回 (位值, 已知位掩码)
+fn read_bits(matrix: &ModuleMatrix, positions: impl Iterator<Item = (usize, usize)>) -> (u32, u32) {
+    positions.enumerate().fold((0u32, 0u32), |(bits, known), (i, (x, y))| {
+        if matrix.is_unknown(x, y) {
+            (bits, known)
+        } else {
+            (bits | ((matrix.get(x, y) as u32) << i), known | (1 << i))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alignment_positions() {
+        assert!(alignment_positions(1).is_empty());
+        assert_eq!(alignment_positions(2), vec![6, 18]);
+        assert_eq!(alignment_positions(7), vec![6, 22, 38]);
+        assert_eq!(alignment_positions(32), vec![6, 34, 60, 86, 112, 138]);
+        assert_eq!(alignment_positions(40), vec![6, 30, 58, 86, 114, 142, 170]);
+    }
+
+    #[test]
+    fn test_encode_bits() {
+        // ISO/IEC 18004 附录 C 中的示例值
+        assert_eq!(encode_format_bits(EcLevel::M, 5), 0x40CE);
+        assert_eq!(encode_version_bits(7), 0x07C94);
+    }
+
+    #[test]
+    fn test_format_info_roundtrip_with_errors() {
+        let mut matrix = ModuleMatrix::new(25);
+        let bits = encode_format_bits(EcLevel::Q, 3);
+        for positions in format_info_positions(25).iter() {
+            for (i, &(x, y)) in positions.iter().enumerate() {
+                matrix.set(x, y, (bits >> i) & 1 == 1);
+            }
+        }
+        // 破坏第一份副本中的两位
+        let (x, y) = format_info_positions(25)[0][2];
+        matrix.set(x, y, !matrix.get(x, y));
+        let (x, y) = format_info_positions(25)[0][9];
+        matrix.set(x, y, !matrix.get(x, y));
+
+        let info = read_format_info(&matrix).unwrap();
+        assert_eq!(info.ec_level, EcLevel::Q);
+        assert_eq!(info.mask, 3);
+        assert_eq!(info.distance, 0);
+    }
+
+    #[test]
+    fn test_module_roles() {
+        let roles = module_roles(25);
+        assert_eq!(roles[0], ModuleRole::Finder);
+        assert_eq!(roles[7 * 25 + 7], ModuleRole::Separator);
+        assert_eq!(roles[6 * 25 + 10], ModuleRole::Timing);
+        assert_eq!(roles[18 * 25 + 18], ModuleRole::Alignment);
+        assert_eq!(roles[8 * 25 + 2], ModuleRole::FormatInfo);
+        assert_eq!(roles[(25 - 8) * 25 + 8], ModuleRole::DarkModule);
+        assert_eq!(roles[24 * 25 + 24], ModuleRole::Data);
+
+        // 版本 2 共有 25*25 - 功能图形 = 359 个数据模块 (44 码字 * 8 + 7 剩余位)
+        let data_modules = roles.iter().filter(|&&r| r == ModuleRole::Data).count();
+        assert_eq!(data_modules, 359);
+    }
+}
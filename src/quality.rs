@@ -0,0 +1,436 @@
+//! 图像质量评估模块
+//!
+//! 用户往往不知道该用 `--preprocess`、增强解码还是 `--brute-force`。本模块先测量图像的
+//! 模糊程度（拉普拉斯方差）、曝光、噪声、对比度、主色调和二维码模块尺度，归纳出问题，
+//! 再据此选择升级路径：是否先做预处理、增强解码使用哪个内置变换配置、失败后是否进入
+//! 暴力破解。
+
+use opencv::{
+    core::{self, Mat},
+    imgproc::{cvt_color, COLOR_BGR2GRAY},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+use crate::halftone;
+use crate::types::ProcessingConfig;
+
+/// 拉普拉斯方差低于该值视为模糊
+const BLUR_THRESHOLD: f64 = 100.0;
+
+/// 平均亮度低于该值视为欠曝
+const DARK_MEAN: f64 = 60.0;
+
+/// 平均亮度高于该值视为过曝
+const BRIGHT_MEAN: f64 = 200.0;
+
+/// 近黑（<= 5）或近白（>= 250）像素比例超过该值视为欠曝或过曝
+const CLIPPED_FRACTION: f64 = 0.25;
+
+/// 噪声标准差超过该值视为噪声较大
+const NOISE_THRESHOLD: f64 = 8.0;
+
+/// 估计噪声时，梯度 |gx| + |gy| 超过该值的像素视为边缘并跳过
+const EDGE_GRADIENT: i32 = 64;
+
+/// 灰度标准差低于该值视为低对比度
+const LOW_CONTRAST: f64 = 30.0;
+
+/// 平均饱和度超过该值时视为彩色二维码并报告主色调
+const COLOR_SATURATION: f64 = 0.25;
+
+/// 主色调直方图的每格宽度（度）
+const HUE_BIN_DEGREES: f64 = 10.0;
+
+/// 模块间距低于该值（像素）视为码太小
+const SMALL_MODULE: f64 = 3.0;
+
+/// 除彩色外的问题达到该数目时预先启用暴力破解
+const ESCALATE_ISSUES: usize = 2;
+
+/// 测量值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QualityMetrics {
+    /// 清晰度：4 邻域拉普拉斯响应的方差
+    pub sharpness: f64,
+    /// 平均灰度 (0-255)
+    pub brightness: f64,
+    /// 近黑像素比例
+    pub dark_fraction: f64,
+    /// 近白像素比例
+    pub bright_fraction: f64,
+    /// 噪声标准差估计（非边缘区域）
+    pub noise: f64,
+    /// 对比度：灰度标准差
+    pub contrast: f64,
+    /// 平均饱和度 (0-1)
+    pub saturation: f64,
+    /// 主色调（度，0-360），灰度图或饱和度低时为 None
+    pub dominant_hue: Option<f64>,
+    /// 从定位图形估计的模块间距（像素），找不到定位图形时为 None
+    pub module_pitch: Option<f64>,
+}
+
+/// 评估出的问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QualityIssue {
+    /// 模糊
+    Blurry,
+    /// 欠曝
+    Underexposed,
+    /// 过曝或大面积反光
+    Overexposed,
+    /// 噪声大
+    Noisy,
+    /// 对比度低
+    LowContrast,
+    /// 彩色或非黑白配色
+    Colored,
+    /// 模块太小
+    SmallModules,
+    /// 找不到定位图形
+    NoFinderPattern,
+}
+
+impl QualityIssue {
+    /// 获取问题的描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            QualityIssue::Blurry => "模糊",
+            QualityIssue::Underexposed => "欠曝",
+            QualityIssue::Overexposed => "过曝或反光",
+            QualityIssue::Noisy => "噪声大",
+            QualityIssue::LowContrast => "对比度低",
+            QualityIssue::Colored => "彩色配色",
+            QualityIssue::SmallModules => "模块太小",
+            QualityIssue::NoFinderPattern => "未找到定位图形",
+        }
+    }
+}
+
+/// 根据问题选择的升级路径
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recommendation {
+    /// 是否先做预处理（灰度、去噪和直方图均衡化）
+    pub preprocess: bool,
+    /// 增强解码使用的内置变换配置，None 时使用内置序列
+    pub profile: Option<String>,
+    /// 增强解码失败后是否进入暴力破解
+    pub brute_force: bool,
+}
+
+impl Recommendation {
+    /// 升级路径的描述，如 "预处理 → 增强解码 (print-scan) → 暴力破解"
+    pub fn description(&self) -> String {
+        let mut stages = Vec::new();
+        if self.preprocess {
+            stages.push("预处理".to_string());
+        }
+        stages.push(match &self.profile {
+            Some(profile) => format!("增强解码 ({})", profile),
+            None => "增强解码".to_string(),
+        });
+        if self.brute_force {
+            stages.push("暴力破解".to_string());
+        }
+        stages.join(" → ")
+    }
+
+    /// 把建议合并到配置中，用户显式指定的流水线、配置名和开关优先
+    pub fn apply(&self, config: &ProcessingConfig) -> ProcessingConfig {
+        let mut config = config.clone();
+        config.preprocess |= self.preprocess;
+        config.brute_force |= self.brute_force;
+        if config.pipeline.is_none() && config.profile.is_none() {
+            config.profile = self.profile.clone();
+        }
+        config
+    }
+}
+
+/// 质量评估报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QualityReport {
+    /// 测量值
+    pub metrics: QualityMetrics,
+    /// 评估出的问题
+    pub issues: Vec<QualityIssue>,
+    /// 选择的升级路径
+    pub recommendation: Recommendation,
+}
+
+impl QualityReport {
+    /// 测量值的单行摘要
+    pub fn summary(&self) -> String {
+        let metrics = &self.metrics;
+        format!(
+            "清晰度 {:.0}，亮度 {:.0}，噪声 {:.1}，对比度 {:.0}，主色调 {}，模块 {}",
+            metrics.sharpness,
+            metrics.brightness,
+            metrics.noise,
+            metrics.contrast,
+            metrics.dominant_hue.map_or_else(|| "无".to_string(), |hue| format!("{:.0}°", hue)),
+            metrics.module_pitch.map_or_else(|| "未知".to_string(), |pitch| format!("{:.1} 像素", pitch)),
+        )
+    }
+
+    /// 问题列表的描述
+    pub fn issue_description(&self) -> String {
+        if self.issues.is_empty() {
+            return "无".to_string();
+        }
+        self.issues.iter().map(QualityIssue::description).collect::<Vec<_>>().join("、")
+    }
+}
+
+/// 评估图像质量并选择升级路径
+pub fn assess(image: &Mat) -> Result<QualityReport> {
+    let gray = if image.channels() == 1 {
+        image.clone()
+    } else {
+        let mut gray = Mat::default();
+        cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+        gray
+    };
+    let (rows, cols) = (gray.rows() as usize, gray.cols() as usize);
+
+    let (saturation, dominant_hue) = if image.channels() == 3 {
+        color_statistics(image.data_bytes()?)
+    } else {
+        (0.0, None)
+    };
+    let metrics = measure_gray(gray.data_bytes()?, rows, cols, saturation, dominant_hue, halftone::module_pitch(&gray)?);
+    let issues = diagnose(&metrics);
+    let recommendation = recommend(&issues);
+    Ok(QualityReport { metrics, issues, recommendation })
+}
+
+/// 在灰度像素上测量清晰度、曝光、噪声和对比度
+pub fn measure_gray(
+    gray: &[u8],
+    rows: usize,
+    cols: usize,
+    saturation: f64,
+    dominant_hue: Option<f64>,
+    module_pitch: Option<f64>,
+) -> QualityMetrics {
+    let count = gray.len().max(1) as f64;
+    let brightness = gray.iter().map(|&p| p as f64).sum::<f64>() / count;
+    let variance = gray.iter().map(|&p| (p as f64 - brightness).powi(2)).sum::<f64>() / count;
+    QualityMetrics {
+        sharpness: laplacian_variance(gray, rows, cols),
+        brightness,
+        dark_fraction: gray.iter().filter(|&&p| p <= 5).count() as f64 / count,
+        bright_fraction: gray.iter().filter(|&&p| p >= 250).count() as f64 / count,
+        noise: noise_sigma(gray, rows, cols),
+        contrast: variance.sqrt(),
+        saturation,
+        dominant_hue,
+        module_pitch,
+    }
+}
+
+/// 4 邻域拉普拉斯响应的方差，越小越模糊
+pub fn laplacian_variance(gray: &[u8], rows: usize, cols: usize) -> f64 {
+    if rows < 3 || cols < 3 {
+        return 0.0;
+    }
+    // 单遍累加响应的和与平方和，不为整幅图像分配响应缓冲区；响应为整数，按整数累加没有精度损失
+    let at = |y: usize, x: usize| gray[y * cols + x] as i64;
+    let (mut sum, mut sum_sq) = (0i128, 0i128);
+    for y in 1..rows - 1 {
+        for x in 1..cols - 1 {
+            let response = at(y - 1, x) + at(y + 1, x) + at(y, x - 1) + at(y, x + 1) - 4 * at(y, x);
+            sum += response as i128;
+            sum_sq += (response * response) as i128;
+        }
+    }
+    let count = ((rows - 2) * (cols - 2)) as i128;
+    (count * sum_sq - sum * sum) as f64 / (count * count) as f64
+}
+
+/// Immerkær 快速噪声估计，跳过梯度大的边缘像素，避免把二维码的模块边缘算作噪声
+pub fn noise_sigma(gray: &[u8], rows: usize, cols: usize) -> f64 {
+    if rows < 3 || cols < 3 {
+        return 0.0;
+    }
+    let at = |y: usize, x: usize| gray[y * cols + x] as i32;
+    let (mut total, mut count) = (0.0, 0usize);
+    for y in 1..rows - 1 {
+        for x in 1..cols - 1 {
+            let gx = at(y - 1, x + 1) + 2 * at(y, x + 1) + at(y + 1, x + 1) - at(y - 1, x - 1) - 2 * at(y, x - 1) - at(y + 1, x - 1);
+            let gy = at(y + 1, x - 1) + 2 * at(y + 1, x) + at(y + 1, x + 1) - at(y - 1, x - 1) - 2 * at(y - 1, x) - at(y - 1, x + 1);
+            if gx.abs() + gy.abs() > EDGE_GRADIENT {
+                continue;
+            }
+            // 掩码 [1 -2 1; -2 4 -2; 1 -2 1]
+            let response = at(y - 1, x - 1) + at(y - 1, x + 1) + at(y + 1, x - 1) + at(y + 1, x + 1)
+                - 2 * (at(y - 1, x) + at(y + 1, x) + at(y, x - 1) + at(y, x + 1))
+                + 4 * at(y, x);
+            total += response.abs() as f64;
+            count += 1;
+        }
+    }
+    if count == 0 {
+        return 0.0;
+    }
+    (std::f64::consts::PI / 2.0).sqrt() * total / (6.0 * count as f64)
+}
+
+/// BGR 像素的平均饱和度和按饱和度加权的主色调
+pub fn color_statistics(bgr: &[u8]) -> (f64, Option<f64>) {
+    let bins = (360.0 / HUE_BIN_DEGREES) as usize;
+    let mut histogram = vec![0.0; bins];
+    let mut saturation_sum = 0.0;
+    let pixels = bgr.chunks_exact(3);
+    let count = pixels.len().max(1) as f64;
+    for pixel in pixels {
+        let (b, g, r) = (pixel[0] as f64, pixel[1] as f64, pixel[2] as f64);
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        if chroma <= 0.0 {
+            continue;
+        }
+        let saturation = chroma / max;
+        let hue = if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        saturation_sum += saturation;
+        histogram[((hue / HUE_BIN_DEGREES) as usize).min(bins - 1)] += saturation;
+    }
+
+    let saturation = saturation_sum / count;
+    if saturation < COLOR_SATURATION {
+        return (saturation, None);
+    }
+    let peak = (0..bins).max_by(|&a, &b| histogram[a].total_cmp(&histogram[b])).unwrap_or(0);
+    (saturation, Some((peak as f64 + 0.5) * HUE_BIN_DEGREES))
+}
+
+/// 按阈值归纳问题
+pub fn diagnose(metrics: &QualityMetrics) -> Vec<QualityIssue> {
+    let mut issues = Vec::new();
+    if metrics.sharpness < BLUR_THRESHOLD {
+        issues.push(QualityIssue::Blurry);
+    }
+    if metrics.brightness < DARK_MEAN || metrics.dark_fraction > CLIPPED_FRACTION {
+        issues.push(QualityIssue::Underexposed);
+    }
+    if metrics.brightness > BRIGHT_MEAN || metrics.bright_fraction > CLIPPED_FRACTION {
+        issues.push(QualityIssue::Overexposed);
+    }
+    if metrics.noise > NOISE_THRESHOLD {
+        issues.push(QualityIssue::Noisy);
+    }
+    if metrics.contrast < LOW_CONTRAST {
+        issues.push(QualityIssue::LowContrast);
+    }
+    if metrics.dominant_hue.is_some() {
+        issues.push(QualityIssue::Colored);
+    }
+    match metrics.module_pitch {
+        Some(pitch) if pitch < SMALL_MODULE => issues.push(QualityIssue::SmallModules),
+        Some(_) => {}
+        None => issues.push(QualityIssue::NoFinderPattern),
+    }
+    issues
+}
+
+/// 根据问题选择升级路径
+///
+/// 彩色配色优先使用 art-qr，模糊使用带去模糊的 screen-photo，曝光、噪声和对比度问题
+/// 使用带光照校正的 print-scan。模糊图像不做预处理，因为预处理中的高斯模糊会让它更糊。
+pub fn recommend(issues: &[QualityIssue]) -> Recommendation {
+    use QualityIssue::*;
+
+    let has = |issue| issues.contains(&issue);
+    let profile = if has(Colored) {
+        Some("art-qr")
+    } else if has(Blurry) {
+        Some("screen-photo")
+    } else if has(Underexposed) || has(Overexposed) || has(Noisy) || has(LowContrast) {
+        Some("print-scan")
+    } else {
+        None
+    };
+    let degradations = issues.iter().filter(|&&issue| issue != Colored).count();
+    Recommendation {
+        preprocess: (has(Noisy) || has(LowContrast)) && !has(Blurry),
+        profile: profile.map(str::to_string),
+        brute_force: has(NoFinderPattern) || has(SmallModules) || degradations >= ESCALATE_ISSUES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 8 像素一格的棋盘格，`blur` 次 3x3 均值滤波
+    fn checkerboard(size: usize, blur: usize) -> Vec<u8> {
+        let mut image: Vec<f64> = (0..size * size)
+            .map(|i| if (i / size / 8 + i % size / 8) % 2 == 1 { 220.0 } else { 30.0 })
+            .collect();
+        for _ in 0..blur {
+            let previous = image.clone();
+            for y in 1..size - 1 {
+                for x in 1..size - 1 {
+                    let sum: f64 = (0..9).map(|k| previous[(y + k / 3 - 1) * size + x + k % 3 - 1]).sum();
+                    image[y * size + x] = sum / 9.0;
+                }
+            }
+        }
+        image.into_iter().map(|v| v.round() as u8).collect()
+    }
+
+    #[test]
+    fn test_sharpness_and_noise() {
+        let sharp = checkerboard(64, 0);
+        let blurred = checkerboard(64, 6);
+        assert!(laplacian_variance(&sharp, 64, 64) > BLUR_THRESHOLD);
+        assert!(laplacian_variance(&blurred, 64, 64) < laplacian_variance(&sharp, 64, 64) / 10.0);
+        assert_eq!(laplacian_variance(&[7; 25], 5, 5), 0.0);
+        // 两个响应 -4 和 1，方差 6.25
+        let mut impulse = [0u8; 12];
+        impulse[4] = 1;
+        assert_eq!(laplacian_variance(&impulse, 4, 3), 6.25);
+
+        // 模块边缘不算噪声，平坦区域上的抖动才算
+        assert!(noise_sigma(&sharp, 64, 64) < 1.0);
+        let noisy: Vec<u8> = (0..64 * 64).map(|i| if (i * 7919) % 13 < 6 { 110 } else { 140 }).collect();
+        assert!(noise_sigma(&noisy, 64, 64) > NOISE_THRESHOLD);
+    }
+
+    #[test]
+    fn test_color_statistics() {
+        // 蓝底白码
+        let bgr: Vec<u8> = (0..100).flat_map(|i| if i % 4 == 0 { [255, 255, 255] } else { [200, 60, 20] }).collect();
+        let (saturation, hue) = color_statistics(&bgr);
+        assert!(saturation > COLOR_SATURATION);
+        let hue = hue.unwrap();
+        assert!((220.0..=230.0).contains(&hue), "{}", hue);
+
+        let gray: Vec<u8> = (0..100).flat_map(|i| [i as u8; 3]).collect();
+        assert_eq!(color_statistics(&gray), (0.0, None));
+    }
+
+    #[test]
+    fn test_recommendation() {
+        use QualityIssue::*;
+
+        assert_eq!(recommend(&[]), Recommendation { preprocess: false, profile: None, brute_force: false });
+        let blurry = recommend(&[Blurry, Noisy]);
+        assert_eq!(blurry.profile.as_deref(), Some("screen-photo"));
+        assert!(!blurry.preprocess && blurry.brute_force);
+        let colored = recommend(&[Colored, LowContrast]);
+        assert_eq!(colored.profile.as_deref(), Some("art-qr"));
+        assert!(colored.preprocess && !colored.brute_force);
+        assert_eq!(colored.description(), "预处理 → 增强解码 (art-qr)");
+        assert!(recommend(&[NoFinderPattern]).brute_force);
+    }
+}
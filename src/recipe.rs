@@ -0,0 +1,121 @@
+//! 变换配方模块
+//!
+//! 暴力破解成功时的参数组合（镜像、旋转、缩放、通道、对比度、亮度、模糊、光照校正和
+//! 二值化）称为一条配方。同一台相机拍摄的一批图像通常需要相同的处理，因此把成功的
+//! 配方连同成功次数保存到 JSON 文件，之后的图像先按成功次数从高到低重放这些配方，
+//! 都失败时才进入完整搜索。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::brute_force_decoder::TransformParams;
+use crate::error::{QRDecodeError, Result};
+
+/// 一条成功过的变换配方
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recipe {
+    /// 暴力破解的参数组合
+    pub params: TransformParams,
+    /// 成功解码的次数
+    #[serde(default)]
+    pub successes: usize,
+}
+
+/// 配方集合
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecipeBook {
+    /// 所有配方，按记录顺序排列
+    pub recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    /// 从 JSON 文件加载配方
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            QRDecodeError::invalid_input(format!("无法读取配方文件 {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text)
+            .map_err(|e| QRDecodeError::invalid_input(format!("配方文件 {} 无效: {}", path.display(), e)))
+    }
+
+    /// 解析 JSON 格式的配方
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// 以 JSON 格式保存配方
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| QRDecodeError::output_error(format!("配方序列化失败: {}", e)))?;
+        std::fs::write(path, json)
+            .map_err(|e| QRDecodeError::output_error(format!("无法写入配方文件 {}: {}", path.display(), e)))
+    }
+
+    /// 记录一次成功，已有相同配方时只增加成功次数
+    pub fn record(&mut self, params: &TransformParams) {
+        match self.recipes.iter_mut().find(|recipe| recipe.params == *params) {
+            Some(recipe) => recipe.successes += 1,
+            None => self.recipes.push(Recipe {
+                params: params.clone(),
+                successes: 1,
+            }),
+        }
+    }
+
+    /// 按成功次数从高到低排列的配方，次数相同时保持记录顺序
+    pub fn ordered(&self) -> Vec<TransformParams> {
+        let mut recipes: Vec<&Recipe> = self.recipes.iter().collect();
+        recipes.sort_by_key(|recipe| std::cmp::Reverse(recipe.successes));
+        recipes.into_iter().map(|recipe| recipe.params.clone()).collect()
+    }
+
+    /// 配方数量
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    /// 是否没有配方
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binarization::BinarizationMethod;
+    use crate::geometry::Mirror;
+
+    fn rotated(rotation: i32) -> TransformParams {
+        TransformParams {
+            rotation,
+            mirror: Mirror::Horizontal,
+            binary: true,
+            binarization: BinarizationMethod::Sauvola { window: 31, k: 0.34 },
+            ..TransformParams::default()
+        }
+    }
+
+    #[test]
+    fn test_record_orders_by_successes() {
+        let mut book = RecipeBook::default();
+        book.record(&rotated(30));
+        book.record(&rotated(90));
+        book.record(&rotated(90));
+        book.record(&rotated(180));
+
+        assert_eq!(book.len(), 3);
+        assert_eq!(book.ordered(), vec![rotated(90), rotated(30), rotated(180)]);
+    }
+
+    #[test]
+    fn test_round_trip_json() {
+        let mut book = RecipeBook::default();
+        book.record(&rotated(45));
+        let json = serde_json::to_string(&book).unwrap();
+        assert_eq!(RecipeBook::parse(&json).unwrap(), book);
+
+        assert!(RecipeBook::parse("{\"recipes\": [{\"successes\": 1}]}").is_err());
+    }
+}
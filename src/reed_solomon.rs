@@ -0,0 +1,402 @@
+//! Reed-Solomon 编解码模块
+//!
+//! 实现二维码使用的 GF(256) Reed-Solomon 纠错码（本原多项式 0x11D，生成元根从 α^0 开始），
+//! 支持同时纠正错误和擦除（已知位置的不可读码字）。
+
+use crate::error::{QRDecodeError, Result};
+
+/// GF(256) 本原多项式
+const PRIMITIVE: u16 = 0x11D;
+
+/// GF(256) 指数表和对数表
+struct GaloisField {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+impl GaloisField {
+    const fn new() -> Self {
+        let mut exp = [0u8; 512];
+        let mut log = [0u8; 256];
+        let mut x: u16 = 1;
+        let mut i = 0;
+        while i < 255 {
+            exp[i] = x as u8;
+            log[x as usize] = i as u8;
+            x <<= 1;
+            if x & 0x100 != 0 {
+                x ^= PRIMITIVE;
+            }
+            i += 1;
+        }
+        while i < 512 {
+            exp[i] = exp[i - 255];
+            i += 1;
+        }
+        Self { exp, log }
+    }
+}
+
+static GF: GaloisField = GaloisField::new();
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    GF.exp[GF.log[a as usize] as usize + GF.log[b as usize] as usize]
+}
+
+fn gf_div(a: u8, b: u8) -> u8 {
+    debug_assert!(b != 0, "GF(256) 除数不能为 0");
+    if a == 0 {
+        return 0;
+    }
+    GF.exp[(GF.log[a as usize] as usize + 255 - GF.log[b as usize] as usize) % 255]
+}
+
+fn gf_pow2(power: usize) -> u8 {
+    GF.exp[power % 255]
+}
+
+fn gf_inverse(a: u8) -> u8 {
+    GF.exp[255 - GF.log[a as usize] as usize]
+}
+
+/// 多项式求值（系数按最高次在前存储）
+fn poly_eval(poly: &[u8], x: u8) -> u8 {
+    poly.iter().fold(0, |acc, &coef| gf_mul(acc, x) ^ coef)
+}
+
+/// 多项式相乘（系数按最高次在前存储）
+fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; p.len() + q.len() - 1];
+    for (i, &a) in p.iter().enumerate() {
+        for (j, &b) in q.iter().enumerate() {
+            result[i + j] ^= gf_mul(a, b);
+        }
+    }
+    result
+}
+
+/// 纠错统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RsCorrection {
+    /// 参与纠正的擦除数：给定的擦除位置全部计入，不论读到的值是否恰好正确
+    pub erasures: usize,
+    /// 纠正的错误数（位置未知，只计实际改变了值的码字）
+    pub errors: usize,
+}
+
+impl RsCorrection {
+    /// 擦除数与错误数之和
+    pub fn total(&self) -> usize {
+        self.erasures + self.errors
+    }
+}
+
+/// 生成多项式 (x - α^0)(x - α^1)...(x - α^(n-1))
+pub fn generator_poly(ecc_len: usize) -> Vec<u8> {
+    let mut generator = vec![1u8];
+    for i in 0..ecc_len {
+        generator = poly_mul(&generator, &[1, gf_pow2(i)]);
+    }
+    generator
+}
+
+/// 计算数据码字对应的纠错码字
+pub fn encode(data: &[u8], ecc_len: usize) -> Vec<u8> {
+    let generator = generator_poly(ecc_len);
+    let mut remainder = vec![0u8; ecc_len];
+    for &byte in data {
+        let factor = byte ^ remainder[0];
+        remainder.remove(0);
+        remainder.push(0);
+        for (r, &g) in remainder.iter_mut().zip(generator.iter().skip(1)) {
+            *r ^= gf_mul(g, factor);
+        }
+    }
+    remainder
+}
+
+/// 计算校正子 S_j = r(α^j)，全部为 0 表示码字无误
+pub fn syndromes(codeword: &[u8], ecc_len: usize) -> Vec<u8> {
+    (0..ecc_len).map(|j| poly_eval(codeword, gf_pow2(j))).collect()
+}
+
+/// 原地纠正码块（数据码字在前，纠错码字在后）
+///
+/// `erasures` 为已知不可读码字在块内的下标。要求 `2 * 错误数 + 擦除数 <= ecc_len`。
+pub fn decode(codeword: &mut [u8], ecc_len: usize, erasures: &[usize]) -> Result<RsCorrection> {
+    let n = codeword.len();
+    if ecc_len == 0 || ecc_len >= n {
+        return Err(QRDecodeError::decode_error(format!(
+            "无效的 Reed-Solomon 参数: 码长 {}, 纠错码字 {}",
+            n, ecc_len
+        )));
+    }
+    if let Some(&pos) = erasures.iter().find(|&&p| p >= n) {
+        return Err(QRDecodeError::decode_error(format!("擦除位置 {} 超出码长 {}", pos, n)));
+    }
+
+    let mut erasures: Vec<usize> = erasures.to_vec();
+    erasures.sort_unstable();
+    erasures.dedup();
+    if erasures.len() > ecc_len {
+        return Err(QRDecodeError::decode_error(format!(
+            "擦除数 {} 超过纠错能力 {}",
+            erasures.len(),
+            ecc_len
+        )));
+    }
+
+    // 擦除位置的码字值不可信，先置 0
+    for &pos in &erasures {
+        codeword[pos] = 0;
+    }
+
+    let synd = syndromes(codeword, ecc_len);
+    if synd.iter().all(|&s| s == 0) {
+        return Ok(RsCorrection { erasures: erasures.len(), errors: 0 });
+    }
+
+    // Forney 校正子：消去擦除的影响后再用 Berlekamp-Massey 求错误位置
+    let forney = forney_syndromes(&synd, &erasures, n);
+    let error_locator = find_error_locator(&forney, ecc_len - erasures.len())?;
+    let mut error_positions = find_error_positions(&error_locator, n)?;
+    error_positions.retain(|pos| !erasures.contains(pos));
+
+    if 2 * error_positions.len() + erasures.len() > ecc_len {
+        return Err(QRDecodeError::decode_error(format!(
+            "错误过多无法纠正: {} 个错误, {} 个擦除, 纠错码字 {}",
+            error_positions.len(),
+            erasures.len(),
+            ecc_len
+        )));
+    }
+
+    let mut all_positions = erasures.clone();
+    all_positions.extend_from_slice(&error_positions);
+    correct_errata(codeword, &synd, &all_positions)?;
+
+    if syndromes(codeword, ecc_len).iter().any(|&s| s != 0) {
+        return Err(QRDecodeError::decode_error("Reed-Solomon 纠错后校验失败".to_string()));
+    }
+
+    Ok(RsCorrection {
+        erasures: erasures.len(),
+        errors: error_positions.len(),
+    })
+}
+
+/// 码字下标对应的多项式次数
+fn degree_of(pos: usize, n: usize) -> usize {
+    n - 1 - pos
+}
+
+fn forney_syndromes(synd: &[u8], erasures: &[usize], n: usize) -> Vec<u8> {
+    let mut forney = synd.to_vec();
+    for &pos in erasures {
+        let x = gf_pow2(degree_of(pos, n));
+        for j in 0..forney.len() - 1 {
+            forney[j] = gf_mul(forney[j], x) ^ forney[j + 1];
+        }
+        forney.pop();
+    }
+    forney
+}
+
+/// Berlekamp-Massey 算法求错误定位多项式（最高次在前）
+fn find_error_locator(synd: &[u8], ecc_left: usize) -> Result<Vec<u8>> {
+    let mut locator = vec![1u8];
+    let mut previous = vec![1u8];
+
+    for k in 0..synd.len() {
+        let mut delta = synd[k];
+        for j in 1..locator.len() {
+            delta ^= gf_mul(locator[locator.len() - 1 - j], synd[k - j]);
+        }
+
+        previous.push(0);
+        if delta != 0 {
+            if previous.len() > locator.len() {
+                let new_locator: Vec<u8> = previous.iter().map(|&c| gf_mul(c, delta)).collect();
+                previous = locator.iter().map(|&c| gf_mul(c, gf_inverse(delta))).collect();
+                locator = new_locator;
+            }
+            let scaled: Vec<u8> = previous.iter().map(|&c| gf_mul(c, delta)).collect();
+            locator = poly_add(&locator, &scaled);
+        }
+    }
+
+    while locator.len() > 1 && locator[0] == 0 {
+        locator.remove(0);
+    }
+
+    let errors = locator.len() - 1;
+    if errors * 2 > ecc_left {
+        return Err(QRDecodeError::decode_error(format!(
+            "错误过多无法纠正: 至少 {} 个错误",
+            errors
+        )));
+    }
+    Ok(locator)
+}
+
+fn poly_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let len = p.len().max(q.len());
+    let mut result = vec![0u8; len];
+    for (i, &c) in p.iter().enumerate() {
+        result[i + len - p.len()] = c;
+    }
+    for (i, &c) in q.iter().enumerate() {
+        result[i + len - q.len()] ^= c;
+    }
+    result
+}
+
+/// Chien 搜索：找出错误定位多项式的根对应的码字下标
+fn find_error_positions(locator: &[u8], n: usize) -> Result<Vec<usize>> {
+    let errors = locator.len() - 1;
+    let positions: Vec<usize> = (0..n)
+        .filter(|&pos| poly_eval(locator, gf_inverse(gf_pow2(degree_of(pos, n)))) == 0)
+        .collect();
+
+    if positions.len() != errors {
+        return Err(QRDecodeError::decode_error(format!(
+            "无法定位全部错误: 期望 {} 个, 找到 {} 个",
+            errors,
+            positions.len()
+        )));
+    }
+    Ok(positions)
+}
+
+/// Forney 算法计算错误值并修正
+///
+/// 生成元根从 α^0 开始时，错误值 e_k = Ω(X_k⁻¹) / Π_{i≠k}(1 + X_i·X_k⁻¹)。
+fn correct_errata(codeword: &mut [u8], synd: &[u8], positions: &[usize]) -> Result<()> {
+    let n = codeword.len();
+    let locations: Vec<u8> = positions.iter().map(|&pos| gf_pow2(degree_of(pos, n))).collect();
+
+    // 错误定位多项式 Λ(x) = Π(1 + X_i x)，低次在前
+    let mut locator = vec![1u8];
+    for &x in &locations {
+        locator = poly_mul(&locator, &[1, x]);
+    }
+
+    // 错误评估多项式 Ω(x) = S(x) Λ(x) mod x^(校正子个数)，低次在前
+    let mut evaluator = poly_mul(synd, &locator);
+    evaluator.truncate(synd.len());
+    evaluator.reverse();
+
+    for (k, &pos) in positions.iter().enumerate() {
+        let x_inv = gf_inverse(locations[k]);
+
+        let mut denominator = 1u8;
+        for (i, &other) in locations.iter().enumerate() {
+            if i != k {
+                denominator = gf_mul(denominator, 1 ^ gf_mul(other, x_inv));
+            }
+        }
+        if denominator == 0 {
+            return Err(QRDecodeError::decode_error("Forney 算法分母为 0".to_string()));
+        }
+
+        codeword[pos] ^= gf_div(poly_eval(&evaluator, x_inv), denominator);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_block() -> (Vec<u8>, usize) {
+        // 版本 1-M 的示例数据 ("01234567")
+        let data = vec![0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11];
+        let ecc_len = 10;
+        let mut block = data.clone();
+        block.extend(encode(&data, ecc_len));
+        (block, ecc_len)
+    }
+
+    #[test]
+    fn test_encode_known_vector() {
+        let (block, ecc_len) = sample_block();
+        assert_eq!(&block[16..], &[0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55]);
+        assert!(syndromes(&block, ecc_len).iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn test_correct_errors() {
+        let (original, ecc_len) = sample_block();
+        let mut damaged = original.clone();
+        for &pos in &[0, 7, 15, 20, 25] {
+            damaged[pos] ^= 0x5A;
+        }
+
+        let correction = decode(&mut damaged, ecc_len, &[]).unwrap();
+        assert_eq!(damaged, original);
+        assert_eq!(correction, RsCorrection { erasures: 0, errors: 5 });
+    }
+
+    #[test]
+    fn test_correct_erasures_beyond_error_capacity() {
+        let (original, ecc_len) = sample_block();
+        let mut damaged = original.clone();
+        let erasures = [1, 3, 5, 8, 9, 12, 17, 19, 22, 24];
+        for &pos in &erasures {
+            damaged[pos] = 0xFF;
+        }
+
+        let correction = decode(&mut damaged, ecc_len, &erasures).unwrap();
+        assert_eq!(damaged, original);
+        assert_eq!(correction.erasures, 10);
+        assert_eq!(correction.errors, 0);
+    }
+
+    #[test]
+    fn test_correct_mixed_errors_and_erasures() {
+        let (original, ecc_len) = sample_block();
+        let mut damaged = original.clone();
+        let erasures = [2, 4, 6, 10];
+        for &pos in &erasures {
+            damaged[pos] = 0;
+        }
+        damaged[13] ^= 0x33;
+        damaged[21] ^= 0x01;
+        damaged[25] ^= 0x80;
+
+        let correction = decode(&mut damaged, ecc_len, &erasures).unwrap();
+        assert_eq!(damaged, original);
+        assert_eq!(correction.errors, 3);
+    }
+
+    #[test]
+    fn test_too_many_errors() {
+        let (original, ecc_len) = sample_block();
+        let mut damaged = original.clone();
+        for byte in damaged.iter_mut().take(6) {
+            *byte ^= 0xA7;
+        }
+        // 6 个错误超出 10 个纠错码字的能力（最多 5 个），必须报错而不是误纠
+        assert!(matches!(decode(&mut damaged, ecc_len, &[]), Err(QRDecodeError::DecodeError(_))));
+    }
+
+    #[test]
+    fn test_erasures_counted_consistently() {
+        // 擦除位置读到的值恰好正确（纠正后校正子为 0）时仍计入擦除数
+        let (original, ecc_len) = sample_block();
+        let mut block = original.clone();
+        block[3] = 0;
+        let correction = decode(&mut block, ecc_len, &[3, 6]).unwrap();
+        assert_eq!(block, original);
+        assert_eq!(correction, RsCorrection { erasures: 2, errors: 0 });
+
+        // 擦除的真实值为 0 时同样计入
+        let mut zero_block = vec![0u8; 26];
+        let correction = decode(&mut zero_block, ecc_len, &[0, 1]).unwrap();
+        assert_eq!(correction, RsCorrection { erasures: 2, errors: 0 });
+    }
+}
@@ -0,0 +1,405 @@
+//! 暴力破解搜索空间模块
+//!
+//! 暴力破解的参数空间由一组有序的参数轴组成，排在前面的轴变化最慢。每个轴列出要尝试的
+//! 取值，未列出的轴固定为不做处理的中性值（对比度 1、亮度 0、不模糊、不缩放、灰度、
+//! 不二值化、不校正光照、不镜像、不旋转）。默认空间不含镜像、旋转和光照校正轴，二值化轴
+//! 只有 Otsu 和不二值化，每加一个轴或取值搜索量就成倍增加，需要时再显式打开。搜索空间可以从 JSON 文件加载，也可以用
+//! `--search-axis 轴=值,值` 在命令行上替换或追加单个轴：
+//!
+//! ```json
+//! {
+//!   "axes": [
+//!     { "rotation": [0, 30, 60] },
+//!     { "channel": ["gray", "r", "g", "b"] },
+//!     { "binarization": ["otsu", "sauvola:41", "none"] }
+//!   ]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::binarization::BinarizationMethod;
+use crate::enhanced_processor::TransformType;
+use crate::error::{QRDecodeError, Result};
+use crate::geometry::{self, Mirror};
+use crate::illumination::BackgroundEstimate;
+use crate::pipeline;
+use crate::transform_dsl;
+
+/// 可用的轴名，按默认搜索顺序排列
+pub const AXIS_NAMES: [&str; 9] = [
+    "mirror",
+    "rotation",
+    "illumination",
+    "scale",
+    "channel",
+    "contrast",
+    "brightness",
+    "blur",
+    "binarization",
+];
+
+/// 转灰度时使用的通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Channel {
+    /// 加权灰度
+    Gray,
+    /// 红色通道
+    Red,
+    /// 绿色通道
+    Green,
+    /// 蓝色通道
+    Blue,
+}
+
+impl Channel {
+    /// 获取通道的描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            Channel::Gray => "灰度",
+            Channel::Red => "R",
+            Channel::Green => "G",
+            Channel::Blue => "B",
+        }
+    }
+
+    /// OpenCV BGR 顺序下的通道下标，灰度为 `None`
+    pub fn bgr_index(&self) -> Option<i32> {
+        match self {
+            Channel::Gray => None,
+            Channel::Blue => Some(0),
+            Channel::Green => Some(1),
+            Channel::Red => Some(2),
+        }
+    }
+}
+
+/// 一个参数轴及其取值
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchAxis {
+    /// 镜像方式
+    Mirror(Vec<Mirror>),
+    /// 旋转角度
+    Rotation(Vec<i32>),
+    /// 光照校正，`None` 表示不校正
+    Illumination(Vec<Option<BackgroundEstimate>>),
+    /// 缩放比例
+    Scale(Vec<f64>),
+    /// 转灰度的通道
+    Channel(Vec<Channel>),
+    /// 对比度系数
+    Contrast(Vec<f64>),
+    /// 亮度偏移
+    Brightness(Vec<i32>),
+    /// 高斯模糊核大小（取绝对值，0 和 ±1 表示不模糊）
+    Blur(Vec<i32>),
+    /// 二值化方法，`None` 表示不二值化
+    Binarization(Vec<Option<BinarizationMethod>>),
+}
+
+impl SearchAxis {
+    /// 轴名
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchAxis::Mirror(_) => "mirror",
+            SearchAxis::Rotation(_) => "rotation",
+            SearchAxis::Illumination(_) => "illumination",
+            SearchAxis::Scale(_) => "scale",
+            SearchAxis::Channel(_) => "channel",
+            SearchAxis::Contrast(_) => "contrast",
+            SearchAxis::Brightness(_) => "brightness",
+            SearchAxis::Blur(_) => "blur",
+            SearchAxis::Binarization(_) => "binarization",
+        }
+    }
+
+    /// 取值个数
+    pub fn len(&self) -> usize {
+        match self {
+            SearchAxis::Mirror(values) => values.len(),
+            SearchAxis::Rotation(values) | SearchAxis::Brightness(values) | SearchAxis::Blur(values) => values.len(),
+            SearchAxis::Illumination(values) => values.len(),
+            SearchAxis::Scale(values) | SearchAxis::Contrast(values) => values.len(),
+            SearchAxis::Channel(values) => values.len(),
+            SearchAxis::Binarization(values) => values.len(),
+        }
+    }
+
+    /// 是否没有取值
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 解析单个轴，`values` 为各取值的文本写法，`window` 为局部二值化未指定窗口时使用的大小
+    pub fn parse(name: &str, values: &[String], window: i32) -> std::result::Result<Self, String> {
+        if values.is_empty() {
+            return Err("至少需要一个取值".to_string());
+        }
+        let axis = match name.to_lowercase().as_str() {
+            "mirror" => SearchAxis::Mirror(parse_values(values, parse_mirror)?),
+            "rotation" | "rotate" => SearchAxis::Rotation(parse_rotations(values)?),
+            "illumination" => SearchAxis::Illumination(parse_values(values, |v| parse_illumination(v, window))?),
+            "scale" => SearchAxis::Scale(parse_values(values, |v| checked(number(v, "缩放比例")?, TransformType::Scale))?),
+            "channel" => SearchAxis::Channel(parse_values(values, parse_channel)?),
+            "contrast" => SearchAxis::Contrast(parse_values(values, |v| checked(number(v, "对比度系数")?, TransformType::Contrast))?),
+            "brightness" => SearchAxis::Brightness(parse_values(values, |v| checked(number(v, "亮度")?, TransformType::Brightness))?),
+            "blur" => SearchAxis::Blur(parse_values(values, parse_blur)?),
+            "binarization" | "binarize" => SearchAxis::Binarization(parse_binarizations(values, window)?),
+            other => return Err(format!("未知的轴 \"{}\" (可用轴: {})", other, AXIS_NAMES.join(", "))),
+        };
+        Ok(axis)
+    }
+}
+
+/// 默认搜索空间：缩放、对比度、亮度、模糊和 Otsu/不二值化，共 4320 种组合
+pub fn default_axes() -> Vec<SearchAxis> {
+    vec![
+        SearchAxis::Scale(vec![0.2, 0.5, 0.7, 0.9, 1.3, 2.0]),
+        SearchAxis::Contrast(vec![2.0, 1.0, 3.0]),
+        SearchAxis::Brightness(vec![-75, 75, -50, -25, -10, 0, 25, 50]),
+        SearchAxis::Blur(vec![-7, -3, 7, 3, -1, 5, 9, 11, 13, 15, 17, 19, 21, 23, 25]),
+        SearchAxis::Binarization(vec![Some(BinarizationMethod::Otsu), None]),
+    ]
+}
+
+/// 解析 `--search-axis` 的 `轴=值,值` 写法
+pub fn parse_axis_arg(text: &str, window: i32) -> Result<SearchAxis> {
+    let parsed = match text.split_once('=') {
+        Some((name, values)) => {
+            let values: Vec<String> = values.split(',').map(|v| v.trim().to_string()).collect();
+            SearchAxis::parse(name.trim(), &values, window)
+        }
+        None => Err("应写作 轴=值,值".to_string()),
+    };
+    parsed.map_err(|e| QRDecodeError::invalid_input(format!("--search-axis \"{}\": {}", text, e)))
+}
+
+/// 解析并校验搜索空间文件的 JSON 文本
+pub fn parse_search_space(text: &str, window: i32) -> std::result::Result<Vec<SearchAxis>, String> {
+    let root: Value = serde_json::from_str(text).map_err(|e| format!("JSON 语法错误: {}", e))?;
+    let root = root.as_object().ok_or("顶层必须是对象")?;
+    if let Some(key) = root.keys().find(|key| key.as_str() != "axes") {
+        return Err(format!("未知字段 \"{}\" (可用字段: axes)", key));
+    }
+    let entries = root.get("axes").ok_or("缺少 \"axes\" 字段")?.as_array().ok_or("\"axes\" 必须是数组")?;
+    if entries.is_empty() {
+        return Err("\"axes\" 中没有任何轴".to_string());
+    }
+
+    let mut axes: Vec<SearchAxis> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let axis = parse_entry(entry, window).map_err(|e| format!("第 {} 个轴: {}", i + 1, e))?;
+        if axes.iter().any(|existing| existing.name() == axis.name()) {
+            return Err(format!("第 {} 个轴: 轴 \"{}\" 重复", i + 1, axis.name()));
+        }
+        axes.push(axis);
+    }
+    Ok(axes)
+}
+
+/// 从文件加载搜索空间
+pub fn load_search_space(path: &Path, window: i32) -> Result<Vec<SearchAxis>> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        QRDecodeError::invalid_input(format!("无法读取搜索空间文件 {}: {}", path.display(), e))
+    })?;
+    parse_search_space(&text, window).map_err(|e| QRDecodeError::invalid_input(format!("搜索空间文件 {} 无效: {}", path.display(), e)))
+}
+
+/// 确定暴力破解的搜索空间：文件中的轴（未指定文件时为默认空间），再按 `overrides` 替换同名轴或追加到最内层
+pub fn resolve_search_space(path: Option<&Path>, overrides: &[SearchAxis], window: i32) -> Result<Vec<SearchAxis>> {
+    let mut axes = match path {
+        Some(path) => load_search_space(path, window)?,
+        None => default_axes(),
+    };
+    for axis in overrides {
+        match axes.iter_mut().find(|existing| existing.name() == axis.name()) {
+            Some(existing) => *existing = axis.clone(),
+            None => axes.push(axis.clone()),
+        }
+    }
+    Ok(axes)
+}
+
+/// 解析 `{"轴名": [取值...]}`
+fn parse_entry(entry: &Value, window: i32) -> std::result::Result<SearchAxis, String> {
+    let object = entry.as_object().filter(|object| object.len() == 1).ok_or("每个轴必须是只有一个字段的对象，如 {\"rotation\": [0, 45]}")?;
+    let (name, values) = object.iter().next().ok_or("轴为空")?;
+    let values = values
+        .as_array()
+        .ok_or_else(|| format!("轴 \"{}\" 的取值必须是数组", name))?
+        .iter()
+        .map(|value| match value {
+            Value::String(text) => Ok(text.clone()),
+            Value::Number(number) => Ok(number.to_string()),
+            Value::Null => Ok("none".to_string()),
+            other => Err(format!("轴 \"{}\" 的取值必须是字符串或数字: {}", name, other)),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    SearchAxis::parse(name, &values, window).map_err(|e| format!("轴 \"{}\": {}", name, e))
+}
+
+fn parse_values<T>(values: &[String], parse: impl Fn(&str) -> std::result::Result<T, String>) -> std::result::Result<Vec<T>, String> {
+    values
+        .iter()
+        .map(|value| parse(value.trim()).map_err(|e| format!("取值 \"{}\": {}", value.trim(), e)))
+        .collect()
+}
+
+fn number<T: std::str::FromStr>(value: &str, what: &str) -> std::result::Result<T, String> {
+    value.parse().map_err(|_| format!("无效的{}", what))
+}
+
+/// 按对应变换的参数范围校验取值
+fn checked<T: Copy>(value: T, transform: impl Fn(T) -> TransformType) -> std::result::Result<T, String> {
+    pipeline::validate_transform(&transform(value)).map(|_| value)
+}
+
+/// 旋转角度，`sweep` 展开为 15° 步长的完整扫描
+fn parse_rotations(values: &[String]) -> std::result::Result<Vec<i32>, String> {
+    let mut rotations = Vec::new();
+    for value in values {
+        if value.trim().eq_ignore_ascii_case("sweep") {
+            rotations.extend(geometry::rotation_sweep(geometry::ROTATION_STEP_DEGREES));
+        } else {
+            rotations.extend(parse_values(std::slice::from_ref(value), |v| checked(number(v, "旋转角度")?, TransformType::Rotate))?);
+        }
+    }
+    Ok(rotations)
+}
+
+fn parse_mirror(value: &str) -> std::result::Result<Mirror, String> {
+    match value.to_lowercase().as_str() {
+        "none" => Ok(Mirror::None),
+        "h" | "horizontal" => Ok(Mirror::Horizontal),
+        "v" | "vertical" => Ok(Mirror::Vertical),
+        _ => Err("可选: none, h, v".to_string()),
+    }
+}
+
+fn parse_channel(value: &str) -> std::result::Result<Channel, String> {
+    match value.to_lowercase().as_str() {
+        "gray" | "grey" => Ok(Channel::Gray),
+        "r" | "red" => Ok(Channel::Red),
+        "g" | "green" => Ok(Channel::Green),
+        "b" | "blue" => Ok(Channel::Blue),
+        _ => Err("可选: gray, r, g, b".to_string()),
+    }
+}
+
+fn parse_blur(value: &str) -> std::result::Result<i32, String> {
+    let kernel: i32 = number(value, "核大小")?;
+    if kernel.abs() > 1 {
+        checked(kernel.abs(), TransformType::GaussianBlur)?;
+    }
+    Ok(kernel)
+}
+
+/// 二值化取值，`local` 展开为给定窗口的一组局部二值化方法
+fn parse_binarizations(values: &[String], window: i32) -> std::result::Result<Vec<Option<BinarizationMethod>>, String> {
+    let mut methods = Vec::new();
+    for value in values {
+        if value.trim().eq_ignore_ascii_case("local") {
+            methods.extend(BinarizationMethod::local_family(window).into_iter().map(Some));
+        } else {
+            methods.extend(parse_values(std::slice::from_ref(value), |v| parse_binarization(v, window))?);
+        }
+    }
+    Ok(methods)
+}
+
+/// 二值化取值使用 `--transforms` 的写法（`otsu`、`sauvola:41` 等），`none` 表示不二值化
+fn parse_binarization(value: &str, window: i32) -> std::result::Result<Option<BinarizationMethod>, String> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    match transform_dsl::parse_transform(value, window)? {
+        TransformType::Binarize(method) => Ok(Some(method)),
+        _ => Err("不是二值化方法 (可选: none, otsu, sauvola, wolf, niblack, multiotsu)".to_string()),
+    }
+}
+
+/// 光照校正取值写作 `close[:核]`、`blackhat[:核]` 或 `blur[:核]`，`none` 表示不校正
+fn parse_illumination(value: &str, window: i32) -> std::result::Result<Option<BackgroundEstimate>, String> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    match transform_dsl::parse_transform(&format!("illumination:{}", value), window)? {
+        TransformType::IlluminationCorrection(estimate) => Ok(Some(estimate)),
+        _ => Err("可选: none, close, blackhat, blur".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_space_file() {
+        let text = r#"{
+            "axes": [
+                { "rotation": [0, 30, 60] },
+                { "channel": ["gray", "r"] },
+                { "binarization": ["otsu", "sauvola:41", null] },
+                { "blur": [0, -5] }
+            ]
+        }"#;
+        let axes = parse_search_space(text, 31).unwrap();
+        assert_eq!(axes[0], SearchAxis::Rotation(vec![0, 30, 60]));
+        assert_eq!(axes[1], SearchAxis::Channel(vec![Channel::Gray, Channel::Red]));
+        assert_eq!(
+            axes[2],
+            SearchAxis::Binarization(vec![
+                Some(BinarizationMethod::Otsu),
+                Some(BinarizationMethod::Sauvola { window: 41, k: 0.34 }),
+                None,
+            ])
+        );
+        assert_eq!(axes.iter().map(SearchAxis::len).product::<usize>(), 3 * 2 * 3 * 2);
+
+        let error = parse_search_space(r#"{"axes": [{"scale": [1.0]}, {"blur": [4]}]}"#, 31).unwrap_err();
+        assert!(error.starts_with("第 2 个轴"), "{}", error);
+        let error = parse_search_space(r#"{"axes": [{"rotation": [0]}, {"rotation": [45]}]}"#, 31).unwrap_err();
+        assert!(error.contains("重复"), "{}", error);
+        assert!(parse_search_space(r#"{"axes": [{"hue": [1]}]}"#, 31).unwrap_err().contains("未知的轴"));
+    }
+
+    #[test]
+    fn test_default_space_size() {
+        let axes = default_axes();
+        let names: Vec<&str> = axes.iter().map(SearchAxis::name).collect();
+        assert_eq!(names, vec!["scale", "contrast", "brightness", "blur", "binarization"]);
+        assert_eq!(axes.iter().map(SearchAxis::len).product::<usize>(), 6 * 3 * 8 * 15 * 2);
+    }
+
+    #[test]
+    fn test_axis_overrides() {
+        let overrides = [
+            parse_axis_arg("contrast=1,2.5", 31).unwrap(),
+            parse_axis_arg("channel=r,g,b", 31).unwrap(),
+        ];
+        let axes = resolve_search_space(None, &overrides, 31).unwrap();
+        let names: Vec<&str> = axes.iter().map(SearchAxis::name).collect();
+        let mut expected: Vec<&str> = default_axes().iter().map(SearchAxis::name).collect();
+        expected.push("channel");
+        assert_eq!(names, expected);
+        assert!(axes.contains(&SearchAxis::Contrast(vec![1.0, 2.5])));
+
+        // 镜像、旋转和光照校正默认不搜索，需要显式打开
+        assert!(!names.contains(&"mirror") && !names.contains(&"rotation") && !names.contains(&"illumination"));
+        assert_eq!(parse_axis_arg("illumination=none,close", 31).unwrap().len(), 2);
+        assert_eq!(parse_axis_arg("rotation=sweep", 31).unwrap(), SearchAxis::Rotation(vec![0, 15, 30, 45, 60, 75]));
+        assert_eq!(parse_axis_arg("rotation=0,sweep", 31).unwrap().len(), 7);
+
+        // 局部二值化方法同样按需打开
+        assert!(axes.contains(&SearchAxis::Binarization(vec![Some(BinarizationMethod::Otsu), None])));
+        let local = parse_axis_arg("binarization=otsu,local,none", 41).unwrap();
+        assert_eq!(local.len(), 7);
+        assert!(matches!(local, SearchAxis::Binarization(ref v) if v[1] == Some(BinarizationMethod::Sauvola { window: 41, k: 0.34 })));
+
+        assert!(parse_axis_arg("scale=9", 31).is_err());
+        assert!(parse_axis_arg("rotation", 31).is_err());
+        assert!(parse_axis_arg("binarization=gamma:0.8", 31).is_err());
+    }
+}
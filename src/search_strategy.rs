@@ -0,0 +1,392 @@
+//! 暴力破解搜索策略模块
+//!
+//! 暴力破解的参数空间是若干参数轴（缩放、对比度、亮度、模糊、二值化、光照、镜像、旋转）
+//! 的笛卡尔积，逐个尝试动辄上百万次。本模块把参数组合表示为各轴的下标，提供几种搜索
+//! 顺序：穷举、粗到细细化、预算内随机采样、按轴的 UCB1 多臂老虎机。没有解码成功的
+//! 组合按定位图形的扫描命中数打分，粗到细和老虎机据此把评估集中到更有希望的区域。
+
+use std::collections::HashMap;
+
+use opencv::{
+    core::{self, Mat},
+    imgproc::{self, cvt_color, COLOR_BGR2GRAY, THRESH_BINARY, THRESH_OTSU},
+    prelude::*,
+};
+use rand::{
+    seq::{index, SliceRandom},
+    Rng,
+};
+
+use crate::error::{QRDecodeError, Result};
+use crate::halftone;
+
+/// 随机采样和老虎机策略的默认评估预算
+pub const DEFAULT_SEARCH_BUDGET: usize = 2000;
+
+/// 粗到细每一轮保留的最高分组合数
+const BEAM_WIDTH: usize = 4;
+
+/// UCB1 的探索系数
+const EXPLORATION: f64 = 0.5;
+
+/// 定位图形命中数达到该值时分数为 0.5
+const FINDER_HIT_SCALE: f64 = 20.0;
+
+/// 暴力破解的搜索策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStrategy {
+    /// 按固定顺序穷举所有组合
+    Exhaustive,
+    /// 先评估每轴两点的粗网格，再在定位图形得分最高的组合附近逐级缩小步长细化
+    CoarseToFine,
+    /// 在预算内不重复地随机采样
+    Random,
+    /// 每个参数轴视为一组老虎机臂，按 UCB1 选择每轴的取值
+    Bandit,
+}
+
+impl SearchStrategy {
+    /// 获取策略的描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            SearchStrategy::Exhaustive => "穷举",
+            SearchStrategy::CoarseToFine => "粗到细",
+            SearchStrategy::Random => "随机采样",
+            SearchStrategy::Bandit => "UCB1 老虎机",
+        }
+    }
+
+    /// 未指定预算时的默认预算，`None` 表示不限
+    pub fn default_budget(&self) -> Option<usize> {
+        match self {
+            SearchStrategy::Exhaustive | SearchStrategy::CoarseToFine => None,
+            SearchStrategy::Random | SearchStrategy::Bandit => Some(DEFAULT_SEARCH_BUDGET),
+        }
+    }
+
+    /// 是否需要未解码组合的定位图形得分
+    pub fn uses_scores(&self) -> bool {
+        matches!(self, SearchStrategy::CoarseToFine | SearchStrategy::Bandit)
+    }
+}
+
+impl std::str::FromStr for SearchStrategy {
+    type Err = QRDecodeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "exhaustive" | "grid" => Ok(SearchStrategy::Exhaustive),
+            "coarse-to-fine" | "coarse" => Ok(SearchStrategy::CoarseToFine),
+            "random" => Ok(SearchStrategy::Random),
+            "bandit" | "ucb" => Ok(SearchStrategy::Bandit),
+            _ => Err(QRDecodeError::invalid_input(format!(
+                "不支持的搜索策略: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// 一次评估的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Evaluation {
+    /// 是否解码成功
+    pub decoded: bool,
+    /// 未解码时的得分 (0.0-1.0)，越高越接近可解码
+    pub score: f64,
+}
+
+impl Evaluation {
+    /// 解码成功
+    pub const DECODED: Evaluation = Evaluation { decoded: true, score: 1.0 };
+    /// 未解码且没有任何线索
+    pub const MISS: Evaluation = Evaluation { decoded: false, score: 0.0 };
+}
+
+/// 搜索结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchOutcome {
+    /// 实际评估的组合数
+    pub evaluations: usize,
+    /// 解码成功的组合（各轴下标）
+    pub found: Option<Vec<usize>>,
+}
+
+/// 按策略搜索参数空间，`sizes` 为各轴的取值个数，找到可解码的组合或用完预算时停止
+///
+/// `randomize` 只影响穷举的顺序。
+pub fn search<R, F>(strategy: SearchStrategy, sizes: &[usize], budget: Option<usize>, randomize: bool, rng: &mut R, evaluate: F) -> SearchOutcome
+where
+    R: Rng + ?Sized,
+    F: FnMut(&[usize]) -> Evaluation,
+{
+    let total = space_size(sizes);
+    let mut evaluator = Evaluator {
+        evaluate,
+        budget: budget.unwrap_or(usize::MAX).min(total),
+        evaluations: 0,
+        scores: HashMap::new(),
+        found: None,
+    };
+    if total > 0 {
+        match strategy {
+            SearchStrategy::Exhaustive => exhaustive(&mut evaluator, sizes, randomize, rng),
+            SearchStrategy::CoarseToFine => coarse_to_fine(&mut evaluator, sizes),
+            SearchStrategy::Random => random_sampling(&mut evaluator, sizes, rng),
+            SearchStrategy::Bandit => bandit(&mut evaluator, sizes, rng),
+        }
+    }
+
+    SearchOutcome {
+        evaluations: evaluator.evaluations,
+        found: evaluator.found,
+    }
+}
+
+/// 把行优先（最后一轴变化最快）的序号还原为各轴下标
+pub fn unflatten(mut flat: usize, sizes: &[usize]) -> Vec<usize> {
+    let mut index = vec![0; sizes.len()];
+    for (axis, &size) in sizes.iter().enumerate().rev() {
+        index[axis] = flat % size;
+        flat /= size;
+    }
+    index
+}
+
+/// 处理后图像的定位图形得分：Otsu 二值化后沿行列扫描 1:1:3:1:1 游程的命中数，映射到 0.0-1.0
+pub fn finder_score(image: &Mat) -> Result<f64> {
+    let gray = if image.channels() == 1 {
+        image.clone()
+    } else {
+        let mut gray = Mat::default();
+        cvt_color(image, &mut gray, COLOR_BGR2GRAY, 0, core::AlgorithmHint::ALGO_HINT_DEFAULT)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("灰度转换失败: {}", e)))?;
+        gray
+    };
+    let mut binary = Mat::default();
+    imgproc::threshold(&gray, &mut binary, 0.0, 255.0, THRESH_BINARY | THRESH_OTSU)
+        .map_err(|e| QRDecodeError::image_processing_error(format!("二值化失败: {}", e)))?;
+
+    let dark: Vec<bool> = binary.data_bytes()?.iter().map(|&p| p == 0).collect();
+    let hits = halftone::finder_pitches(&dark, binary.rows() as usize, binary.cols() as usize).len() as f64;
+    Ok(hits / (hits + FINDER_HIT_SCALE))
+}
+
+fn space_size(sizes: &[usize]) -> usize {
+    sizes.iter().try_fold(1usize, |total, &size| total.checked_mul(size)).unwrap_or(usize::MAX)
+}
+
+/// 记录评估次数、得分和成功组合，已评估过的组合直接返回缓存的得分
+struct Evaluator<F> {
+    evaluate: F,
+    budget: usize,
+    evaluations: usize,
+    scores: HashMap<Vec<usize>, f64>,
+    found: Option<Vec<usize>>,
+}
+
+impl<F: FnMut(&[usize]) -> Evaluation> Evaluator<F> {
+    fn done(&self) -> bool {
+        self.found.is_some() || self.evaluations >= self.budget
+    }
+
+    /// 评估组合并返回得分；搜索已结束时返回 `None`
+    fn score(&mut self, index: &[usize]) -> Option<f64> {
+        if let Some(&score) = self.scores.get(index) {
+            return Some(score);
+        }
+        let score = self.evaluate_once(index)?;
+        self.scores.insert(index.to_vec(), score);
+        Some(score)
+    }
+
+    /// 评估组合但不缓存得分，用于不会重复访问的穷举和随机采样
+    fn evaluate_once(&mut self, index: &[usize]) -> Option<f64> {
+        if self.done() {
+            return None;
+        }
+        self.evaluations += 1;
+        let evaluation = (self.evaluate)(index);
+        if evaluation.decoded {
+            self.found = Some(index.to_vec());
+            return Some(1.0);
+        }
+        Some(evaluation.score)
+    }
+}
+
+fn exhaustive<F, R>(evaluator: &mut Evaluator<F>, sizes: &[usize], randomize: bool, rng: &mut R)
+where
+    F: FnMut(&[usize]) -> Evaluation,
+    R: Rng + ?Sized,
+{
+    let total = space_size(sizes);
+    if randomize {
+        let mut order: Vec<usize> = (0..total).collect();
+        order.shuffle(rng);
+        for flat in order {
+            if evaluator.evaluate_once(&unflatten(flat, sizes)).is_none() {
+                return;
+            }
+        }
+    } else {
+        for flat in 0..total {
+            if evaluator.evaluate_once(&unflatten(flat, sizes)).is_none() {
+                return;
+            }
+        }
+    }
+}
+
+fn random_sampling<F, R>(evaluator: &mut Evaluator<F>, sizes: &[usize], rng: &mut R)
+where
+    F: FnMut(&[usize]) -> Evaluation,
+    R: Rng + ?Sized,
+{
+    let samples = index::sample(rng, space_size(sizes), evaluator.budget);
+    for flat in samples.iter() {
+        if evaluator.evaluate_once(&unflatten(flat, sizes)).is_none() {
+            return;
+        }
+    }
+}
+
+fn coarse_to_fine<F: FnMut(&[usize]) -> Evaluation>(evaluator: &mut Evaluator<F>, sizes: &[usize]) {
+    // 粗网格：每轴取 0 和中点，步长随后逐级减半
+    let mut strides: Vec<usize> = sizes.iter().map(|&size| size.div_ceil(2)).collect();
+    let coarse: Vec<usize> = sizes.iter().zip(&strides).map(|(&size, &stride)| size.div_ceil(stride)).collect();
+    for flat in 0..space_size(&coarse) {
+        let index: Vec<usize> = unflatten(flat, &coarse).iter().zip(&strides).map(|(&i, &stride)| i * stride).collect();
+        if evaluator.score(&index).is_none() {
+            return;
+        }
+    }
+
+    let mut beam = best_scored(&evaluator.scores);
+    while !beam.is_empty() {
+        strides.iter_mut().for_each(|stride| *stride = (*stride / 2).max(1));
+        for center in &beam {
+            for (axis, &stride) in strides.iter().enumerate() {
+                let neighbours = [center[axis].checked_sub(stride), Some(center[axis] + stride)];
+                for value in neighbours.into_iter().flatten().filter(|&value| value < sizes[axis]) {
+                    let mut index = center.clone();
+                    index[axis] = value;
+                    if evaluator.score(&index).is_none() {
+                        return;
+                    }
+                }
+            }
+        }
+
+        // 步长已到 1 且最高分组合不再变化时停止
+        let next = best_scored(&evaluator.scores);
+        if next == beam && strides.iter().all(|&stride| stride == 1) {
+            return;
+        }
+        beam = next;
+    }
+}
+
+/// 得分大于 0 的前 `BEAM_WIDTH` 个组合，同分按下标排序保证结果确定
+fn best_scored(scores: &HashMap<Vec<usize>, f64>) -> Vec<Vec<usize>> {
+    let mut scored: Vec<(&Vec<usize>, f64)> = scores.iter().filter(|(_, &score)| score > 0.0).map(|(index, &score)| (index, score)).collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    scored.into_iter().take(BEAM_WIDTH).map(|(index, _)| index.clone()).collect()
+}
+
+fn bandit<F, R>(evaluator: &mut Evaluator<F>, sizes: &[usize], rng: &mut R)
+where
+    F: FnMut(&[usize]) -> Evaluation,
+    R: Rng + ?Sized,
+{
+    let mut pulls: Vec<Vec<f64>> = sizes.iter().map(|&size| vec![0.0; size]).collect();
+    let mut rewards: Vec<Vec<f64>> = pulls.clone();
+
+    for round in 1..=evaluator.budget {
+        let log_round = (round as f64).ln();
+        let index: Vec<usize> = pulls
+            .iter()
+            .zip(&rewards)
+            .map(|(pulls, rewards)| {
+                // 未尝试过的取值优先，随机选一个
+                let untried: Vec<usize> = (0..pulls.len()).filter(|&value| pulls[value] == 0.0).collect();
+                if let Some(&value) = untried.choose(rng) {
+                    return value;
+                }
+                let ucb = |value: usize| rewards[value] / pulls[value] + EXPLORATION * (log_round / pulls[value]).sqrt();
+                (0..pulls.len()).max_by(|&a, &b| ucb(a).total_cmp(&ucb(b))).unwrap_or(0)
+            })
+            .collect();
+
+        // 选中的组合已评估过时随机改动一个轴，在当前最优取值附近继续探索
+        let mut index = index;
+        while evaluator.scores.contains_key(&index) {
+            let axis = rng.gen_range(0..sizes.len());
+            index[axis] = rng.gen_range(0..sizes[axis]);
+        }
+
+        let Some(score) = evaluator.score(&index) else {
+            return;
+        };
+        for (axis, &value) in index.iter().enumerate() {
+            pulls[axis][value] += 1.0;
+            rewards[axis][value] += score;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// 目标组合附近得分逐渐升高，只有目标本身可解码
+    fn peak(target: &[usize]) -> impl Fn(&[usize]) -> Evaluation + '_ {
+        move |index| {
+            let distance: usize = index.iter().zip(target).map(|(&a, &b)| a.abs_diff(b)).sum();
+            match distance {
+                0 => Evaluation::DECODED,
+                d => Evaluation { decoded: false, score: 1.0 / (1.0 + d as f64) },
+            }
+        }
+    }
+
+    #[test]
+    fn test_strategies_find_peak() {
+        let sizes = [3, 6, 2, 6, 3, 8, 15, 6];
+        let target = [1, 3, 1, 4, 2, 5, 11, 2];
+        let total = space_size(&sizes);
+
+        let exhaustive = search(SearchStrategy::Exhaustive, &sizes, None, false, &mut StdRng::seed_from_u64(1), peak(&target));
+        assert_eq!(exhaustive.found.as_deref(), Some(&target[..]));
+        assert_eq!(exhaustive.evaluations, (0..total).position(|flat| unflatten(flat, &sizes) == target).unwrap() + 1);
+
+        for strategy in [SearchStrategy::CoarseToFine, SearchStrategy::Bandit] {
+            let outcome = search(strategy, &sizes, strategy.default_budget(), false, &mut StdRng::seed_from_u64(1), peak(&target));
+            assert_eq!(outcome.found.as_deref(), Some(&target[..]), "{}", strategy.description());
+            assert!(outcome.evaluations < exhaustive.evaluations / 10, "{} 评估 {} 次", strategy.description(), outcome.evaluations);
+        }
+    }
+
+    #[test]
+    fn test_budget_limits_evaluations() {
+        let sizes = [4, 5, 6];
+        let never = |_: &[usize]| Evaluation::MISS;
+        for strategy in [SearchStrategy::Exhaustive, SearchStrategy::CoarseToFine, SearchStrategy::Random, SearchStrategy::Bandit] {
+            let outcome = search(strategy, &sizes, Some(25), true, &mut StdRng::seed_from_u64(7), never);
+            assert!(outcome.found.is_none());
+            assert!(outcome.evaluations <= 25, "{} 评估 {} 次", strategy.description(), outcome.evaluations);
+        }
+
+        // 预算大于空间时随机采样恰好不重复地覆盖全部组合
+        let mut seen = Vec::new();
+        let outcome = search(SearchStrategy::Random, &sizes, Some(1000), false, &mut StdRng::seed_from_u64(7), |index: &[usize]| {
+            seen.push(index.to_vec());
+            Evaluation::MISS
+        });
+        assert_eq!(outcome.evaluations, 120);
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 120);
+    }
+}
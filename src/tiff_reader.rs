@@ -0,0 +1,581 @@
+//! TIFF 流式读取模块
+//!
+//! OpenCV 只能整幅解码 TIFF，上亿像素的扫描件和显微拼接图会占满内存。本模块直接解析
+//! 基线 TIFF 的第一个图像目录，按条带或瓦片定位像素数据，每次只解码请求的行并转换为
+//! 8 位灰度。支持无压缩和 PackBits 压缩、1/8/16 位样本、灰度和 RGB，按块平面存放的
+//! 样本不支持；其他压缩方式和颜色空间返回 `UnsupportedFormat`，由调用方改为整幅解码。
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::error::{QRDecodeError, Result};
+
+const TAG_IMAGE_WIDTH: u16 = 256;
+const TAG_IMAGE_LENGTH: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_COMPRESSION: u16 = 259;
+const TAG_PHOTOMETRIC: u16 = 262;
+const TAG_STRIP_OFFSETS: u16 = 273;
+const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+const TAG_ROWS_PER_STRIP: u16 = 278;
+const TAG_STRIP_BYTE_COUNTS: u16 = 279;
+const TAG_PLANAR_CONFIG: u16 = 284;
+const TAG_TILE_WIDTH: u16 = 322;
+const TAG_TILE_LENGTH: u16 = 323;
+const TAG_TILE_OFFSETS: u16 = 324;
+const TAG_TILE_BYTE_COUNTS: u16 = 325;
+
+const COMPRESSION_NONE: u32 = 1;
+const COMPRESSION_PACKBITS: u32 = 32773;
+
+/// 像素数据的压缩方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    PackBits,
+}
+
+/// 灰度值的解释方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Photometric {
+    /// 0 为白色
+    WhiteIsZero,
+    /// 0 为黑色
+    BlackIsZero,
+    Rgb,
+}
+
+/// 一个条带或瓦片在图像中的位置和文件中的数据范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Block {
+    x: u32,
+    y: u32,
+    /// 存储的每行像素数（瓦片在右边缘处按完整宽度存储）
+    stored_width: u32,
+    /// 覆盖的行数
+    rows: u32,
+    offset: u64,
+    byte_count: u64,
+}
+
+/// 样本的存储方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SampleFormat {
+    bits_per_sample: u32,
+    samples_per_pixel: u32,
+    big_endian: bool,
+    photometric: Photometric,
+}
+
+impl SampleFormat {
+    /// 一行存储数据的字节数，位数超出 u32 时报错
+    fn row_bytes(&self, stored_width: u32) -> Result<usize> {
+        stored_width
+            .checked_mul(self.samples_per_pixel)
+            .and_then(|samples| samples.checked_mul(self.bits_per_sample))
+            .map(|bits| (bits as usize).div_ceil(8))
+            .ok_or_else(|| QRDecodeError::UnsupportedFormat(format!("TIFF 行宽 {} 像素超出范围", stored_width)))
+    }
+
+    /// 把一行存储数据转换为 8 位灰度
+    fn convert_row(&self, row: &[u8], gray: &mut [u8]) {
+        let samples = self.samples_per_pixel as usize;
+        let sample = |index: usize| -> u8 {
+            match self.bits_per_sample {
+                1 => ((row[index / 8] >> (7 - index % 8)) & 1) * 255,
+                // 16 位样本取高字节
+                16 => row[index * 2 + usize::from(!self.big_endian)],
+                _ => row[index],
+            }
+        };
+        for (x, pixel) in gray.iter_mut().enumerate() {
+            *pixel = match self.photometric {
+                Photometric::BlackIsZero => sample(x * samples),
+                Photometric::WhiteIsZero => 255 - sample(x * samples),
+                Photometric::Rgb => {
+                    let (r, g, b) = (sample(x * samples), sample(x * samples + 1), sample(x * samples + 2));
+                    ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114 + 500) / 1000) as u8
+                }
+            };
+        }
+    }
+}
+
+/// 按行读取 TIFF 第一幅图像的灰度像素
+pub struct TiffReader<R> {
+    reader: R,
+    width: u32,
+    height: u32,
+    format: SampleFormat,
+    compression: Compression,
+    blocks: Vec<Block>,
+}
+
+impl TiffReader<BufReader<File>> {
+    /// 打开 TIFF 文件
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| {
+            QRDecodeError::invalid_input(format!("无法打开图像 {}: {}", path.display(), e))
+        })?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> TiffReader<R> {
+    /// 解析文件头和第一个图像目录
+    pub fn new(mut reader: R) -> Result<Self> {
+        let (big_endian, directory) = read_first_directory(&mut reader)?;
+        let field = |tag: u16| field(&directory, tag);
+        let single = |tag: u16, default: Option<u32>| single(&directory, tag, default);
+
+        let width = single(TAG_IMAGE_WIDTH, None)?;
+        let height = single(TAG_IMAGE_LENGTH, None)?;
+        let samples_per_pixel = single(TAG_SAMPLES_PER_PIXEL, Some(1))?;
+        let bits_per_sample = single(TAG_BITS_PER_SAMPLE, Some(1))?;
+        let compression = match single(TAG_COMPRESSION, Some(COMPRESSION_NONE))? {
+            COMPRESSION_NONE => Compression::None,
+            COMPRESSION_PACKBITS => Compression::PackBits,
+            other => return Err(QRDecodeError::UnsupportedFormat(format!("TIFF 压缩方式 {} 不支持流式读取", other))),
+        };
+        let photometric = match single(TAG_PHOTOMETRIC, Some(1))? {
+            0 => Photometric::WhiteIsZero,
+            1 => Photometric::BlackIsZero,
+            2 => Photometric::Rgb,
+            other => return Err(QRDecodeError::UnsupportedFormat(format!("TIFF 颜色空间 {} 不支持流式读取", other))),
+        };
+        if single(TAG_PLANAR_CONFIG, Some(1))? != 1 {
+            return Err(QRDecodeError::UnsupportedFormat("按平面存放样本的 TIFF 不支持流式读取".to_string()));
+        }
+        let supported = match (photometric, bits_per_sample) {
+            (Photometric::Rgb, 8 | 16) => samples_per_pixel >= 3,
+            (Photometric::Rgb, _) => false,
+            (_, 1) => samples_per_pixel == 1,
+            (_, 8 | 16) => samples_per_pixel >= 1,
+            _ => false,
+        };
+        if !supported || width == 0 || height == 0 {
+            return Err(QRDecodeError::UnsupportedFormat(format!(
+                "TIFF 样本格式 {} 位 x {} 不支持流式读取",
+                bits_per_sample, samples_per_pixel
+            )));
+        }
+        if width > i32::MAX as u32 || height > i32::MAX as u32 {
+            return Err(QRDecodeError::UnsupportedFormat(format!("TIFF 尺寸 {}x{} 超出范围", width, height)));
+        }
+        let format = SampleFormat {
+            bits_per_sample,
+            samples_per_pixel,
+            big_endian,
+            photometric,
+        };
+        // 块的起点来自文件中的块序号和块尺寸，乘积溢出 u32 时报错
+        let origin = |index: usize, size: u32| {
+            u32::try_from(index)
+                .ok()
+                .and_then(|index| index.checked_mul(size))
+                .ok_or_else(|| QRDecodeError::UnsupportedFormat(format!("TIFF 第 {} 个数据块的位置超出范围", index)))
+        };
+
+        let list = |tag: u16| field(tag).map(<[u32]>::to_vec).unwrap_or_default();
+        let blocks = match (field(TAG_TILE_WIDTH), field(TAG_TILE_LENGTH)) {
+            (Some(_), Some(_)) => {
+                let tile_width = single(TAG_TILE_WIDTH, None)?.max(1);
+                let tile_length = single(TAG_TILE_LENGTH, None)?.max(1);
+                let across = width.div_ceil(tile_width) as usize;
+                format.row_bytes(tile_width)?;
+                list(TAG_TILE_OFFSETS)
+                    .into_iter()
+                    .zip(list(TAG_TILE_BYTE_COUNTS))
+                    .enumerate()
+                    .map(|(i, (offset, byte_count))| {
+                        Ok(Block {
+                            x: origin(i % across, tile_width)?,
+                            y: origin(i / across, tile_length)?,
+                            stored_width: tile_width,
+                            rows: tile_length,
+                            offset: offset as u64,
+                            byte_count: byte_count as u64,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            _ => {
+                let rows_per_strip = single(TAG_ROWS_PER_STRIP, Some(height))?.clamp(1, height);
+                format.row_bytes(width)?;
+                list(TAG_STRIP_OFFSETS)
+                    .into_iter()
+                    .zip(list(TAG_STRIP_BYTE_COUNTS))
+                    .enumerate()
+                    .map(|(i, (offset, byte_count))| {
+                        Ok(Block {
+                            x: 0,
+                            y: origin(i, rows_per_strip)?,
+                            stored_width: width,
+                            rows: rows_per_strip,
+                            offset: offset as u64,
+                            byte_count: byte_count as u64,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+        };
+        if blocks.is_empty() {
+            return Err(QRDecodeError::image_processing_error("TIFF 没有像素数据".to_string()));
+        }
+
+        Ok(Self {
+            reader,
+            width,
+            height,
+            format,
+            compression,
+            blocks,
+        })
+    }
+
+    /// 图像宽度
+    pub fn width(&self) -> i32 {
+        self.width as i32
+    }
+
+    /// 图像高度
+    pub fn height(&self) -> i32 {
+        self.height as i32
+    }
+
+    /// 压缩方式
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// 条带和瓦片索引占用的内存字节数
+    pub fn index_bytes(&self) -> usize {
+        self.blocks.len() * std::mem::size_of::<Block>()
+    }
+
+    /// 读取 [y, y + rows) 行的 8 位灰度像素，按行连续存放
+    pub fn read_rows(&mut self, y: i32, rows: i32) -> Result<Vec<u8>> {
+        let width = self.width as usize;
+        let first = y.max(0) as u32;
+        let last = (y.max(0) + rows.max(0)).min(self.height as i32) as u32;
+        let mut gray = vec![0u8; width * last.saturating_sub(first) as usize];
+
+        let blocks: Vec<Block> = self
+            .blocks
+            .iter()
+            .filter(|block| block.y < last && block.y.saturating_add(block.rows) > first)
+            .copied()
+            .collect();
+        for block in blocks {
+            let row_bytes = self.format.row_bytes(block.stored_width)?;
+            let skip = first.saturating_sub(block.y);
+            let end = (last - block.y).min(block.rows);
+            let visible = (block.stored_width.min(self.width - block.x.min(self.width))) as usize;
+
+            // 无压缩数据直接定位到第一个需要的行，PackBits 需要逐行解码跳过
+            let start_row = if self.compression == Compression::None { skip } else { 0 };
+            let skipped = start_row as u64 * row_bytes as u64;
+            self.reader.seek(SeekFrom::Start(block.offset + skipped))?;
+            let mut data = (&mut self.reader).take(block.byte_count.saturating_sub(skipped));
+            let mut row = vec![0u8; row_bytes];
+            for _ in start_row..skip {
+                read_row(&mut data, self.compression, &mut row)?;
+            }
+            for row_in_block in skip..end {
+                read_row(&mut data, self.compression, &mut row)?;
+                let start = (block.y + row_in_block - first) as usize * width + block.x as usize;
+                self.format.convert_row(&row, &mut gray[start..start + visible]);
+            }
+        }
+        Ok(gray)
+    }
+}
+
+/// 只读取第一幅图像的宽和高，不检查像素格式是否支持流式读取
+pub fn dimensions(reader: &mut (impl Read + Seek)) -> Result<(u32, u32)> {
+    let (_, directory) = read_first_directory(reader)?;
+    Ok((single(&directory, TAG_IMAGE_WIDTH, None)?, single(&directory, TAG_IMAGE_LENGTH, None)?))
+}
+
+/// 解析文件头并读取第一个图像目录，返回字节序和目录
+fn read_first_directory(reader: &mut (impl Read + Seek)) -> Result<(bool, Vec<(u16, Vec<u32>)>)> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)?;
+    let big_endian = match &header[..2] {
+        b"II" => false,
+        b"MM" => true,
+        _ => return Err(QRDecodeError::UnsupportedFormat("不是 TIFF 文件".to_string())),
+    };
+    if read_u16(&header[2..4], big_endian) != 42 {
+        return Err(QRDecodeError::UnsupportedFormat("仅支持经典 TIFF，不支持 BigTIFF".to_string()));
+    }
+    let directory = read_directory(reader, read_u32(&header[4..8], big_endian) as u64, big_endian)?;
+    Ok((big_endian, directory))
+}
+
+fn field(directory: &[(u16, Vec<u32>)], tag: u16) -> Option<&[u32]> {
+    directory.iter().find(|(t, _)| *t == tag).map(|(_, values)| values.as_slice())
+}
+
+/// 标签的第一个值，缺失时取默认值，没有默认值则报错
+fn single(directory: &[(u16, Vec<u32>)], tag: u16, default: Option<u32>) -> Result<u32> {
+    field(directory, tag)
+        .and_then(|values| values.first().copied())
+        .or(default)
+        .ok_or_else(|| QRDecodeError::image_processing_error(format!("TIFF 缺少标签 {}", tag)))
+}
+
+/// 读取一行存储数据，PackBits 按 TIFF 规范逐行压缩，游程不跨行
+fn read_row(data: &mut impl Read, compression: Compression, row: &mut [u8]) -> Result<()> {
+    match compression {
+        Compression::None => data.read_exact(row)?,
+        Compression::PackBits => {
+            let mut filled = 0;
+            while filled < row.len() {
+                let mut header = [0u8; 1];
+                data.read_exact(&mut header)?;
+                let count = header[0] as i8;
+                if count >= 0 {
+                    let len = count as usize + 1;
+                    let target = row.get_mut(filled..filled + len).ok_or_else(|| {
+                        QRDecodeError::image_processing_error("PackBits 数据跨行".to_string())
+                    })?;
+                    data.read_exact(target)?;
+                    filled += len;
+                } else if count != -128 {
+                    let len = 1 - count as isize;
+                    let mut value = [0u8; 1];
+                    data.read_exact(&mut value)?;
+                    let target = row.get_mut(filled..filled + len as usize).ok_or_else(|| {
+                        QRDecodeError::image_processing_error("PackBits 数据跨行".to_string())
+                    })?;
+                    target.fill(value[0]);
+                    filled += len as usize;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 读取图像目录，返回 (标签, 数值) 列表
+fn read_directory(reader: &mut (impl Read + Seek), offset: u64, big_endian: bool) -> Result<Vec<(u16, Vec<u32>)>> {
+    // 目录中的计数和偏移来自文件，分配前先核对文件长度，损坏的文件不会触发巨量分配
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let check_extent = |what: &str, start: u64, len: u64| {
+        if start.saturating_add(len) > file_len {
+            return Err(QRDecodeError::image_processing_error(format!(
+                "TIFF {} 超出文件范围: 偏移 {}，长度 {}，文件 {} 字节",
+                what, start, len, file_len
+            )));
+        }
+        Ok(())
+    };
+
+    check_extent("图像目录", offset, 2)?;
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut count = [0u8; 2];
+    reader.read_exact(&mut count)?;
+    let entries_len = read_u16(&count, big_endian) as u64 * 12;
+    check_extent("图像目录", offset + 2, entries_len)?;
+    let mut entries = vec![0u8; entries_len as usize];
+    reader.read_exact(&mut entries)?;
+
+    let mut fields = Vec::new();
+    for entry in entries.chunks_exact(12) {
+        let tag = read_u16(&entry[0..2], big_endian);
+        let size = match read_u16(&entry[2..4], big_endian) {
+            1 => 1,
+            3 => 2,
+            4 => 4,
+            // 其他类型的标签与像素布局无关
+            _ => continue,
+        };
+        let len = read_u32(&entry[4..8], big_endian) as u64 * size as u64;
+        let bytes = if len <= 4 {
+            entry[8..8 + len as usize].to_vec()
+        } else {
+            let value_offset = read_u32(&entry[8..12], big_endian) as u64;
+            check_extent(&format!("标签 {} 的值", tag), value_offset, len)?;
+            let mut bytes = vec![0u8; len as usize];
+            reader.seek(SeekFrom::Start(value_offset))?;
+            reader.read_exact(&mut bytes)?;
+            bytes
+        };
+        let values = bytes
+            .chunks_exact(size)
+            .map(|value| match size {
+                1 => value[0] as u32,
+                2 => read_u16(value, big_endian) as u32,
+                _ => read_u32(value, big_endian),
+            })
+            .collect();
+        fields.push((tag, values));
+    }
+    Ok(fields)
+}
+
+fn read_u16(bytes: &[u8], big_endian: bool) -> u16 {
+    let bytes = [bytes[0], bytes[1]];
+    if big_endian {
+        u16::from_be_bytes(bytes)
+    } else {
+        u16::from_le_bytes(bytes)
+    }
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// 小端 TIFF：文件头、像素数据、图像目录
+    fn tiff(width: u32, height: u32, tags: &[(u16, u16, Vec<u32>)], data: &[u8]) -> Vec<u8> {
+        let ifd_offset = 8 + data.len() as u32;
+        let mut file = b"II".to_vec();
+        file.extend(42u16.to_le_bytes());
+        file.extend(ifd_offset.to_le_bytes());
+        file.extend(data);
+
+        let mut entries = vec![(TAG_IMAGE_WIDTH, 4, vec![width]), (TAG_IMAGE_LENGTH, 4, vec![height])];
+        entries.extend(tags.iter().cloned());
+        let extra_offset = ifd_offset + 2 + entries.len() as u32 * 12 + 4;
+        let mut extra = Vec::new();
+        file.extend((entries.len() as u16).to_le_bytes());
+        for (tag, kind, values) in entries {
+            file.extend(tag.to_le_bytes());
+            file.extend(kind.to_le_bytes());
+            file.extend((values.len() as u32).to_le_bytes());
+            if values.len() == 1 {
+                file.extend(values[0].to_le_bytes());
+            } else {
+                file.extend((extra_offset + extra.len() as u32).to_le_bytes());
+                values.iter().for_each(|v| extra.extend(v.to_le_bytes()));
+            }
+        }
+        file.extend(0u32.to_le_bytes());
+        file.extend(extra);
+        file
+    }
+
+    #[test]
+    fn test_reads_rows_across_strips() {
+        // 4x5 灰度，每条带 2 行，无压缩
+        let pixels: Vec<u8> = (0..20).map(|i| i * 10).collect();
+        let tags = vec![
+            (TAG_BITS_PER_SAMPLE, 3, vec![8]),
+            (TAG_ROWS_PER_STRIP, 4, vec![2]),
+            (TAG_STRIP_OFFSETS, 4, vec![8, 16, 24]),
+            (TAG_STRIP_BYTE_COUNTS, 4, vec![8, 8, 4]),
+        ];
+        let mut reader = TiffReader::new(Cursor::new(tiff(4, 5, &tags, &pixels))).unwrap();
+        assert_eq!((reader.width(), reader.height()), (4, 5));
+        assert_eq!(reader.read_rows(1, 3).unwrap(), pixels[4..16]);
+        assert_eq!(reader.read_rows(3, 10).unwrap(), pixels[12..20]);
+    }
+
+    #[test]
+    fn test_rejects_counts_beyond_file() {
+        let tags = vec![
+            (TAG_BITS_PER_SAMPLE, 3, vec![8]),
+            (TAG_ROWS_PER_STRIP, 4, vec![1]),
+            (TAG_STRIP_OFFSETS, 4, vec![8]),
+            (TAG_STRIP_BYTE_COUNTS, 4, vec![4]),
+        ];
+        let mut file = tiff(4, 1, &tags, &[0; 4]);
+        assert!(TiffReader::new(Cursor::new(file.clone())).is_ok());
+
+        // 第 5 个目录项（StripOffsets）的值个数改为 2^30，按值个数分配需要 4 GB
+        let count_at = 12 + 2 + 4 * 12 + 4;
+        file[count_at..count_at + 4].copy_from_slice(&(1u32 << 30).to_le_bytes());
+        assert!(matches!(
+            TiffReader::new(Cursor::new(file.clone())),
+            Err(QRDecodeError::ImageProcessingError(_))
+        ));
+
+        // 目录项数超出文件长度
+        file[12..14].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(matches!(TiffReader::new(Cursor::new(file)), Err(QRDecodeError::ImageProcessingError(_))));
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_layouts() {
+        let unsupported = |width: u32, height: u32, tags: &[(u16, u16, Vec<u32>)]| {
+            matches!(
+                TiffReader::new(Cursor::new(tiff(width, height, tags, &[0; 4]))),
+                Err(QRDecodeError::UnsupportedFormat(_))
+            )
+        };
+        let strips = |rows_per_strip: u32, count: usize| {
+            vec![
+                (TAG_BITS_PER_SAMPLE, 3, vec![8]),
+                (TAG_ROWS_PER_STRIP, 4, vec![rows_per_strip]),
+                (TAG_STRIP_OFFSETS, 4, vec![8; count]),
+                (TAG_STRIP_BYTE_COUNTS, 4, vec![1; count]),
+            ]
+        };
+
+        // RGB 只支持 8/16 位，1 位 RGB（默认每像素 1 个样本）按 3 个样本读取会越界
+        let rgb_bilevel = vec![(TAG_PHOTOMETRIC, 3, vec![2]), (TAG_STRIP_OFFSETS, 4, vec![8]), (TAG_STRIP_BYTE_COUNTS, 4, vec![1])];
+        assert!(unsupported(4, 1, &rgb_bilevel));
+
+        // 尺寸超出 i32
+        assert!(unsupported(1 << 31, 1, &strips(1, 1)));
+        assert!(unsupported(1, 1 << 31, &strips(1, 1)));
+
+        // 行位数溢出 u32：2^30 像素 x 3 样本 x 16 位
+        let mut wide = strips(1, 1);
+        wide[0] = (TAG_BITS_PER_SAMPLE, 3, vec![16]);
+        wide.push((TAG_PHOTOMETRIC, 3, vec![2]));
+        wide.push((TAG_SAMPLES_PER_PIXEL, 3, vec![3]));
+        assert!(unsupported(1 << 30, 1, &wide));
+
+        // 第 5 条带的起点 4 x 2^30 溢出 u32
+        assert!(unsupported(1, i32::MAX as u32, &strips(1 << 30, 5)));
+        assert!(TiffReader::new(Cursor::new(tiff(1, i32::MAX as u32, &strips(1 << 30, 2), &[0; 4]))).is_ok());
+    }
+
+    #[test]
+    fn test_packbits_tiles_and_rgb() {
+        // 3x2 RGB，2x2 瓦片（右侧瓦片按完整宽度存储），PackBits 压缩
+        let red = [255u8, 0, 0];
+        let white = [255u8, 255, 255];
+        let packed_left = [5, 255, 0, 0, 255, 0, 0, -5i8 as u8, 255];
+        let packed_right = [-5i8 as u8, 0, -5i8 as u8, 0];
+        let mut data = packed_left.to_vec();
+        data.extend(packed_right);
+        let tags = vec![
+            (TAG_BITS_PER_SAMPLE, 3, vec![8]),
+            (TAG_COMPRESSION, 3, vec![COMPRESSION_PACKBITS]),
+            (TAG_PHOTOMETRIC, 3, vec![2]),
+            (TAG_SAMPLES_PER_PIXEL, 3, vec![3]),
+            (TAG_TILE_WIDTH, 3, vec![2]),
+            (TAG_TILE_LENGTH, 3, vec![2]),
+            (TAG_TILE_OFFSETS, 4, vec![8, 17]),
+            (TAG_TILE_BYTE_COUNTS, 4, vec![9, 4]),
+        ];
+        let mut reader = TiffReader::new(Cursor::new(tiff(3, 2, &tags, &data))).unwrap();
+        assert_eq!(reader.compression(), Compression::PackBits);
+        let gray = |rgb: [u8; 3]| ((rgb[0] as u32 * 299 + rgb[1] as u32 * 587 + rgb[2] as u32 * 114 + 500) / 1000) as u8;
+        // 左瓦片第一行为红色，第二行为白色；右瓦片全黑
+        assert_eq!(reader.read_rows(0, 2).unwrap(), vec![gray(red), gray(red), 0, gray(white), gray(white), 0]);
+
+        let lzw = vec![(TAG_COMPRESSION, 3, vec![5]), (TAG_STRIP_OFFSETS, 4, vec![8]), (TAG_STRIP_BYTE_COUNTS, 4, vec![1])];
+        assert!(matches!(
+            TiffReader::new(Cursor::new(tiff(1, 1, &lzw, &[0]))),
+            Err(QRDecodeError::UnsupportedFormat(_))
+        ));
+        // 不支持流式读取的 TIFF 仍能读出尺寸
+        assert_eq!(dimensions(&mut Cursor::new(tiff(7, 5, &lzw, &[0]))).unwrap(), (7, 5));
+    }
+}
@@ -0,0 +1,346 @@
+//! 大图分块扫描模块
+//!
+//! 大幅海报扫描件和显微拼接图动辄上亿像素，超出 50 MP 的限制，整幅交给 OpenCV 也会
+//! 耗尽内存。本模块按水平行带读取灰度像素，每条行带与图块等高，切成相互重叠的图块逐块
+//! 解码，结果映射回原图坐标后合并重叠区域中的重复检测。TIFF 通过 `tiff_reader` 流式
+//! 读取，峰值内存只与图像宽度和图块大小有关；其他格式先整幅解码为灰度图。图块边长按
+//! 内存上限自动收缩。
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use opencv::{
+    core::{Mat, Scalar, CV_8UC1},
+    imgcodecs::{imread, IMREAD_GRAYSCALE},
+    prelude::*,
+};
+
+use crate::error::{QRDecodeError, Result};
+use crate::pyramid::{self, Tile};
+use crate::qr_decoder::QRDecoder;
+use crate::tiff_reader::{self, TiffReader};
+use crate::types::QRCodeResult;
+
+/// 默认图块边长（像素）
+pub const DEFAULT_SCAN_TILE: i32 = 2048;
+
+/// 默认相邻图块重叠宽度（像素），边长不超过该值的二维码总能完整落在某个图块中
+pub const DEFAULT_SCAN_OVERLAP: i32 = 256;
+
+/// 默认内存上限（MB）
+pub const DEFAULT_MAX_MEMORY_MB: usize = 1024;
+
+/// 内存不足时图块边长收缩的下限
+pub const MIN_SCAN_TILE: i32 = 256;
+
+/// 单个图块解码时的工作内存约为图块灰度字节数的倍数（检测器的彩色副本和中间图像）
+const WORKING_FACTOR: usize = 16;
+
+/// 图块边长收缩的步长
+const SHRINK_STEP: i32 = 64;
+
+/// 按行带读取的灰度图像来源
+pub enum RowSource {
+    /// 流式读取的 TIFF
+    Tiff(TiffReader<BufReader<File>>),
+    /// 整幅解码的灰度图像
+    Decoded(Mat),
+}
+
+impl RowSource {
+    /// 打开图像，TIFF 优先流式读取，不支持流式读取的 TIFF 和其他格式整幅解码
+    ///
+    /// 整幅解码前先从文件头读出尺寸，灰度图已超出 `limit` 字节时直接报错，不做解码。
+    pub fn open(path: &Path, limit: usize) -> Result<Self> {
+        let is_tiff = path
+            .extension()
+            .map(|ext| matches!(ext.to_string_lossy().to_lowercase().as_str(), "tif" | "tiff"))
+            .unwrap_or(false);
+        if is_tiff {
+            match TiffReader::open(path) {
+                Ok(reader) => return Ok(RowSource::Tiff(reader)),
+                Err(QRDecodeError::UnsupportedFormat(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let file = File::open(path)
+            .map_err(|e| QRDecodeError::invalid_input(format!("无法打开图像 {}: {}", path.display(), e)))?;
+        let mut file = BufReader::new(file);
+        if let Some((width, height)) = header_dimensions(&mut file)? {
+            let bytes = width as usize * height as usize;
+            if bytes > limit {
+                return Err(QRDecodeError::invalid_input(format!(
+                    "内存上限 {} MB 不足：{}x{} 的图像整幅解码后灰度图即占 {} MB（该格式需要整幅解码，可转换为无压缩或 PackBits 的 TIFF 流式读取）",
+                    limit >> 20,
+                    width,
+                    height,
+                    bytes.div_ceil(1 << 20)
+                )));
+            }
+        }
+
+        let path_str = path.to_string_lossy();
+        let image = imread(&path_str, IMREAD_GRAYSCALE)
+            .map_err(|e| QRDecodeError::image_processing_error(format!("无法加载图像 {}: {}", path_str, e)))?;
+        if image.empty() {
+            return Err(QRDecodeError::image_processing_error(format!("加载的图像为空: {}", path_str)));
+        }
+        Ok(RowSource::Decoded(image))
+    }
+
+    /// 是否流式读取
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, RowSource::Tiff(_))
+    }
+
+    /// 图像宽度
+    pub fn width(&self) -> i32 {
+        match self {
+            RowSource::Tiff(reader) => reader.width(),
+            RowSource::Decoded(image) => image.cols(),
+        }
+    }
+
+    /// 图像高度
+    pub fn height(&self) -> i32 {
+        match self {
+            RowSource::Tiff(reader) => reader.height(),
+            RowSource::Decoded(image) => image.rows(),
+        }
+    }
+
+    /// 扫描期间常驻的内存字节数（不含行带和图块）
+    pub fn resident_bytes(&self) -> usize {
+        match self {
+            RowSource::Tiff(reader) => reader.index_bytes(),
+            RowSource::Decoded(image) => image.total() * image.elem_size().unwrap_or(1),
+        }
+    }
+
+    /// 读取 [y, y + rows) 行的灰度像素，按行连续存放
+    pub fn read_rows(&mut self, y: i32, rows: i32) -> Result<Vec<u8>> {
+        match self {
+            RowSource::Tiff(reader) => reader.read_rows(y, rows),
+            RowSource::Decoded(image) => {
+                // imread 得到的图像连续存放，直接复制所需的行
+                let width = image.cols() as usize;
+                let band = image
+                    .data_bytes()?
+                    .get(y as usize * width..(y + rows) as usize * width)
+                    .ok_or_else(|| QRDecodeError::invalid_input(format!("行 {}..{} 超出图像范围", y, y + rows)))?;
+                Ok(band.to_vec())
+            }
+        }
+    }
+}
+
+/// 从文件头读取 PNG、JPEG、BMP 和 TIFF 的尺寸，其他格式返回 `None`
+pub fn header_dimensions(reader: &mut (impl Read + Seek)) -> Result<Option<(u32, u32)>> {
+    let mut magic = [0u8; 4];
+    if reader.read_exact(&mut magic).is_err() {
+        return Ok(None);
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    let be32 = |bytes: &[u8]| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let le32 = |bytes: &[u8]| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).unsigned_abs();
+    match magic {
+        [0x89, b'P', b'N', b'G'] => {
+            // 签名 8 字节之后的第一个块是 IHDR：长度、类型、宽、高
+            let mut header = [0u8; 24];
+            reader.read_exact(&mut header)?;
+            Ok(Some((be32(&header[16..20]), be32(&header[20..24]))))
+        }
+        [0xFF, 0xD8, ..] => jpeg_dimensions(reader),
+        [b'B', b'M', ..] => {
+            let mut header = [0u8; 26];
+            reader.read_exact(&mut header)?;
+            // 高度为负表示自上而下存放
+            Ok(Some((le32(&header[18..22]), le32(&header[22..26]))))
+        }
+        [b'I', b'I', ..] | [b'M', b'M', ..] => match tiff_reader::dimensions(reader) {
+            Ok(dimensions) => Ok(Some(dimensions)),
+            Err(QRDecodeError::UnsupportedFormat(_)) => Ok(None),
+            Err(e) => Err(e),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// 逐个跳过 JPEG 标记段，直到帧头 (SOFn)
+fn jpeg_dimensions(reader: &mut (impl Read + Seek)) -> Result<Option<(u32, u32)>> {
+    reader.seek(SeekFrom::Start(2))?;
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte)?;
+        if byte[0] != 0xFF {
+            return Ok(None);
+        }
+        // 标记前可以有任意个填充的 0xFF
+        let mut marker = 0xFF;
+        while marker == 0xFF {
+            reader.read_exact(&mut byte)?;
+            marker = byte[0];
+        }
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            continue;
+        }
+        let mut length = [0u8; 2];
+        reader.read_exact(&mut length)?;
+        let length = u16::from_be_bytes(length);
+        if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            // 精度 1 字节，之后是高和宽
+            let mut frame = [0u8; 5];
+            reader.read_exact(&mut frame)?;
+            let height = u16::from_be_bytes([frame[1], frame[2]]) as u32;
+            let width = u16::from_be_bytes([frame[3], frame[4]]) as u32;
+            return Ok(Some((width, height)));
+        }
+        if marker == 0xD9 || length < 2 {
+            return Ok(None);
+        }
+        reader.seek(SeekFrom::Current(length as i64 - 2))?;
+    }
+}
+
+/// 估算峰值内存：常驻内存、一条行带和单个图块的工作内存
+pub fn peak_bytes(width: i32, tile_size: i32, resident: usize) -> usize {
+    let tile = tile_size as usize;
+    resident + width as usize * tile + WORKING_FACTOR * tile * tile
+}
+
+/// 在内存上限内选择不超过 `requested` 的最大图块边长
+pub fn fit_tile_size(width: i32, requested: i32, overlap: i32, resident: usize, limit: usize) -> Result<i32> {
+    let minimum = MIN_SCAN_TILE.max(overlap * 2);
+    let fits = |tile: i32| peak_bytes(width, tile, resident) <= limit;
+    if !fits(minimum) {
+        return Err(QRDecodeError::invalid_input(format!(
+            "内存上限 {} MB 不足：宽 {} 像素的图像按 {} 像素的图块扫描至少需要 {} MB",
+            limit >> 20,
+            width,
+            minimum,
+            peak_bytes(width, minimum, resident).div_ceil(1 << 20)
+        )));
+    }
+    let mut tile = requested.max(minimum);
+    while !fits(tile) {
+        tile = (tile - SHRINK_STEP).max(minimum);
+    }
+    Ok(tile)
+}
+
+/// 切分相互重叠的图块，按行带从上到下排列
+pub fn plan(width: i32, height: i32, tile_size: i32, overlap: i32) -> Vec<Tile> {
+    let stride = (tile_size - overlap).max(1);
+    let mut tiles = Vec::new();
+    for y in pyramid::starts(height, tile_size, stride) {
+        for x in pyramid::starts(width, tile_size, stride) {
+            tiles.push(Tile {
+                scale: 1.0,
+                x,
+                y,
+                width: tile_size.min(width),
+                height: tile_size.min(height),
+            });
+        }
+    }
+    tiles
+}
+
+/// 逐行带逐图块解码，结果为原图坐标
+///
+/// `attach_matrices` 为真时在图块上采样模块矩阵，之后不再需要整幅图像。
+/// `progress` 在每条行带开始时调用，参数为已完成和总图块数。
+pub fn scan(
+    source: &mut RowSource,
+    decoder: &mut QRDecoder,
+    tile_size: i32,
+    overlap: i32,
+    attach_matrices: bool,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Vec<QRCodeResult>> {
+    let width = source.width();
+    let tiles = plan(width, source.height(), tile_size, overlap);
+    let mut results = Vec::new();
+    let mut band: Option<(i32, Vec<u8>)> = None;
+    for (index, tile) in tiles.iter().enumerate() {
+        if band.as_ref().map(|(y, _)| *y) != Some(tile.y) {
+            progress(index, tiles.len());
+            // 先释放上一条行带，峰值内存只含一条
+            drop(band.take());
+            band = Some((tile.y, source.read_rows(tile.y, tile.height)?));
+        }
+        let Some((_, rows)) = band.as_ref() else { continue };
+
+        let mut gray = Mat::new_rows_cols_with_default(tile.height, tile.width, CV_8UC1, Scalar::all(0.0))?;
+        let bytes = gray.data_bytes_mut()?;
+        for (row, target) in bytes.chunks_exact_mut(tile.width as usize).enumerate() {
+            let start = row * width as usize + tile.x as usize;
+            target.copy_from_slice(&rows[start..start + tile.width as usize]);
+        }
+        results.extend(tile.decode(decoder, &gray, attach_matrices)?);
+    }
+    progress(tiles.len(), tiles.len());
+    Ok(pyramid::merge_detections(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_overlaps_and_covers() {
+        let tiles = plan(5000, 3000, 2048, 256);
+        let xs: Vec<i32> = tiles.iter().filter(|t| t.y == 0).map(|t| t.x).collect();
+        assert_eq!(xs, vec![0, 1792, 2952]);
+        let ys: Vec<i32> = tiles.iter().filter(|t| t.x == 0).map(|t| t.y).collect();
+        assert_eq!(ys, vec![0, 952]);
+        assert!(tiles.iter().all(|t| t.width == 2048 && t.height == 2048));
+
+        // 小图只有一个图块
+        assert_eq!(plan(800, 600, 2048, 256), vec![Tile { scale: 1.0, x: 0, y: 0, width: 800, height: 600 }]);
+    }
+
+    #[test]
+    fn test_header_dimensions() {
+        use std::io::Cursor;
+        let dimensions = |bytes: Vec<u8>| header_dimensions(&mut Cursor::new(bytes)).unwrap();
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        png.extend(b"IHDR");
+        png.extend(60000u32.to_be_bytes());
+        png.extend(40000u32.to_be_bytes());
+        assert_eq!(dimensions(png), Some((60000, 40000)));
+
+        // SOI、带填充字节的 APP0 段、SOF2
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xFF, 0xE0, 0, 4, 0, 0, 0xFF, 0xC2, 0, 11, 8];
+        jpeg.extend(3000u16.to_be_bytes());
+        jpeg.extend(4000u16.to_be_bytes());
+        assert_eq!(dimensions(jpeg), Some((4000, 3000)));
+
+        let mut bmp = vec![0u8; 26];
+        bmp[..2].copy_from_slice(b"BM");
+        bmp[18..22].copy_from_slice(&5000i32.to_le_bytes());
+        bmp[22..26].copy_from_slice(&(-2000i32).to_le_bytes());
+        assert_eq!(dimensions(bmp), Some((5000, 2000)));
+
+        assert_eq!(dimensions(b"RIFF....WEBP".to_vec()), None);
+        assert_eq!(dimensions(vec![0xFF]), None);
+    }
+
+    #[test]
+    fn test_fit_tile_size_respects_limit() {
+        let limit = 256 << 20;
+        assert_eq!(fit_tile_size(5000, 2048, 256, 0, limit).unwrap(), 2048);
+
+        // 宽 100000 像素：2048 高的行带约 195 MB，加上图块工作内存超出上限
+        let tile = fit_tile_size(100_000, 2048, 256, 0, limit).unwrap();
+        assert!((MIN_SCAN_TILE..2048).contains(&tile));
+        assert!(peak_bytes(100_000, tile, 0) <= limit);
+        assert!(peak_bytes(100_000, tile + SHRINK_STEP, 0) > limit);
+
+        // 整幅解码的常驻内存已超出上限
+        assert!(fit_tile_size(20_000, 2048, 256, 400 << 20, limit).is_err());
+    }
+}
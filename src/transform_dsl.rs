@@ -0,0 +1,313 @@
+//! 命令行变换链模块
+//!
+//! `--transforms "gray,clahe:2.0,gamma:0.8,otsu"` 按顺序叠加逗号分隔的变换，每个变换写作
+//! `名称[:参数[:参数...]]`，省略的参数取常用默认值。名称覆盖增强解码的全部变换以及暴力
+//! 破解中的缩放、灰度、二值化、光照校正、反色、镜像和旋转，解析结果为一条变换链。
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::binarization::BinarizationMethod;
+use crate::deblur::{BlurKernel, DeconvolutionMethod};
+use crate::enhanced_processor::TransformType;
+use crate::error::{QRDecodeError, Result};
+use crate::glare::DEFAULT_GLARE_THRESHOLD;
+use crate::halftone::HalftoneRepair;
+use crate::illumination::BackgroundEstimate;
+use crate::moire::MoireFilter;
+use crate::pipeline;
+
+/// 可用变换的写法和说明，用于帮助信息和错误提示
+pub const DSL_TRANSFORMS: &[(&str, &str)] = &[
+    ("original", "原图，不做处理"),
+    ("gray", "灰度转换"),
+    ("scale:比例", "缩放 (0.1-4.0)"),
+    ("brightness:值", "亮度调整 (-255-255)"),
+    ("contrast:系数", "对比度调整"),
+    ("gamma:值", "伽马校正"),
+    ("blur[:核]", "高斯模糊 (默认 5)"),
+    ("median[:核]", "中值滤波 (默认 3)"),
+    ("bilateral", "双边滤波"),
+    ("open", "形态学开运算"),
+    ("close", "形态学闭运算"),
+    ("sharpen", "锐化"),
+    ("invert", "反色"),
+    ("mirror[:h|v]", "水平或垂直镜像 (默认 h)"),
+    ("rotate:角度", "旋转 (-180-180)"),
+    ("clahe[:限幅[:网格]]", "CLAHE (默认 2.0 / 8)"),
+    ("tonemap[:强度]", "局部色调映射 (默认 0.7)"),
+    ("otsu", "Otsu 全局二值化"),
+    ("sauvola[:窗口[:k]]", "Sauvola 局部二值化 (默认 k 0.34)"),
+    ("wolf[:窗口[:k]]", "Wolf-Jolion 局部二值化 (默认 k 0.5)"),
+    ("niblack[:窗口[:k]]", "Niblack 局部二值化 (默认 k -0.2)"),
+    ("multiotsu[:类数[:分界]]", "多级 Otsu (默认 3 类 / 第 1 分界)"),
+    ("illumination[:close|blackhat|blur[:核]]", "光照不均校正 (默认 close / 自动核)"),
+    ("glare[:阈值]", "去除反光 (默认 235)"),
+    ("notch[:最低频率]", "摩尔纹陷波滤波 (默认 0.15 周期/像素)"),
+    ("lowpass[:截止频率]", "摩尔纹低通滤波 (默认 0.2 周期/像素)"),
+    ("deblur[:snr]", "估计运动模糊后维纳去模糊 (默认 SNR 100)"),
+    ("defocus:半径", "散焦去模糊"),
+    ("motion:长度:角度", "运动去模糊"),
+    ("logo", "覆盖中心 logo"),
+    ("halftone[:close|centre]", "点状模块修复 (默认 close)"),
+];
+
+/// 默认的维纳滤波信噪比
+const DEFAULT_SNR: f64 = 100.0;
+
+/// 解析变换链，`window` 为局部二值化未指定窗口时使用的大小
+pub fn parse_transforms(text: &str, window: i32) -> Result<Vec<TransformType>> {
+    if text.trim().is_empty() {
+        return Err(QRDecodeError::invalid_input("--transforms 不能为空".to_string()));
+    }
+
+    text.split(',')
+        .enumerate()
+        .map(|(i, token)| {
+            parse_transform(token.trim(), window)
+                .map_err(|e| QRDecodeError::invalid_input(format!("--transforms 第 {} 步 \"{}\": {}", i + 1, token.trim(), e)))
+        })
+        .collect()
+}
+
+/// 解析并校验单个变换，如 `sauvola:41`
+pub fn parse_transform(token: &str, window: i32) -> std::result::Result<TransformType, String> {
+    parse_step(token, window).and_then(|transform| pipeline::validate_transform(&transform).map(|_| transform))
+}
+
+/// 第 `index` 步（从 1 开始）的中间结果保存路径：`out.png` → `out_step01.png`
+pub fn step_output_path(base: &Path, index: usize) -> PathBuf {
+    let stem = base.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "processed".to_string());
+    let extension = base.extension().map(|e| e.to_string_lossy().into_owned()).unwrap_or_else(|| "png".to_string());
+    base.with_file_name(format!("{}_step{:02}.{}", stem, index, extension))
+}
+
+/// 解析单个变换
+fn parse_step(token: &str, window: i32) -> std::result::Result<TransformType, String> {
+    if token.is_empty() {
+        return Err("变换为空（多余的逗号？）".to_string());
+    }
+    let mut parts = token.split(':').map(str::trim);
+    let name = parts.next().unwrap_or_default().to_lowercase();
+    let args = StepArgs { values: parts.collect() };
+
+    let transform = match name.as_str() {
+        "original" | "none" => args.none(TransformType::Original)?,
+        "gray" | "grey" | "grayscale" => args.none(TransformType::Grayscale)?,
+        "scale" => TransformType::Scale(args.required(0, "缩放比例")?),
+        "brightness" => TransformType::Brightness(args.required(0, "亮度")?),
+        "contrast" => TransformType::Contrast(args.required(0, "对比度系数")?),
+        "gamma" => TransformType::Gamma(args.required(0, "伽马值")?),
+        "blur" => TransformType::GaussianBlur(args.optional(0, 5, "核大小")?),
+        "median" => TransformType::MedianBlur(args.optional(0, 3, "核大小")?),
+        "bilateral" => args.none(TransformType::BilateralFilter)?,
+        "open" => args.none(TransformType::MorphOpen)?,
+        "close" => args.none(TransformType::MorphClose)?,
+        "sharpen" => args.none(TransformType::Sharpen)?,
+        "invert" => args.none(TransformType::Invert)?,
+        "mirror" => match args.keyword(0, &["h", "v"])? {
+            Some("v") => TransformType::MirrorVertical,
+            _ => TransformType::MirrorHorizontal,
+        },
+        "rotate" => TransformType::Rotate(args.required(0, "旋转角度")?),
+        "clahe" => TransformType::Clahe(args.optional(0, 2.0, "限幅")?, args.optional(1, 8, "网格大小")?),
+        "tonemap" => TransformType::LocalToneMapping(args.optional(0, 0.7, "强度")?),
+        "otsu" => args.none(TransformType::Binarize(BinarizationMethod::Otsu))?,
+        "sauvola" => TransformType::Binarize(BinarizationMethod::Sauvola {
+            window: args.optional(0, window, "窗口")?,
+            k: args.optional(1, 0.34, "k")?,
+        }),
+        "wolf" => TransformType::Binarize(BinarizationMethod::Wolf {
+            window: args.optional(0, window, "窗口")?,
+            k: args.optional(1, 0.5, "k")?,
+        }),
+        "niblack" => TransformType::Binarize(BinarizationMethod::Niblack {
+            window: args.optional(0, window, "窗口")?,
+            k: args.optional(1, -0.2, "k")?,
+        }),
+        "multiotsu" => TransformType::Binarize(BinarizationMethod::MultiOtsu {
+            classes: args.optional(0, 3, "类数")?,
+            level: args.optional::<u8>(1, 1, "分界序号")?.checked_sub(1).ok_or("分界序号从 1 开始")?,
+        }),
+        "illumination" => {
+            let size = args.optional(1, 0, "核大小")?;
+            TransformType::IlluminationCorrection(match args.keyword(0, &["close", "blackhat", "blur"])? {
+                Some("blackhat") => BackgroundEstimate::BlackHat(size),
+                Some("blur") => BackgroundEstimate::Blur(size),
+                _ => BackgroundEstimate::MorphClose(size),
+            })
+        }
+        "glare" => TransformType::GlareRemoval(args.optional(0, DEFAULT_GLARE_THRESHOLD, "阈值")?),
+        "notch" => TransformType::MoireSuppression(MoireFilter::Notch { min_frequency: args.optional(0, 0.15, "最低频率")? }),
+        "lowpass" => TransformType::MoireSuppression(MoireFilter::LowPass { cutoff: args.optional(0, 0.2, "截止频率")? }),
+        "deblur" => TransformType::DeblurEstimated(DeconvolutionMethod::Wiener { snr: args.optional(0, DEFAULT_SNR, "信噪比")? }),
+        "defocus" => TransformType::Deblur(
+            BlurKernel::Defocus { radius: args.required(0, "散焦半径")? },
+            DeconvolutionMethod::Wiener { snr: DEFAULT_SNR },
+        ),
+        "motion" => TransformType::Deblur(
+            BlurKernel::Motion { length: args.required(0, "运动长度")?, angle: args.required(1, "运动角度")? },
+            DeconvolutionMethod::Wiener { snr: DEFAULT_SNR },
+        ),
+        "logo" => args.none(TransformType::LogoInpainting)?,
+        "halftone" => TransformType::Halftone(match args.keyword(0, &["close", "centre", "center"])? {
+            Some("centre") | Some("center") => HalftoneRepair::CentreSampling,
+            _ => HalftoneRepair::MorphClose,
+        }),
+        _ => return Err(unknown_transform(&name)),
+    };
+
+    args.check_count(&name)?;
+    Ok(transform)
+}
+
+/// 单个变换冒号后的参数
+struct StepArgs<'a> {
+    values: Vec<&'a str>,
+}
+
+impl<'a> StepArgs<'a> {
+    /// 不接受参数的变换
+    fn none(&self, transform: TransformType) -> std::result::Result<TransformType, String> {
+        if self.values.is_empty() {
+            Ok(transform)
+        } else {
+            Err("该变换不接受参数".to_string())
+        }
+    }
+
+    fn required<T: FromStr>(&self, index: usize, what: &str) -> std::result::Result<T, String> {
+        match self.values.get(index) {
+            Some(value) => parse_value(value, what),
+            None => Err(format!("缺少参数: {}", what)),
+        }
+    }
+
+    fn optional<T: FromStr>(&self, index: usize, default: T, what: &str) -> std::result::Result<T, String> {
+        match self.values.get(index) {
+            Some(value) if !value.is_empty() => parse_value(value, what),
+            _ => Ok(default),
+        }
+    }
+
+    fn keyword(&self, index: usize, allowed: &[&'a str]) -> std::result::Result<Option<&'a str>, String> {
+        match self.values.get(index) {
+            None => Ok(None),
+            Some(value) => {
+                let value = value.to_lowercase();
+                allowed
+                    .iter()
+                    .find(|&&keyword| keyword == value)
+                    .map(|&keyword| Some(keyword))
+                    .ok_or_else(|| format!("无效的选项 \"{}\" (可选: {})", value, allowed.join(", ")))
+            }
+        }
+    }
+
+    /// 参数个数不能超过该变换在 `DSL_TRANSFORMS` 中声明的个数
+    fn check_count(&self, name: &str) -> std::result::Result<(), String> {
+        let syntax = DSL_TRANSFORMS
+            .iter()
+            .map(|&(syntax, _)| syntax)
+            .find(|syntax| dsl_name(syntax) == canonical_name(name))
+            .unwrap_or_default();
+        let max = syntax.matches(':').count();
+        if self.values.len() > max {
+            Err(format!("参数过多 (写法: {})", syntax))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(value: &str, what: &str) -> std::result::Result<T, String> {
+    value.parse().map_err(|_| format!("无效的{} \"{}\"", what, value))
+}
+
+/// 别名对应的规范名称
+fn canonical_name(name: &str) -> &str {
+    match name {
+        "none" => "original",
+        "grey" | "grayscale" => "gray",
+        other => other,
+    }
+}
+
+/// 写法中的变换名
+fn dsl_name(syntax: &str) -> &str {
+    syntax.split([':', '[']).next().unwrap_or(syntax)
+}
+
+/// 未知变换的错误信息，拼写相近时给出建议
+fn unknown_transform(name: &str) -> String {
+    let closest = DSL_TRANSFORMS
+        .iter()
+        .map(|&(syntax, _)| dsl_name(syntax))
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .min();
+    match closest {
+        Some((distance, candidate)) if distance <= 2 => format!("未知的变换 \"{}\"，是否想用 \"{}\"?", name, candidate),
+        _ => {
+            let names: Vec<&str> = DSL_TRANSFORMS.iter().map(|&(syntax, _)| dsl_name(syntax)).collect();
+            format!("未知的变换 \"{}\" (可用: {})", name, names.join(", "))
+        }
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_transforms() {
+        let chain = parse_transforms("gray, clahe:2.5, gamma:0.8, otsu, mirror:v, sauvola::0.3", 41).unwrap();
+        assert_eq!(
+            chain,
+            vec![
+                TransformType::Grayscale,
+                TransformType::Clahe(2.5, 8),
+                TransformType::Gamma(0.8),
+                TransformType::Binarize(BinarizationMethod::Otsu),
+                TransformType::MirrorVertical,
+                TransformType::Binarize(BinarizationMethod::Sauvola { window: 41, k: 0.3 }),
+            ]
+        );
+        assert_eq!(parse_transforms("halftone:centre", 31).unwrap(), vec![TransformType::Halftone(HalftoneRepair::CentreSampling)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let message = |text: &str| parse_transforms(text, 31).unwrap_err().to_string();
+
+        assert!(message("gray,gama:0.8").contains("第 2 步") && message("gray,gama:0.8").contains("\"gamma\""));
+        assert!(message("gamma").contains("缺少参数"));
+        assert!(message("gamma:x").contains("无效的伽马值"));
+        assert!(message("sharpen:2").contains("不接受参数"));
+        assert!(message("clahe:2:8:1").contains("参数过多"));
+        assert!(message("blur:4").contains("奇数"));
+        assert!(message("mirror:d").contains("h, v"));
+        assert_eq!(parse_transforms("mirror", 31).unwrap(), vec![TransformType::MirrorHorizontal]);
+        assert!(message("gray,,otsu").contains("多余的逗号"));
+        assert!(message("multiotsu:3:0").contains("从 1 开始"));
+    }
+
+    #[test]
+    fn test_step_output_path() {
+        assert_eq!(step_output_path(Path::new("out/processed.jpg"), 3), PathBuf::from("out/processed_step03.jpg"));
+        assert_eq!(step_output_path(Path::new("processed"), 12), PathBuf::from("processed_step12.png"));
+    }
+}
@@ -0,0 +1,170 @@
+//! 变换统计持久化模块
+//!
+//! 增强解码按固定顺序逐个尝试变换，而不同来源的图像（屏幕翻拍、票据、艺术二维码）
+//! 真正起作用的变换差别很大。本模块按配置名分别记录每个步骤的尝试次数、成功次数和
+//! 平均耗时，保存到 JSON 文件供之后的运行使用，并按单位耗时的成功概率重新排列步骤。
+//! 成功概率以本配置的整体成功率为先验做平滑，没有记录的步骤取先验值和平均耗时，
+//! 相互之间保持原有顺序。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{QRDecodeError, Result};
+
+/// 未指定 `--profile` 时使用的配置名
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 先验成功率的等效尝试次数，尝试次数越多越接近实际成功率
+const PRIOR_WEIGHT: f64 = 4.0;
+
+/// 没有任何记录时的先验成功率
+const DEFAULT_SUCCESS_RATE: f64 = 0.05;
+
+/// 没有任何记录时的平均耗时（秒）
+const DEFAULT_COST: f64 = 0.05;
+
+/// 单个步骤的累计统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepStats {
+    /// 尝试次数
+    pub attempts: usize,
+    /// 解码成功次数
+    pub successes: usize,
+    /// 累计耗时（秒，含变换和解码）
+    pub total_seconds: f64,
+}
+
+impl StepStats {
+    /// 平均耗时（秒），没有尝试过时为 None
+    pub fn average_cost(&self) -> Option<f64> {
+        (self.attempts > 0).then(|| self.total_seconds / self.attempts as f64)
+    }
+
+    /// 以 `prior` 为先验平滑后的成功概率
+    pub fn success_rate(&self, prior: f64) -> f64 {
+        (self.successes as f64 + prior * PRIOR_WEIGHT) / (self.attempts as f64 + PRIOR_WEIGHT)
+    }
+}
+
+/// 按配置名分组的步骤统计，键为步骤描述
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransformStats {
+    /// 配置名 → 步骤描述 → 统计
+    pub profiles: BTreeMap<String, BTreeMap<String, StepStats>>,
+}
+
+impl TransformStats {
+    /// 从 JSON 文件加载统计，文件不存在时返回空统计
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            QRDecodeError::invalid_input(format!("无法读取变换统计文件 {}: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&text)
+            .map_err(|e| QRDecodeError::invalid_input(format!("变换统计文件 {} 无效: {}", path.display(), e)))
+    }
+
+    /// 以 JSON 格式保存统计
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| QRDecodeError::output_error(format!("变换统计序列化失败: {}", e)))?;
+        std::fs::write(path, json)
+            .map_err(|e| QRDecodeError::output_error(format!("无法写入变换统计文件 {}: {}", path.display(), e)))
+    }
+
+    /// 记录一次尝试
+    pub fn record(&mut self, profile: &str, step: &str, success: bool, seconds: f64) {
+        let stats = self
+            .profiles
+            .entry(profile.to_string())
+            .or_default()
+            .entry(step.to_string())
+            .or_default();
+        stats.attempts += 1;
+        stats.successes += usize::from(success);
+        stats.total_seconds += seconds;
+    }
+
+    /// 某个配置下的步骤统计
+    pub fn steps(&self, profile: &str) -> Option<&BTreeMap<String, StepStats>> {
+        self.profiles.get(profile)
+    }
+
+    /// 某个配置下各步骤的成功次数，格式与增强解码的成功统计相同
+    pub fn success_counts(&self, profile: &str) -> HashMap<String, usize> {
+        self.steps(profile)
+            .into_iter()
+            .flatten()
+            .filter(|(_, stats)| stats.successes > 0)
+            .map(|(step, stats)| (step.clone(), stats.successes))
+            .collect()
+    }
+
+    /// 单位耗时的成功概率，`key` 为步骤描述
+    pub fn score(&self, profile: &str, key: &str) -> f64 {
+        let steps = self.steps(profile);
+        let (prior, mean_cost) = steps.map_or((DEFAULT_SUCCESS_RATE, DEFAULT_COST), profile_baseline);
+        match steps.and_then(|steps| steps.get(key)) {
+            Some(stats) => stats.success_rate(prior) / stats.average_cost().unwrap_or(mean_cost).max(f64::EPSILON),
+            None => prior / mean_cost.max(f64::EPSILON),
+        }
+    }
+
+    /// 按单位耗时的成功概率从高到低重新排列，分数相同时保持原有顺序
+    pub fn reorder<T>(&self, profile: &str, items: Vec<T>, key: impl Fn(&T) -> String) -> Vec<T> {
+        let mut scored: Vec<(f64, T)> = items.into_iter().map(|item| (self.score(profile, &key(&item)), item)).collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+/// 配置的整体成功率和平均耗时，作为没有记录的步骤的先验
+fn profile_baseline(steps: &BTreeMap<String, StepStats>) -> (f64, f64) {
+    let attempts: usize = steps.values().map(|stats| stats.attempts).sum();
+    if attempts == 0 {
+        return (DEFAULT_SUCCESS_RATE, DEFAULT_COST);
+    }
+    let successes: usize = steps.values().map(|stats| stats.successes).sum();
+    let seconds: f64 = steps.values().map(|stats| stats.total_seconds).sum();
+    (successes as f64 / attempts as f64, seconds / attempts as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder_prefers_cheap_successful_steps() {
+        let mut stats = TransformStats::default();
+        for _ in 0..10 {
+            stats.record("screen", "原始图像", false, 0.20);
+            stats.record("screen", "CLAHE", true, 0.02);
+            stats.record("screen", "去模糊", true, 0.50);
+        }
+        let steps = vec!["原始图像", "去模糊", "未记录", "CLAHE"];
+        let ordered = stats.reorder("screen", steps.clone(), |step| step.to_string());
+        assert_eq!(ordered, vec!["CLAHE", "未记录", "去模糊", "原始图像"]);
+
+        // 其他配置没有记录，保持原有顺序
+        assert_eq!(stats.reorder("ticket", steps.clone(), |step| step.to_string()), steps);
+    }
+
+    #[test]
+    fn test_round_trip_json() {
+        let mut stats = TransformStats::default();
+        stats.record(DEFAULT_PROFILE, "Otsu", true, 0.03);
+        stats.record(DEFAULT_PROFILE, "Otsu", false, 0.01);
+        let json = serde_json::to_string(&stats).unwrap();
+        let loaded: TransformStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, stats);
+
+        let step = &loaded.steps(DEFAULT_PROFILE).unwrap()["Otsu"];
+        assert_eq!((step.attempts, step.successes), (2, 1));
+        assert!((step.average_cost().unwrap() - 0.02).abs() < 1e-12);
+        assert_eq!(loaded.success_counts(DEFAULT_PROFILE)["Otsu"], 1);
+    }
+}
@@ -8,6 +8,19 @@ use std::path::PathBuf;
 
 use crate::cli::Args;
 use crate::error::{QRDecodeError, Result};
+use crate::binarization::DEFAULT_BINARIZATION_WINDOW;
+use crate::brute_force_decoder::TransformParams;
+use crate::composition::DEFAULT_MAX_COMPOSITIONS;
+use crate::enhanced_processor::TransformType;
+use crate::inspection::InspectionReport;
+use crate::local_contrast::{DEFAULT_CLAHE_CLIP_LIMIT, DEFAULT_CLAHE_TILE_SIZE};
+use crate::pyramid::{DEFAULT_MAX_SCALE, DEFAULT_TILE_SIZE};
+use crate::tiled::{DEFAULT_MAX_MEMORY_MB, DEFAULT_SCAN_OVERLAP, DEFAULT_SCAN_TILE};
+use crate::module_matrix::ModuleMatrix;
+use crate::quality::QualityReport;
+use crate::search_space::SearchAxis;
+use crate::search_strategy::SearchStrategy;
+use crate::qr_layout::EcLevel;
 
 /// 简化的二维码解码结果（用于批量处理）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +29,9 @@ pub struct QrResult {
     pub content: String,
     /// 二维码角点坐标 (可选)
     pub points: Option<Vec<(f32, f32)>>,
+    /// 解码成功的变换配方 (可选)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipe: Option<TransformParams>,
 }
 
 /// 二维码在图像中的位置信息
@@ -80,6 +96,45 @@ pub struct QRCodeResult {
     pub timestamp: DateTime<Utc>,
     /// 原始字节数据 (可选)
     pub raw_bytes: Option<Vec<u8>>,
+    /// 采样得到的模块矩阵 (可选)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_matrix: Option<ModuleMatrix>,
+    /// Reed-Solomon 纠错报告 (仅模块矩阵直接解码时提供)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correction: Option<CorrectionReport>,
+    /// 隐藏数据检查报告 (仅启用 --inspect 时提供)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inspection: Option<InspectionReport>,
+    /// 彩色分层标签 (仅启用 --color-layers 时提供，如 cluster-R)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    /// 暴力破解成功的变换配方 (仅暴力破解或配方重放解码时提供)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipe: Option<TransformParams>,
+    /// 图像质量评估报告 (仅启用 --auto 时提供)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<QualityReport>,
+}
+
+/// Reed-Solomon 纠错报告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorrectionReport {
+    /// 版本号
+    pub version: u8,
+    /// 纠错等级
+    pub ec_level: EcLevel,
+    /// 掩码编号
+    pub mask: u8,
+    /// 标记为未知的模块数
+    pub unknown_modules: usize,
+    /// 参与纠正的擦除码字数（标记为不可读的码字全部计入）
+    pub erasures_corrected: usize,
+    /// 纠正的错误码字数
+    pub errors_corrected: usize,
+    /// 纠错块数
+    pub blocks: usize,
+    /// 每块纠错码字数（每块满足 擦除 + 2×错误 ≤ 该值时可纠正）
+    pub ecc_per_block: usize,
 }
 
 impl QRCodeResult {
@@ -97,6 +152,12 @@ impl QRCodeResult {
             qr_type: qr_type.into(),
             timestamp: Utc::now(),
             raw_bytes: None,
+            module_matrix: None,
+            correction: None,
+            inspection: None,
+            layer: None,
+            recipe: None,
+            quality: None,
         }
     }
     
@@ -106,6 +167,36 @@ impl QRCodeResult {
         self
     }
     
+    /// 设置采样得到的模块矩阵
+    pub fn with_module_matrix(mut self, matrix: ModuleMatrix) -> Self {
+        self.module_matrix = Some(matrix);
+        self
+    }
+    
+    /// 设置纠错报告
+    pub fn with_correction(mut self, correction: CorrectionReport) -> Self {
+        self.correction = Some(correction);
+        self
+    }
+    
+    /// 设置彩色分层标签
+    pub fn with_layer<S: Into<String>>(mut self, layer: S) -> Self {
+        self.layer = Some(layer.into());
+        self
+    }
+    
+    /// 设置解码成功的变换配方
+    pub fn with_recipe(mut self, recipe: TransformParams) -> Self {
+        self.recipe = Some(recipe);
+        self
+    }
+    
+    /// 设置图像质量评估报告
+    pub fn with_quality(mut self, quality: QualityReport) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+    
     /// 检查解码结果是否有效
     pub fn is_valid(&self) -> bool {
         !self.content.is_empty() && self.confidence > 0.0
@@ -142,6 +233,33 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+/// 模块矩阵导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatrixExportFormat {
+    /// ASCII 字符画 (█/空格)
+    Ascii,
+    /// 0/1 文本网格
+    Binary,
+    /// 重新渲染的 PNG 图像
+    Png,
+}
+
+impl std::str::FromStr for MatrixExportFormat {
+    type Err = QRDecodeError;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ascii" | "art" => Ok(MatrixExportFormat::Ascii),
+            "binary" | "01" => Ok(MatrixExportFormat::Binary),
+            "png" => Ok(MatrixExportFormat::Png),
+            _ => Err(QRDecodeError::invalid_input(format!(
+                "不支持的模块矩阵导出格式: {}",
+                s
+            ))),
+        }
+    }
+}
+
 /// 处理配置
 #[derive(Debug, Clone)]
 pub struct ProcessingConfig {
@@ -153,6 +271,8 @@ pub struct ProcessingConfig {
     pub output_format: OutputFormat,
     /// 是否启用图像预处理
     pub preprocess: bool,
+    /// 是否根据图像质量评估自动选择处理策略
+    pub auto: bool,
     /// 是否显示详细信息
     pub verbose: bool,
     /// 是否显示位置信息
@@ -171,6 +291,78 @@ pub struct ProcessingConfig {
     pub randomize: bool,
     /// 是否反色处理
     pub invert: bool,
+    /// 用户提供的四个角点 (左上、右上、右下、左下)
+    pub corners: Option<Vec<(f32, f32)>>,
+    /// 用户指定的每边模块数 (可选)
+    pub module_count: Option<usize>,
+    /// 模块矩阵导出格式 (可选)
+    pub matrix_export: Option<MatrixExportFormat>,
+    /// 模块矩阵导出路径 (可选)
+    pub matrix_output_path: Option<PathBuf>,
+    /// 输入是否为文本模块网格
+    pub grid_input: bool,
+    /// 歧义模块擦除阈值 (0 表示关闭)
+    pub ambiguity_margin: f32,
+    /// 遮挡掩码图像路径 (可选)
+    pub occlusion_mask: Option<PathBuf>,
+    /// 是否把检测到的反光区域视为擦除
+    pub glare_erasures: bool,
+    /// 是否把中心 logo 区域视为擦除
+    pub logo_erasures: bool,
+    /// 是否检查隐藏数据和非标准填充
+    pub inspect: bool,
+    /// 是否按颜色通道分层解码
+    pub color_layers: bool,
+    /// 是否多尺度金字塔分块检测
+    pub pyramid: bool,
+    /// 金字塔最大放大倍数
+    pub pyramid_max_scale: f64,
+    /// 金字塔图块边长（缩放后的像素）
+    pub pyramid_tile: i32,
+    /// 是否按行带分块扫描大图
+    pub tiled: bool,
+    /// 分块扫描的图块边长（像素）
+    pub tile_size: i32,
+    /// 相邻图块的重叠宽度（像素）
+    pub tile_overlap: i32,
+    /// 分块扫描的内存上限（MB）
+    pub max_memory_mb: usize,
+    /// 预处理时是否使用 CLAHE 代替全局直方图均衡化
+    pub clahe: bool,
+    /// CLAHE 限幅
+    pub clahe_clip_limit: f64,
+    /// CLAHE 网格大小（每边分块数）
+    pub clahe_tile_size: i32,
+    /// 预处理时的局部色调映射强度 (可选，0.0-1.0)
+    pub tone_mapping: Option<f64>,
+    /// 局部二值化窗口大小（像素）
+    pub binarization_window: i32,
+    /// 增强解码的流水线文件 (JSON)
+    pub pipeline: Option<PathBuf>,
+    /// 增强解码使用的流水线配置名
+    pub profile: Option<String>,
+    /// `--transforms` 指定的变换链，在增强解码序列之前尝试
+    pub transforms: Option<Vec<TransformType>>,
+    /// 是否只尝试 `--transforms` 指定的变换链
+    pub transforms_only: bool,
+    /// 单步变换都失败后最多尝试的组合变换链数 (0 表示关闭，None 时见 `composition_limit`)
+    pub max_compositions: Option<usize>,
+    /// 增强解码的时间预算（秒，可选）
+    pub time_budget: Option<f64>,
+    /// 跨运行的变换统计文件（可选）
+    pub transform_stats: Option<PathBuf>,
+    /// 暴力破解的搜索策略
+    pub search: SearchStrategy,
+    /// 暴力破解最多评估的参数组合数（可选，默认按策略）
+    pub search_budget: Option<usize>,
+    /// 暴力破解搜索空间文件（可选，默认使用内置搜索空间）
+    pub search_space: Option<PathBuf>,
+    /// 命令行替换或追加的搜索轴
+    pub search_axes: Vec<SearchAxis>,
+    /// 预先重放的变换配方文件（可选）
+    pub recipe: Option<PathBuf>,
+    /// 保存成功配方的文件（可选）
+    pub save_recipe: Option<PathBuf>,
 }
 
 impl Default for ProcessingConfig {
@@ -180,6 +372,7 @@ impl Default for ProcessingConfig {
             output_path: None,
             output_format: OutputFormat::Text,
             preprocess: true,
+            auto: false,
             verbose: false,
             show_position: false,
             min_confidence: 0.0,
@@ -189,6 +382,42 @@ impl Default for ProcessingConfig {
             expected_count: 1,
             randomize: false,
             invert: false,
+            corners: None,
+            module_count: None,
+            matrix_export: None,
+            matrix_output_path: None,
+            grid_input: false,
+            ambiguity_margin: 0.0,
+            occlusion_mask: None,
+            glare_erasures: false,
+            logo_erasures: false,
+            inspect: false,
+            color_layers: false,
+            pyramid: false,
+            pyramid_max_scale: DEFAULT_MAX_SCALE,
+            pyramid_tile: DEFAULT_TILE_SIZE,
+            tiled: false,
+            tile_size: DEFAULT_SCAN_TILE,
+            tile_overlap: DEFAULT_SCAN_OVERLAP,
+            max_memory_mb: DEFAULT_MAX_MEMORY_MB,
+            clahe: false,
+            clahe_clip_limit: DEFAULT_CLAHE_CLIP_LIMIT,
+            clahe_tile_size: DEFAULT_CLAHE_TILE_SIZE,
+            tone_mapping: None,
+            binarization_window: DEFAULT_BINARIZATION_WINDOW,
+            pipeline: None,
+            profile: None,
+            transforms: None,
+            transforms_only: false,
+            max_compositions: None,
+            time_budget: None,
+            transform_stats: None,
+            search: SearchStrategy::Exhaustive,
+            search_budget: None,
+            search_space: None,
+            search_axes: Vec::new(),
+            recipe: None,
+            save_recipe: None,
         }
     }
 }
@@ -201,6 +430,7 @@ impl ProcessingConfig {
             output_path: args.output_path.clone(),
             output_format: args.output_format,
             preprocess: args.preprocess,
+            auto: args.auto,
             verbose: args.verbose,
             show_position: args.show_position,
             min_confidence: args.min_confidence,
@@ -210,6 +440,42 @@ impl ProcessingConfig {
             expected_count: args.expected_count,
             randomize: args.randomize,
             invert: args.invert,
+            corners: args.corners.clone(),
+            module_count: args.module_count,
+            matrix_export: args.matrix_export,
+            matrix_output_path: args.matrix_output_path.clone(),
+            grid_input: args.grid_input,
+            ambiguity_margin: args.ambiguity_margin,
+            occlusion_mask: args.occlusion_mask.clone(),
+            glare_erasures: args.glare_erasures,
+            logo_erasures: args.logo_erasures,
+            inspect: args.inspect,
+            color_layers: args.color_layers,
+            pyramid: args.pyramid,
+            pyramid_max_scale: args.pyramid_max_scale,
+            pyramid_tile: args.pyramid_tile,
+            tiled: args.tiled,
+            tile_size: args.tile_size,
+            tile_overlap: args.tile_overlap,
+            max_memory_mb: args.max_memory_mb,
+            clahe: args.clahe,
+            clahe_clip_limit: args.clahe_clip_limit,
+            clahe_tile_size: args.clahe_tile_size,
+            tone_mapping: args.tone_mapping,
+            binarization_window: args.binarization_window,
+            pipeline: args.pipeline.clone(),
+            profile: args.profile.clone(),
+            transforms: args.transforms.clone(),
+            transforms_only: args.transforms_only,
+            max_compositions: args.max_compositions,
+            time_budget: args.time_budget,
+            transform_stats: args.transform_stats.clone(),
+            search: args.search,
+            search_budget: args.search_budget,
+            search_space: args.search_space.clone(),
+            search_axes: args.search_axes.clone(),
+            recipe: args.recipe.clone(),
+            save_recipe: args.save_recipe.clone(),
         })
     }
     
@@ -230,8 +496,33 @@ impl ProcessingConfig {
             ));
         }
         
+        // 检查局部对比度参数
+        if self.clahe_clip_limit <= 0.0 || !(1..=64).contains(&self.clahe_tile_size) {
+            return Err(QRDecodeError::invalid_input(
+                "CLAHE 限幅必须大于 0，网格大小必须在 1 到 64 之间".to_string(),
+            ));
+        }
+        
+        // 检查角点数量
+        if let Some(corners) = &self.corners {
+            if corners.len() != 4 {
+                return Err(QRDecodeError::invalid_input(format!(
+                    "需要 4 个角点，实际提供了 {} 个",
+                    corners.len()
+                )));
+            }
+        }
+        
         Ok(())
     }
+    
+    /// 实际尝试的组合变换链数
+    ///
+    /// 组合搜索的耗时可达单步变换的数倍，未指定 `--max-compositions` 时只在 `--auto` 或
+    /// `--profile` 下启用，默认的增强解码只尝试单步变换。
+    pub fn composition_limit(&self) -> usize {
+        self.max_compositions.unwrap_or(if self.auto || self.profile.is_some() { DEFAULT_MAX_COMPOSITIONS } else { 0 })
+    }
 }
 
 /// 图像处理参数
@@ -245,6 +536,14 @@ pub struct ImageProcessingParams {
     pub blur_kernel_size: i32,
     /// 是否应用直方图均衡化
     pub histogram_equalization: bool,
+    /// 是否使用 CLAHE 代替全局直方图均衡化
+    pub clahe: bool,
+    /// CLAHE 限幅
+    pub clahe_clip_limit: f64,
+    /// CLAHE 网格大小
+    pub clahe_tile_size: i32,
+    /// 局部色调映射强度 (可选)
+    pub tone_mapping: Option<f64>,
     /// 是否应用自适应阈值
     pub adaptive_threshold: bool,
     /// 缩放因子
@@ -258,6 +557,10 @@ impl Default for ImageProcessingParams {
             gaussian_blur: true,
             blur_kernel_size: 3,
             histogram_equalization: true,
+            clahe: false,
+            clahe_clip_limit: DEFAULT_CLAHE_CLIP_LIMIT,
+            clahe_tile_size: DEFAULT_CLAHE_TILE_SIZE,
+            tone_mapping: None,
             adaptive_threshold: false,
             scale_factor: 1.0,
         }
@@ -301,6 +301,7 @@ mod tests {
             min_confidence: 0.5,
             save_processed: false,
             processed_output_path: None,
+            ..ProcessingConfig::default()
         }
     }
     